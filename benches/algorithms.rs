@@ -0,0 +1,756 @@
+//! Benchmarks for sorting and searching
+//!
+//! Runs on stable Rust without the unstable `test` crate. Every algorithm is
+//! timed over a sweep of input sizes, from 10 up to a million elements, and
+//! several input distributions from `kb::gen`. Lookups go up to ten million
+//! elements, with targets spread over the whole array, so that the input
+//! outgrows the cache. Substring searches run over texts of up to a million
+//! letters, against `str::find`.
+//!
+//! In one run, the Eytzinger layout from `kb::search::layout` took 21 ns per
+//! lookup at 10³ elements, 63 ns at 10⁶ and 465 ns at 10⁷, against 52 ns,
+//! 223 ns and 1074 ns for binary search. The B-tree layout took 26 ns, 89 ns
+//! and 1319 ns, so it was ahead up to a few million elements but behind at
+//! 10⁷.
+//!
+//! Sorting networks only sort arrays of a fixed length, so they are timed on
+//! eight elements alone, against insertion sort on the same arrays. The
+//! quicksorts are also timed on input built to make the median of three pivot
+//! quadratic, up to ten thousand elements.
+//!
+//! For each size the input is generated once, then sorted (or searched) many
+//! times, with a fresh copy prepared before the clock starts. The time of a
+//! batch of runs is divided by its length, and the median of several batches
+//! is reported.
+//!
+//! The growth exponent is fitted with a least squares line through the
+//! logarithms of the sizes and times, using sizes of at least 1000 so that
+//! constant overheads don't dominate. On the first distribution of each
+//! algorithm, which is uniform input for sorts, it is compared with the
+//! exponent of the documented average case over the same sizes. That is
+//! _1_ for _O(n)_, but a little more than _1_ for _O(n log(n))_, as
+//! _log(n)_ grows too.
+//!
+//! The times are also fitted against every model with `kb::complexity`, and
+//! the best fit is reported alongside. Times are noisy, so this is a hint
+//! rather than a check; the operation counts in the unit tests are exact.
+//!
+//! Results are written as CSV and JSON to `target/bench`, named after the
+//! current commit, so runs of different commits can be compared.
+//!
+//! ```text
+//! cargo bench                           # everything
+//! cargo bench -- quick                  # only names containing "quick"
+//! cargo bench -- --quick                # sizes up to 10⁴, fewer samples
+//! cargo bench -- --max-len 100000       # sizes up to 10⁵
+//! cargo bench -- --compare target/bench/abc1234.csv
+//! ```
+
+extern crate kb;
+extern crate rand;
+
+use std::env;
+use std::fs;
+use std::hint::black_box;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+use kb::complexity;
+use kb::gen;
+use kb::search::layout::{BTree, Eytzinger};
+use kb::search::{binary, exponential, fibonacci, interpolation, linear, ternary};
+use kb::sort::external::{ExternalSort, U64};
+use kb::sort::network::Network;
+use kb::sort::{self, bucket, insertion, merge, partial, radix, selection, Complexity};
+use kb::sortsearch::find_sum::{self, VecSet};
+use kb::string::search::{BoyerMoore, Horspool, Kmp, RabinKarp, Z};
+
+use rand::{Rng, SeedableRng, XorShiftRng};
+
+const SEED: u64 = 1;
+
+/// Lookups per run of a search benchmark
+const QUERIES: usize = 1000;
+
+/// The most elements held in a batch of prepared inputs
+const BATCH_ELEMENTS: usize = 1 << 24;
+
+/// The smallest size used to fit the growth exponent
+const FIT_MIN: usize = 1000;
+
+/// The length of the pattern in a substring search
+const PATTERN_LEN: usize = 16;
+
+/// The length of the arrays sorted by sorting networks
+const NETWORK_LEN: usize = 8;
+
+/// The largest input that sorts may take quadratic time on
+const QUADRATIC_MAX_LEN: usize = 10_000;
+
+/// How far a fitted exponent may be from the documented one
+const TOLERANCE: f64 = 0.25;
+
+/// Sizes grow by a factor of about √10, from 10 to 10⁷
+const SIZES: [usize; 13] = [
+    10, 32, 100, 316, 1_000, 3_162, 10_000, 31_623, 100_000, 316_228, 1_000_000, 3_162_278, 10_000_000,
+];
+
+/// Generates an input of a given length
+type Distribution = (&'static str, fn(usize) -> Vec<u32>);
+
+const DISTRIBUTIONS: [Distribution; 6] = [
+    ("uniform", uniform),
+    ("sorted", gen::sorted),
+    ("reversed", gen::reversed),
+    ("nearly sorted", nearly_sorted),
+    ("few unique", few_unique),
+    ("organ pipe", gen::organ_pipe),
+];
+
+fn uniform(n: usize) -> Vec<u32> {
+    gen::uniform(n, SEED)
+}
+
+fn nearly_sorted(n: usize) -> Vec<u32> {
+    gen::nearly_sorted(n, n / 100 + 1, SEED)
+}
+
+fn few_unique(n: usize) -> Vec<u32> {
+    gen::few_unique(n, 8, SEED)
+}
+
+const KILLER: Distribution = ("median of three killer", gen::median_of_three_killer);
+
+/// Runs an algorithm a number of times on copies of an input, returning the
+/// time taken
+type Bench = Box<dyn Fn(&[u32], usize) -> Duration>;
+
+/// An algorithm to benchmark
+struct Case {
+    group: &'static str,
+    name: String,
+    /// The documented growth of one run, as a function of the input size
+    expected: Complexity,
+    sizes: &'static [usize],
+    max_len: usize,
+    distributions: Vec<Distribution>,
+    /// Whether the documented complexity is checked against the first
+    /// distribution
+    checked: bool,
+    /// The number of lookups in a run, or `None` if a run processes each
+    /// element of the input
+    queries: Option<usize>,
+    bench: Bench,
+}
+
+impl Case {
+    fn new(group: &'static str, name: &str, expected: Complexity, bench: Bench) -> Self {
+        Case {
+            group,
+            name: name.to_string(),
+            expected,
+            sizes: &SIZES,
+            max_len: 1_000_000,
+            distributions: DISTRIBUTIONS.to_vec(),
+            checked: true,
+            queries: None,
+            bench,
+        }
+    }
+
+    fn max_len(mut self, max_len: usize) -> Self {
+        self.max_len = max_len;
+        self
+    }
+
+    /// Benchmarks input built to defeat the algorithm, where the documented
+    /// average case doesn't apply
+    fn adversary(mut self, distribution: Distribution) -> Self {
+        self.max_len = QUADRATIC_MAX_LEN;
+        self.distributions = vec![distribution];
+        self.checked = false;
+        self
+    }
+
+    /// Benchmarks arrays of `NETWORK_LEN` elements only
+    fn network(mut self) -> Self {
+        self.sizes = &[NETWORK_LEN];
+        self
+    }
+
+    /// Benchmarks lookups in sorted input
+    fn search(mut self) -> Self {
+        self.max_len = 10_000_000;
+        self.distributions = vec![("sorted", gen::sorted)];
+        self.queries = Some(QUERIES);
+        self
+    }
+
+    /// Benchmarks substring searches, in uniform text and text of a few
+    /// letters, where partial matches are common
+    fn text(mut self) -> Self {
+        self.distributions = vec![("uniform", uniform), ("few unique", few_unique)];
+        self
+    }
+
+    fn id(&self) -> String {
+        format!("{}/{}", self.group, self.name)
+    }
+}
+
+/// Prepares copies of an input, then times running `run` on each
+fn batch<T, R, F>(input: &T, iters: usize, mut run: F) -> Duration
+where
+    T: Clone,
+    F: FnMut(T) -> R
+{
+    let inputs: Vec<T> = (0..iters).map(|_| input.clone()).collect();
+    let mut outputs = Vec::with_capacity(iters);
+
+    let start = Instant::now();
+    for input in inputs {
+        outputs.push(run(black_box(input)));
+    }
+    let elapsed = start.elapsed();
+
+    black_box(outputs);
+    elapsed
+}
+
+/// Benchmarks an in-place sort, on input converted from `u32`
+fn sort_bench<T, P, S>(prepare: P, sort: S) -> Bench
+where
+    T: Clone + 'static,
+    P: Fn(&[u32]) -> Vec<T> + 'static,
+    S: Fn(&mut [T]) + 'static
+{
+    Box::new(move |input, iters| {
+        batch(&prepare(input), iters, |mut values| {
+            sort(&mut values);
+            values
+        })
+    })
+}
+
+/// Benchmarks a sort of an array of `NETWORK_LEN` elements
+fn array_bench<S>(sort: S) -> Bench
+where
+    S: Fn(&mut [u8; NETWORK_LEN]) + 'static
+{
+    Box::new(move |input, iters| {
+        let mut values = [0u8; NETWORK_LEN];
+        for (value, &input) in values.iter_mut().zip(input) {
+            *value = input as u8;
+        }
+
+        batch(&values, iters, |mut values| {
+            sort(&mut values);
+            values
+        })
+    })
+}
+
+/// Benchmarks lookups of `QUERIES` targets, about half of which are present
+fn search_bench<S>(search: S) -> Bench
+where
+    S: Fn(&[u32], &u32) -> Option<usize> + 'static
+{
+    lookup_bench(|values| values.to_vec(), move |values: &Vec<u32>, target| search(values, target))
+}
+
+/// Benchmarks lookups in a structure built from the sorted values, which
+/// isn't timed
+fn lookup_bench<L, B, S>(build: B, search: S) -> Bench
+where
+    B: Fn(&[u32]) -> L + 'static,
+    S: Fn(&L, &u32) -> Option<usize> + 'static
+{
+    Box::new(move |input, iters| {
+        // Even values are present, odd values are missing
+        let values: Vec<u32> = input.iter().map(|&value| 2 * value).collect();
+        // Targets are spread over the whole array, so lookups miss the cache
+        // once it outgrows it
+        let mut rng = XorShiftRng::from_seed([SEED as u32, 0x9e37_79b9, 0x7f4a_7c15, 0x6a09_e667]);
+        let targets: Vec<u32> = (0..QUERIES).map(|_| rng.gen_range(0, 2 * input.len().max(1) as u32)).collect();
+        let built = build(&values);
+
+        batch(&(), iters, |_| {
+            targets.iter().filter(|target| search(&built, target).is_some()).count()
+        })
+    })
+}
+
+/// Benchmarks counting the matches of a pattern from the middle of a text of
+/// lowercase letters, with the pattern preprocessed by `build` untimed
+fn string_bench<P, B, C>(build: B, count: C) -> Bench
+where
+    B: Fn(&str) -> P + 'static,
+    C: Fn(&P, &str) -> usize + 'static
+{
+    Box::new(move |input, iters| {
+        let text: String = input.iter().map(|&value| char::from(b'a' + (value % 26) as u8)).collect();
+        let start = text.len() / 2;
+        let pattern = build(&text[start..(start + PATTERN_LEN).min(text.len())]);
+
+        batch(&(), iters, |_| count(&pattern, &text))
+    })
+}
+
+fn cases() -> Vec<Case> {
+    let mut cases = Vec::new();
+
+    for sorter in sort::all_sorters::<u32>().into_iter().chain(sort::unsigned_sorters()) {
+        let (name, expected) = (sorter.name(), sorter.average_case());
+        let max_len = if expected == Complexity::Quadratic { QUADRATIC_MAX_LEN } else { 1_000_000 };
+        cases.push(Case::new("sort", name, expected, sort_bench(<[u32]>::to_vec, move |values| sorter.sort(values))).max_len(max_len));
+    }
+
+    for sorter in sort::all_sorters::<u32>().into_iter().filter(|sorter| sorter.name().starts_with("Quicksort")) {
+        let (name, expected) = (sorter.name(), sorter.average_case());
+        cases.push(Case::new("sort", name, expected, sort_bench(<[u32]>::to_vec, move |values| sorter.sort(values))).adversary(KILLER));
+    }
+
+    cases.push(Case::new(
+        "sort",
+        "Bucket Sort",
+        Complexity::Linear,
+        sort_bench(
+            |input| {
+                let scale = input.iter().max().map_or(1.0, |&max| f64::from(max) + 1.0);
+                input.iter().map(|&value| f64::from(value) / scale).collect()
+            },
+            bucket::bucket_sort,
+        ),
+    ));
+    cases.push(Case::new(
+        "sort",
+        "Radix Sort (LSD, byte strings)",
+        Complexity::Linear,
+        sort_bench(|input| input.iter().map(|value| format!("{:010}", value)).collect(), radix::lsd_bytes),
+    ));
+    cases.push(Case::new(
+        "sort",
+        "Radix Sort (MSD, strings)",
+        Complexity::Linear,
+        sort_bench(|input| input.iter().map(|value| format!("{:010}", value)).collect(), radix::msd),
+    ));
+    cases.push(Case::new(
+        "sort",
+        "Merge Sort (parallel)",
+        Complexity::Linearithmic,
+        sort_bench(<[u32]>::to_vec, |values| merge::par_merge_sort(values, merge::DEFAULT_GRAIN)),
+    ));
+    cases.push(Case::new(
+        "sort",
+        "External Sort",
+        Complexity::Linearithmic,
+        Box::new(|input, iters| {
+            let mut bytes = Vec::with_capacity(8 * input.len());
+            for &value in input {
+                bytes.extend_from_slice(&u64::from(value).to_le_bytes());
+            }
+
+            let sort = ExternalSort::new(U64, 10_000);
+            batch(&bytes, iters, |bytes| {
+                let mut output = Vec::with_capacity(bytes.len());
+                sort.sort(&bytes[..], &mut output).unwrap();
+                output
+            })
+        }),
+    ).max_len(100_000));
+    cases.push(Case::new(
+        "sort",
+        "Partial Sort (k = 10)",
+        Complexity::Linear,
+        sort_bench(<[u32]>::to_vec, |values| partial::partial_sort(values, 10)),
+    ));
+    cases.push(Case::new(
+        "sort",
+        "Sorted Iterator (first 10)",
+        Complexity::Linear,
+        Box::new(|input, iters| {
+            batch(&input.to_vec(), iters, |values| partial::sorted_iter(values).take(10).last())
+        }),
+    ));
+    cases.push(Case::new(
+        "sort",
+        "Select Median",
+        Complexity::Linear,
+        sort_bench(<[u32]>::to_vec, |values| {
+            selection::median(values);
+        }),
+    ));
+    cases.push(Case::new(
+        "sort",
+        "Top 10",
+        Complexity::Linear,
+        Box::new(|input, iters| batch(&input.to_vec(), iters, |values| selection::top_k(values, 10))),
+    ));
+
+    let bitonic = Network::bitonic(NETWORK_LEN);
+    let odd_even_merge = Network::odd_even_merge(NETWORK_LEN);
+    cases.push(Case::new("network", "Bitonic", Complexity::Constant, array_bench(move |values| bitonic.sort_array(values))).network());
+    cases.push(Case::new(
+        "network",
+        "Odd-Even Merge",
+        Complexity::Constant,
+        array_bench(move |values| odd_even_merge.sort_array(values)),
+    ).network());
+    cases.push(Case::new("network", "Insertion Sort", Complexity::Constant, array_bench(|values| insertion::clrs(values))).network());
+
+    cases.push(Case::new("search", "Binary Search", Complexity::Logarithmic, search_bench(binary::search)).search());
+    cases.push(Case::new(
+        "search",
+        "Binary Search (closest)",
+        Complexity::Logarithmic,
+        search_bench(|values, target| binary::search_closest(values, target).filter(|&i| values[i] == *target)),
+    ).search());
+    cases.push(Case::new(
+        "search",
+        "Lower Bound",
+        Complexity::Logarithmic,
+        search_bench(|values, target| {
+            let i = binary::lower_bound(values, target);
+            if values.get(i) == Some(target) { Some(i) } else { None }
+        }),
+    ).search());
+    cases.push(Case::new(
+        "search",
+        "Eytzinger Layout",
+        Complexity::Logarithmic,
+        lookup_bench(Eytzinger::new, Eytzinger::search),
+    ).search());
+    cases.push(Case::new("search", "B-tree Layout", Complexity::Logarithmic, lookup_bench(BTree::new, BTree::search)).search());
+    cases.push(Case::new("search", "Exponential Search", Complexity::Logarithmic, search_bench(exponential::search)).search());
+    cases.push(Case::new("search", "Fibonacci Search", Complexity::Logarithmic, search_bench(fibonacci::search)).search());
+    // O(lg(lg(n))) on this evenly spaced input, which is within O(lg(n))
+    cases.push(Case::new("search", "Interpolation Search", Complexity::Logarithmic, search_bench(interpolation::search)).search());
+    cases.push(Case::new("search", "Ternary Search", Complexity::Logarithmic, search_bench(ternary::search)).search());
+    cases.push(Case::new(
+        "search",
+        "Linear Search",
+        Complexity::Linear,
+        search_bench(linear::linear),
+    ).search().max_len(100_000));
+    cases.push(Case::new(
+        "search",
+        "Linear Search (chunked)",
+        Complexity::Linear,
+        search_bench(linear::chunked),
+    ).search().max_len(100_000));
+
+    cases.push(Case::new(
+        "string",
+        "str::find",
+        Complexity::Linear,
+        string_bench(str::to_string, |pattern, text| {
+            // Restarts one byte on, to count overlapping matches like the others
+            let (mut count, mut offset) = (0, 0);
+            while let Some(i) = text[offset..].find(pattern.as_str()) {
+                count += 1;
+                offset += i + 1;
+            }
+            count
+        }),
+    ).text());
+    cases.push(Case::new(
+        "string",
+        "Knuth-Morris-Pratt",
+        Complexity::Linear,
+        string_bench(Kmp::new, |kmp, text| kmp.find_iter(text).count()),
+    ).text());
+    cases.push(Case::new(
+        "string",
+        "Boyer-Moore",
+        Complexity::Linear,
+        string_bench(BoyerMoore::new, |bm, text| bm.find_iter(text).count()),
+    ).text());
+    cases.push(Case::new(
+        "string",
+        "Horspool",
+        Complexity::Linear,
+        string_bench(Horspool::new, |horspool, text| horspool.find_iter(text).count()),
+    ).text());
+    cases.push(Case::new(
+        "string",
+        "Rabin-Karp",
+        Complexity::Linear,
+        string_bench(RabinKarp::new, |rk, text| rk.find_iter(text).count()),
+    ).text());
+    cases.push(Case::new("string", "Z-algorithm", Complexity::Linear, string_bench(Z::new, |z, text| z.find_iter(text).count())).text());
+
+    cases.push(Case::new(
+        "sortsearch",
+        "Find Sum",
+        Complexity::Linearithmic,
+        Box::new(|input, iters| {
+            let values: Vec<i64> = input.iter().map(|&value| i64::from(value)).collect();
+
+            // No two elements sum to a negative target, so every element is checked
+            batch(&VecSet::new(&values), iters, |set| find_sum::find_sum(set, &-1))
+        }),
+    ));
+
+    cases
+}
+
+/// Settings from the command line
+struct Options {
+    filter: Option<String>,
+    max_len: usize,
+    sample: Duration,
+    samples: usize,
+    out: PathBuf,
+    compare: Option<PathBuf>,
+}
+
+impl Options {
+    fn parse() -> Self {
+        let mut options = Options {
+            filter: None,
+            max_len: usize::MAX,
+            sample: Duration::from_millis(10),
+            samples: 5,
+            out: PathBuf::from("target/bench"),
+            compare: None,
+        };
+
+        let mut args = env::args().skip(1);
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                // Passed by `cargo bench`
+                "--bench" => {}
+                "--quick" => {
+                    options.max_len = 10_000;
+                    options.sample = Duration::from_millis(2);
+                    options.samples = 3;
+                }
+                "--max-len" => options.max_len = args.next().and_then(|len| len.parse().ok()).expect("--max-len needs a number"),
+                "--out" => options.out = args.next().map(PathBuf::from).expect("--out needs a directory"),
+                "--compare" => options.compare = args.next().map(PathBuf::from),
+                _ => options.filter = Some(arg),
+            }
+        }
+
+        options
+    }
+}
+
+/// The timing of one case on one input
+struct Measurement {
+    id: String,
+    distribution: &'static str,
+    n: usize,
+    iters: usize,
+    ns_per_iter: f64,
+    ns_per_element: f64,
+}
+
+/// Times a case on an input, returning the batch size and the median time of
+/// one run in nanoseconds
+fn measure(case: &Case, input: &[u32], options: &Options) -> (usize, f64) {
+    // Warms up, and estimates how many runs fill a sample
+    let once = (case.bench)(input, 1).as_nanos().max(1);
+    let max_iters = (BATCH_ELEMENTS / input.len().max(1)).max(1);
+    let iters = ((options.sample.as_nanos() / once) as usize).clamp(1, max_iters);
+
+    let mut samples: Vec<f64> = (0..options.samples)
+        .map(|_| (case.bench)(input, iters).as_nanos() as f64 / iters as f64)
+        .collect();
+    samples.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    (iters, samples[samples.len() / 2])
+}
+
+/// The slope of the least squares line through a set of points
+fn slope(points: &[(f64, f64)]) -> f64 {
+    let n = points.len() as f64;
+    let (mean_x, mean_y) = points.iter().fold((0.0, 0.0), |(x, y), &(px, py)| (x + px / n, y + py / n));
+    let (covariance, variance) = points.iter().fold((0.0, 0.0), |(c, v), &(x, y)| {
+        (c + (x - mean_x) * (y - mean_y), v + (x - mean_x) * (x - mean_x))
+    });
+
+    covariance / variance
+}
+
+/// The fitted and documented growth exponents of a case on one distribution
+struct Fit {
+    id: String,
+    distribution: &'static str,
+    expected: Complexity,
+    exponent: f64,
+    expected_exponent: f64,
+    /// The model that best fits the times
+    best_fit: complexity::Fit,
+    /// Whether the documented complexity applies to this distribution
+    checked: bool,
+}
+
+impl Fit {
+    /// Whether the fit confirms the documented complexity, or `None` if it
+    /// isn't checked for this distribution
+    fn confirmed(&self) -> Option<bool> {
+        if !self.checked { return None }
+        Some((self.exponent - self.expected_exponent).abs() <= TOLERANCE)
+    }
+}
+
+fn fit(case: &Case, distribution: &'static str, measurements: &[Measurement]) -> Option<Fit> {
+    let points: Vec<(usize, f64)> = measurements
+        .iter()
+        .filter(|m| m.distribution == distribution && m.n >= FIT_MIN)
+        .map(|m| (m.n, m.ns_per_iter))
+        .collect();
+    if points.len() < 3 { return None }
+
+    let log = |points: &[(usize, f64)], f: &dyn Fn(usize, f64) -> f64| -> Vec<(f64, f64)> {
+        points.iter().map(|&(n, y)| ((n as f64).ln(), f(n, y).ln())).collect()
+    };
+
+    Some(Fit {
+        id: case.id(),
+        distribution,
+        expected: case.expected,
+        exponent: slope(&log(&points, &|_, y| y)),
+        expected_exponent: slope(&log(&points, &|n, _| case.expected.cost(n as f64))),
+        best_fit: complexity::fit(&points),
+        checked: case.checked && distribution == case.distributions[0].0,
+    })
+}
+
+/// Names results after the current commit, if there is one
+fn label() -> String {
+    Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|commit| commit.trim().to_string())
+        .unwrap_or_else(|| "latest".to_string())
+}
+
+fn csv(measurements: &[Measurement]) -> String {
+    let mut csv = String::from("id,distribution,n,iterations,ns_per_iter,ns_per_element\n");
+    for m in measurements {
+        csv.push_str(&format!("{},{},{},{},{:.1},{:.3}\n", m.id, m.distribution, m.n, m.iters, m.ns_per_iter, m.ns_per_element));
+    }
+
+    csv
+}
+
+fn json(label: &str, measurements: &[Measurement], fits: &[Fit]) -> String {
+    let results: Vec<String> = measurements
+        .iter()
+        .map(|m| format!(
+            "    {{\"id\": {:?}, \"distribution\": {:?}, \"n\": {}, \"iterations\": {}, \"ns_per_iter\": {:.1}, \"ns_per_element\": {:.3}}}",
+            m.id, m.distribution, m.n, m.iters, m.ns_per_iter, m.ns_per_element,
+        ))
+        .collect();
+    let fits: Vec<String> = fits
+        .iter()
+        .map(|f| format!(
+            "    {{\"id\": {:?}, \"distribution\": {:?}, \"expected\": {:?}, \"exponent\": {:.3}, \"expected_exponent\": {:.3}, \"best_fit\": {:?}, \"confidence\": {:.3}, \"confirmed\": {}}}",
+            f.id,
+            f.distribution,
+            f.expected.to_string(),
+            f.exponent,
+            f.expected_exponent,
+            f.best_fit.complexity.to_string(),
+            f.best_fit.confidence,
+            f.confirmed().map_or("null".to_string(), |confirmed| confirmed.to_string()),
+        ))
+        .collect();
+
+    format!(
+        "{{\n  \"commit\": {:?},\n  \"results\": [\n{}\n  ],\n  \"fits\": [\n{}\n  ]\n}}\n",
+        label,
+        results.join(",\n"),
+        fits.join(",\n"),
+    )
+}
+
+/// Prints the change in time of each result also in an earlier CSV file
+fn compare(path: &Path, measurements: &[Measurement]) {
+    let previous = match fs::read_to_string(path) {
+        Ok(previous) => previous,
+        Err(err) => return eprintln!("can't read {}: {}", path.display(), err),
+    };
+
+    println!("\nCompared with {}:", path.display());
+    for line in previous.lines().skip(1) {
+        let fields: Vec<&str> = line.split(',').collect();
+        if fields.len() < 5 { continue }
+
+        let (id, distribution, n) = (fields[0], fields[1], fields[2]);
+        let before: f64 = match fields[4].parse() {
+            Ok(before) => before,
+            Err(_) => continue,
+        };
+
+        if let Some(m) = measurements.iter().find(|m| m.id == id && m.distribution == distribution && m.n.to_string() == n) {
+            println!("{:<45} {:<14} {:>9} {:>+8.1}%", id, distribution, n, (m.ns_per_iter / before - 1.0) * 100.0);
+        }
+    }
+}
+
+fn main() {
+    let options = Options::parse();
+    let cases: Vec<Case> = cases()
+        .into_iter()
+        .filter(|case| options.filter.as_ref().is_none_or(|filter| case.id().to_lowercase().contains(&filter.to_lowercase())))
+        .collect();
+
+    let mut measurements = Vec::new();
+    let mut fits = Vec::new();
+    println!("{:<45} {:<14} {:>9} {:>14} {:>12}", "algorithm", "distribution", "n", "ns/iter", "ns/element");
+
+    for case in &cases {
+        for &(distribution, generate) in &case.distributions {
+            let start = measurements.len();
+            for &n in case.sizes.iter().filter(|&&n| n <= case.max_len && n <= options.max_len) {
+                let input = generate(n);
+                let (iters, ns_per_iter) = measure(case, &input, &options);
+                let ns_per_element = ns_per_iter / case.queries.unwrap_or(n) as f64;
+                println!("{:<45} {:<14} {:>9} {:>14.1} {:>12.3}", case.id(), distribution, n, ns_per_iter, ns_per_element);
+
+                measurements.push(Measurement { id: case.id(), distribution, n, iters, ns_per_iter, ns_per_element });
+            }
+
+            if let Some(fit) = fit(case, distribution, &measurements[start..]) {
+                fits.push(fit);
+            }
+        }
+    }
+
+    println!("\n{:<45} {:<14} {:>16} {:>9} {:>9} {:>16}", "algorithm", "distribution", "documented", "expected", "fitted", "best fit");
+    for fit in &fits {
+        let verdict = match fit.confirmed() {
+            Some(true) => "ok",
+            Some(false) => "differs",
+            None => "",
+        };
+        println!(
+            "{:<45} {:<14} {:>16} {:>9.2} {:>9.2} {:>16} {}",
+            fit.id,
+            fit.distribution,
+            fit.expected.to_string(),
+            fit.expected_exponent,
+            fit.exponent,
+            fit.best_fit.complexity.to_string(),
+            verdict,
+        );
+    }
+
+    let label = label();
+    fs::create_dir_all(&options.out).expect("can't create the output directory");
+    for (extension, contents) in &[("csv", csv(&measurements)), ("json", json(&label, &measurements, &fits))] {
+        let path = options.out.join(format!("{}.{}", label, extension));
+        fs::write(&path, contents).expect("can't write results");
+        println!("\nWrote {}", path.display());
+    }
+
+    if let Some(ref path) = options.compare {
+        compare(path, &measurements);
+    }
+}
@@ -0,0 +1,223 @@
+//! Empirical complexity analysis
+//!
+//! Documentation can claim that an algorithm takes _O(n²)_ time, but only a
+//! measurement can check it. The cost of running the algorithm, as a count of
+//! operations or a time, is measured on inputs of geometrically increasing
+//! size, then fitted against each candidate model.
+//!
+//! Each model _f_ is fitted as _cost ≈ c·f(n)_, choosing _c_ to minimise the
+//! squared *relative* error. Absolute errors would let the largest input
+//! drown out the rest. Lower order terms, such as the _-n/2_ in the _n(n-1)/2_
+//! comparisons of insertion sort, are small relative to the leading term and
+//! barely affect the fit, so they don't hide the growth rate.
+//!
+//! The model with the smallest error is the best fit. Its *confidence* is how
+//! much smaller that error is than the runner-up's, from _0_ when they are
+//! equally good to _1_ when the best fit is exact.
+//!
+//! Operation counts are deterministic, so they fit far more cleanly than times
+//! do. Sizes spanning a factor of 32 or more are enough to tell _O(n)_ from
+//! _O(n log(n))_. The exponential model overflows past _n = 1023_, so it needs
+//! small, linearly increasing sizes instead.
+//!
+//! ```
+//! use kb::complexity::{self, Complexity};
+//!
+//! let sizes = complexity::geometric(16, 2, 8);
+//! let fit = complexity::fit(&complexity::measure(&sizes, |n| (n * (n - 1) / 2) as f64));
+//!
+//! assert_eq!(fit.complexity, Complexity::Quadratic);
+//! assert!(fit.confidence > 0.9);
+//! ```
+
+use std::fmt;
+use std::time::Instant;
+
+/// The growth rate of a cost as a function of the input size _n_
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Complexity {
+    Constant,
+    Logarithmic,
+    Linear,
+    /// Linear in both the size and the range _k_ of the keys, as in counting
+    /// sort
+    LinearPlusRange,
+    Linearithmic,
+    Quadratic,
+    Cubic,
+    Exponential,
+}
+
+impl Complexity {
+    /// Every model of the size alone, from slowest growing to fastest
+    pub const ALL: [Complexity; 7] = [
+        Complexity::Constant,
+        Complexity::Logarithmic,
+        Complexity::Linear,
+        Complexity::Linearithmic,
+        Complexity::Quadratic,
+        Complexity::Cubic,
+        Complexity::Exponential,
+    ];
+
+    /// The cost of an input of size `n` under this model, up to a constant
+    /// factor
+    pub fn cost(self, n: f64) -> f64 {
+        match self {
+            Complexity::Constant => 1.0,
+            Complexity::Logarithmic => n.log2().max(1.0),
+            Complexity::Linear => n,
+            // Taking k to be O(n), which is when it is worth counting
+            Complexity::LinearPlusRange => n,
+            Complexity::Linearithmic => n * n.log2().max(1.0),
+            Complexity::Quadratic => n * n,
+            Complexity::Cubic => n * n * n,
+            Complexity::Exponential => n.exp2(),
+        }
+    }
+}
+
+impl fmt::Display for Complexity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let notation = match *self {
+            Complexity::Constant => "O(1)",
+            Complexity::Logarithmic => "O(lg(n))",
+            Complexity::Linear => "O(n)",
+            Complexity::LinearPlusRange => "O(n + k)",
+            Complexity::Linearithmic => "O(n log(n))",
+            Complexity::Quadratic => "O(n²)",
+            Complexity::Cubic => "O(n³)",
+            Complexity::Exponential => "O(2ⁿ)",
+        };
+
+        f.write_str(notation)
+    }
+}
+
+/// The result of fitting costs against every model
+#[derive(Clone, Debug, PartialEq)]
+pub struct Fit {
+    /// The model that fits best
+    pub complexity: Complexity,
+    /// The constant factor _c_ of the best fit
+    pub coefficient: f64,
+    /// The root mean square relative error of the best fit
+    pub error: f64,
+    /// How much better the best fit is than the next best, from _0_ to _1_
+    pub confidence: f64,
+}
+
+/// Returns `count` sizes, starting at `start` and multiplying by `factor`
+pub fn geometric(start: usize, factor: usize, count: usize) -> Vec<usize> {
+    (0..count as u32).map(|i| start * factor.pow(i)).collect()
+}
+
+/// Measures the cost at each size
+pub fn measure<F>(sizes: &[usize], mut cost: F) -> Vec<(usize, f64)>
+where
+    F: FnMut(usize) -> f64
+{
+    sizes.iter().map(|&n| (n, cost(n))).collect()
+}
+
+/// Returns the time taken to run a function, in nanoseconds
+///
+/// To measure time rather than operations, pass this to `measure`. It is best
+/// to take the minimum of a few runs, as noise only ever adds time.
+pub fn time<F>(run: F) -> f64
+where
+    F: FnOnce()
+{
+    let start = Instant::now();
+    run();
+    start.elapsed().as_nanos() as f64
+}
+
+/// Fits the costs against a single model, returning the coefficient and the
+/// root mean square relative error
+///
+/// Minimising _Σ(1 - c·f(n)/y)²_ gives _c = Σ(f/y) / Σ(f/y)²_.
+pub fn fit_model(samples: &[(usize, f64)], complexity: Complexity) -> (f64, f64) {
+    let ratios: Vec<f64> = samples.iter().map(|&(n, y)| complexity.cost(n as f64) / y).collect();
+    if ratios.iter().any(|ratio| !ratio.is_finite()) { return (f64::NAN, f64::INFINITY) }
+
+    let coefficient = ratios.iter().sum::<f64>() / ratios.iter().map(|r| r * r).sum::<f64>();
+    let error = ratios.iter().map(|r| (1.0 - coefficient * r).powi(2)).sum::<f64>() / ratios.len() as f64;
+    (coefficient, error.sqrt())
+}
+
+/// Finds the model that best fits a set of costs
+///
+/// # Panics
+///
+/// Panics if there are fewer than two samples, or any cost isn't positive.
+pub fn fit(samples: &[(usize, f64)]) -> Fit {
+    assert!(samples.len() >= 2, "need at least two samples to fit");
+    assert!(samples.iter().all(|&(_, y)| y > 0.0), "costs must be positive");
+
+    let mut fits: Vec<(Complexity, f64, f64)> = Complexity::ALL
+        .iter()
+        .map(|&complexity| {
+            let (coefficient, error) = fit_model(samples, complexity);
+            (complexity, coefficient, error)
+        })
+        .collect();
+
+    // Ties go to the slower growing model, which is listed first
+    fits.sort_by(|a, b| a.2.partial_cmp(&b.2).unwrap());
+    let (complexity, coefficient, error) = fits[0];
+    let confidence = if fits[1].2 > 0.0 { 1.0 - error / fits[1].2 } else { 0.0 };
+
+    Fit { complexity, coefficient, error, confidence }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fit_exact() {
+        let sizes = geometric(16, 2, 8);
+        for &complexity in &Complexity::ALL[..6] {
+            let fit = fit(&measure(&sizes, |n| 3.0 * complexity.cost(n as f64)));
+            assert_eq!(fit.complexity, complexity);
+            assert!((fit.coefficient - 3.0).abs() < 1e-9, "{}", fit.coefficient);
+            assert!(fit.error < 1e-9 && fit.confidence > 0.99, "{:?}", fit);
+        }
+
+        let sizes: Vec<usize> = (10..30).collect();
+        assert_eq!(fit(&measure(&sizes, |n| (n as f64).exp2())).complexity, Complexity::Exponential);
+    }
+
+    #[test]
+    fn test_fit_lower_order_terms() {
+        let sizes = geometric(32, 2, 7);
+        let fits = |cost: fn(f64) -> f64| fit(&measure(&sizes, |n| cost(n as f64))).complexity;
+
+        assert_eq!(fits(|n| 2.0 * n + 5.0), Complexity::Linear);
+        assert_eq!(fits(|n| n * n.log2() - n + 1.0), Complexity::Linearithmic);
+        assert_eq!(fits(|n| n * (n - 1.0) / 2.0), Complexity::Quadratic);
+        assert_eq!(fits(|n| n.log2() + 1.0), Complexity::Logarithmic);
+        assert_eq!(fits(|_| 7.0), Complexity::Constant);
+    }
+
+    #[test]
+    fn test_fit_noise() {
+        // Alternately 10% over and under
+        let sizes = geometric(64, 2, 8);
+        let fit = fit(&measure(&sizes, |n| n as f64 * n as f64 * if n % 256 == 0 { 1.1 } else { 0.9 }));
+        assert_eq!(fit.complexity, Complexity::Quadratic);
+        assert!(fit.confidence > 0.5 && fit.confidence < 1.0, "{:?}", fit);
+    }
+
+    #[test]
+    fn test_time() {
+        assert!(time(|| ()) >= 0.0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_fit_too_few() {
+        fit(&[(1, 1.0)]);
+    }
+}
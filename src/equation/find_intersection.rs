@@ -29,7 +29,7 @@ where
         i += 0.01;
     }
 
-    (*results.get(0).unwrap_or(&None), *results.get(1).unwrap_or(&None))
+    (*results.first().unwrap_or(&None), *results.get(1).unwrap_or(&None))
 }
 
 #[cfg(test)]
@@ -0,0 +1,323 @@
+//! Input generators for tests and benchmarks
+//!
+//! Each generator builds a `Vec` of _n_ numbers with a particular shape.
+//! Algorithms that behave the same on every input are rare: insertion sort
+//! is linear on nearly sorted input, quicksort with a fixed pivot is
+//! quadratic on sorted input, and three-way partitioning shines when there
+//! are few unique values. Sharing these shapes lets tests and benchmarks
+//! across the crate cover the same cases.
+//!
+//! Generators work in terms of *ranks* in _[0, n)_, which are converted to the
+//! element type with `Numeric`. Types too small to hold every rank have them
+//! scaled down, which keeps the order but introduces duplicates.
+//!
+//! The random generators take a seed, and always return the same values for
+//! the same seed, so failures and benchmark results are reproducible.
+//!
+//! ```
+//! use kb::gen;
+//!
+//! let values: Vec<u32> = gen::nearly_sorted(8, 1, 42);
+//! assert_eq!(values, gen::nearly_sorted::<u32>(8, 1, 42));
+//! assert_eq!(gen::organ_pipe::<u8>(6), [0, 1, 2, 2, 1, 0]);
+//! ```
+
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::rc::Rc;
+
+use rand::{Rng, SeedableRng, XorShiftRng};
+
+use search::binary;
+use sort::quick;
+
+/// A number that can be built from a rank
+pub trait Numeric: Copy + PartialOrd {
+    /// Converts `rank`, one of `ranks` possible ranks, keeping their order
+    fn from_rank(rank: u64, ranks: u64) -> Self;
+}
+
+macro_rules! impl_numeric_int {
+    ($($t:ty),*) => {$(
+        impl Numeric for $t {
+            fn from_rank(rank: u64, ranks: u64) -> Self {
+                let max = <$t>::MAX as u64;
+                if ranks <= 1 || ranks - 1 <= max {
+                    rank as $t
+                } else {
+                    (u128::from(rank) * u128::from(max) / u128::from(ranks - 1)) as $t
+                }
+            }
+        }
+    )*};
+}
+
+impl_numeric_int!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize);
+
+impl Numeric for f32 {
+    fn from_rank(rank: u64, _: u64) -> Self {
+        rank as f32
+    }
+}
+
+impl Numeric for f64 {
+    fn from_rank(rank: u64, _: u64) -> Self {
+        rank as f64
+    }
+}
+
+/// Creates a generator from a seed
+///
+/// `XorShiftRng` can't be seeded with all zeros, so the seed is mixed with
+/// constants first.
+fn rng(seed: u64) -> XorShiftRng {
+    XorShiftRng::from_seed([
+        seed as u32 ^ 0x9e37_79b9,
+        (seed >> 32) as u32 ^ 0x7f4a_7c15,
+        0x6a09_e667,
+        0xbb67_ae85,
+    ])
+}
+
+/// Converts ranks in _[0, n)_ to values
+fn values<T, I>(ranks: I, n: usize) -> Vec<T>
+where
+    T: Numeric,
+    I: IntoIterator<Item = u64>
+{
+    ranks.into_iter().map(|rank| T::from_rank(rank, n as u64)).collect()
+}
+
+/// Values chosen uniformly at random
+pub fn uniform<T: Numeric>(n: usize, seed: u64) -> Vec<T> {
+    let mut rng = rng(seed);
+    values((0..n).map(|_| rng.gen_range(0, n as u64)), n)
+}
+
+/// Distinct values in increasing order
+pub fn sorted<T: Numeric>(n: usize) -> Vec<T> {
+    values(0..n as u64, n)
+}
+
+/// Distinct values in decreasing order
+pub fn reversed<T: Numeric>(n: usize) -> Vec<T> {
+    values((0..n as u64).rev(), n)
+}
+
+/// Sorted values with `swaps` random pairs swapped
+pub fn nearly_sorted<T: Numeric>(n: usize, swaps: usize, seed: u64) -> Vec<T> {
+    let mut values = sorted(n);
+    if n < 2 { return values }
+
+    let mut rng = rng(seed);
+    for _ in 0..swaps {
+        let (i, j) = (rng.gen_range(0, n), rng.gen_range(0, n));
+        values.swap(i, j);
+    }
+
+    values
+}
+
+/// Values chosen uniformly at random from `k` distinct values spread over the
+/// ranks
+pub fn few_unique<T: Numeric>(n: usize, k: usize, seed: u64) -> Vec<T> {
+    let mut rng = rng(seed);
+    let k = k.max(1) as u64;
+    values((0..n).map(|_| rng.gen_range(0, k) * n as u64 / k), n)
+}
+
+/// Runs of `period` increasing values, one after another
+pub fn sawtooth<T: Numeric>(n: usize, period: usize) -> Vec<T> {
+    let period = period.max(1) as u64;
+    values((0..n as u64).map(|i| i % period), n)
+}
+
+/// Values that increase to the middle, then decrease back again
+pub fn organ_pipe<T: Numeric>(n: usize) -> Vec<T> {
+    values((0..n).map(|i| i.min(n - 1 - i) as u64), n)
+}
+
+/// Values following Zipf's law, where rank _r_ is chosen with probability
+/// proportional to _1/(r + 1)ˢ_
+///
+/// A few small values are very common and most are rare, like the words of a
+/// natural language or the popularity of web pages. A larger `exponent`
+/// makes the distribution more skewed.
+pub fn zipf<T: Numeric>(n: usize, exponent: f64, seed: u64) -> Vec<T> {
+    let mut total = 0.0;
+    let cumulative: Vec<f64> = (0..n)
+        .map(|r| {
+            total += 1.0 / ((r + 1) as f64).powf(exponent);
+            total
+        })
+        .collect();
+
+    let mut rng = rng(seed);
+    let ranks = (0..n).map(|_| {
+        let u = rng.gen::<f64>() * total;
+        let rank = binary::lower_bound_by(&cumulative, |c| if *c <= u { Ordering::Less } else { Ordering::Greater });
+        rank.min(n - 1) as u64
+    });
+
+    values(ranks, n)
+}
+
+/// Values that drive `quick::median_of_three` to quadratic time
+pub fn median_of_three_killer<T: Numeric>(n: usize) -> Vec<T> {
+    adversary(n, quick::median_of_three)
+}
+
+/// Builds an input that makes a comparison sort do as much work as possible,
+/// with McIlroy's adversary
+///
+/// The sort is run on _n_ elements whose values haven't been decided yet,
+/// which are *gas*. Gas compares greater than any decided value. When two gas
+/// elements are compared, one of them is *frozen* at the next smallest value.
+/// The adversary prefers to freeze the element most recently compared against
+/// a frozen one, which is likely the pivot, so every partition splits off as
+/// little as possible. Any gas left at the end is given the same value.
+///
+/// The answers are consistent with the final values, so a deterministic sort
+/// repeats the same comparisons when given them.
+///
+/// See McIlroy, "A Killer Adversary for Quicksort", 1999.
+pub fn adversary<T, F>(n: usize, sort: F) -> Vec<T>
+where
+    T: Numeric,
+    F: FnOnce(&mut [Gas])
+{
+    let state = Rc::new(RefCell::new(Adversary {
+        values: vec![None; n],
+        frozen: 0,
+        candidate: 0,
+    }));
+
+    let mut elements: Vec<Gas> = (0..n).map(|id| Gas { id, state: state.clone() }).collect();
+    sort(&mut elements);
+
+    let state = state.borrow();
+    values(state.values.iter().map(|value| value.unwrap_or(state.frozen) as u64), n + 1)
+}
+
+/// An element whose value is decided as it is compared, for `adversary`
+#[derive(Clone)]
+pub struct Gas {
+    id: usize,
+    state: Rc<RefCell<Adversary>>,
+}
+
+struct Adversary {
+    /// The value each element is frozen at, or `None` for gas
+    values: Vec<Option<usize>>,
+    frozen: usize,
+    candidate: usize,
+}
+
+impl Adversary {
+    fn compare(&mut self, a: usize, b: usize) -> Ordering {
+        if self.values[a].is_none() && self.values[b].is_none() {
+            let freeze = if a == self.candidate { a } else { b };
+            self.values[freeze] = Some(self.frozen);
+            self.frozen += 1;
+        }
+
+        if self.values[a].is_none() {
+            self.candidate = a;
+        } else if self.values[b].is_none() {
+            self.candidate = b;
+        }
+
+        let gas = self.values.len();
+        self.values[a].unwrap_or(gas).cmp(&self.values[b].unwrap_or(gas))
+    }
+}
+
+impl Ord for Gas {
+    fn cmp(&self, other: &Gas) -> Ordering {
+        self.state.borrow_mut().compare(self.id, other.id)
+    }
+}
+
+impl PartialOrd for Gas {
+    fn partial_cmp(&self, other: &Gas) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Gas {
+    fn eq(&self, other: &Gas) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Gas {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sort::instrument::Metrics;
+    use sort::Sorter;
+
+    fn is_sorted<T: PartialOrd>(values: &[T]) -> bool {
+        values.windows(2).all(|pair| pair[0] <= pair[1])
+    }
+
+    #[test]
+    fn test_seeded() {
+        assert_eq!(uniform::<u32>(100, 7), uniform::<u32>(100, 7));
+        assert_ne!(uniform::<u32>(100, 7), uniform::<u32>(100, 8));
+        assert_eq!(zipf::<u32>(100, 1.0, 7), zipf::<u32>(100, 1.0, 7));
+        assert!(uniform::<u64>(1000, 0).iter().all(|&value| value < 1000));
+    }
+
+    #[test]
+    fn test_numeric() {
+        assert_eq!(sorted::<u8>(4), [0, 1, 2, 3]);
+        assert_eq!(sorted::<f64>(3), [0.0, 1.0, 2.0]);
+        assert_eq!(reversed::<i32>(3), [2, 1, 0]);
+
+        // Ranks are scaled down to fit, keeping their order
+        let values = sorted::<u8>(1000);
+        assert_eq!((values[0], values[999]), (0, 255));
+        assert!(is_sorted(&values));
+    }
+
+    #[test]
+    fn test_shapes() {
+        let values: Vec<u32> = nearly_sorted(100, 3, 1);
+        assert!(values.iter().enumerate().filter(|&(i, &value)| value != i as u32).count() <= 6);
+
+        let mut values: Vec<u32> = few_unique(100, 4, 1);
+        values.sort();
+        values.dedup();
+        assert!(values.len() <= 4);
+
+        assert_eq!(sawtooth::<u8>(7, 3), [0, 1, 2, 0, 1, 2, 0]);
+        assert_eq!(organ_pipe::<u8>(5), [0, 1, 2, 1, 0]);
+        assert_eq!(organ_pipe::<u8>(0), []);
+    }
+
+    #[test]
+    fn test_zipf() {
+        let values: Vec<usize> = zipf(1000, 1.0, 3);
+        let count = |rank| values.iter().filter(|&&value| value == rank).count();
+        assert!(count(0) > count(1) && count(1) > count(9));
+        assert!(values.iter().all(|&value| value < 1000));
+    }
+
+    #[test]
+    fn test_median_of_three_killer() {
+        let comparisons = |values: &[u32]| {
+            let mut values = values.to_vec();
+            let mut metrics = Metrics::default();
+            quick::MedianOfThree.sort_instrumented(&mut values, &mut metrics);
+            assert!(is_sorted(&values));
+            metrics.comparisons
+        };
+
+        let n = 1000;
+        let killer = comparisons(&median_of_three_killer(n));
+        assert!(killer > n * n / 8, "{} comparisons", killer);
+        assert!(comparisons(&uniform(n, 1)) < n * n / 50);
+    }
+}
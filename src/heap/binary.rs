@@ -0,0 +1,565 @@
+//! Binary heap
+//!
+//! A binary heap is a slice that can be viewed as a nearly complete binary
+//! tree. Each element is a node of the tree. The tree is completely filled on
+//! all levels except possibly the lowest, which is filled from the left.
+//!
+//! The root of the tree is `values[0]`. Given the index `i` of a node, the
+//! indices of its parent and children are calculated as:
+//!
+//! ```text
+//! parent(i) = (i - 1) / 2
+//! left(i)   = 2i + 1
+//! right(i)  = 2i + 2
+//! ```
+//!
+//! CLRS indexes from one, which gives the slightly simpler _i/2_, _2i_ and
+//! _2i + 1_.
+//!
+//! In a *max-heap*, the *max-heap property* is that every node other than the
+//! root is less than or equal to its parent, so the largest element is at the
+//! root. A *min-heap* is the mirror image, with the smallest element at the
+//! root.
+//!
+//! The functions in this module keep the max-heap property with respect to a
+//! comparator. Reversing the comparator gives a min-heap.
+//!
+//! ```text
+//!                  16
+//!            /            \
+//!          14              10
+//!        /    \          /    \
+//!       8      7        9      3
+//!      / \    /
+//!     2   4  1
+//!
+//! +----+----+----+---+---+---+---+---+---+---+
+//! | 16 | 14 | 10 | 8 | 7 | 9 | 3 | 2 | 4 | 1 |
+//! +----+----+----+---+---+---+---+---+---+---+
+//! ```
+//!
+//! The height of a heap of _n_ elements is _⌊lg(n)⌋_, so sifting an element
+//! up or down takes _O(lg(n))_ time.
+
+use std::cmp::Ordering;
+
+/// Returns the index of the parent of node `i`
+///
+/// The root has no parent, so `i` must be greater than zero.
+pub fn parent(i: usize) -> usize {
+    (i - 1) / 2
+}
+
+/// Returns the index of the left child of node `i`
+pub fn left(i: usize) -> usize {
+    2 * i + 1
+}
+
+/// Returns the index of the right child of node `i`
+pub fn right(i: usize) -> usize {
+    2 * i + 2
+}
+
+/// Restores the max-heap property downwards from node `i`
+///
+/// This is `MAX-HEAPIFY` in CLRS. It assumes that the subtrees rooted at the
+/// children of `i` are max-heaps, but that `values[i]` might be smaller than
+/// its children. The larger child is swapped with `values[i]`, which fixes
+/// node `i` but might violate the property in the subtree the value moved
+/// into, so the process continues from there until the value is not smaller
+/// than either of its children, or is a leaf.
+pub fn sift_down<T>(values: &mut [T], i: usize)
+where
+    T: Ord
+{
+    sift_down_by(values, i, T::cmp);
+}
+
+/// Restores the max-heap property downwards from node `i`, with respect to a
+/// comparator function
+pub fn sift_down_by<T, F>(values: &mut [T], i: usize, mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering
+{
+    sift_down_with(
+        values,
+        i,
+        &mut |values: &[T], a, b| compare(&values[a], &values[b]),
+        &mut |values: &mut [T], a, b| values.swap(a, b),
+    );
+}
+
+/// Restores the max-heap property upwards from node `i`
+///
+/// This is the loop within `MAX-HEAP-INCREASE-KEY` in CLRS. It assumes that
+/// the rest of the slice is a max-heap, but that `values[i]` might be larger
+/// than its parent. The two are swapped, and the process continues from the
+/// parent until the value is not larger than its parent, or is the root.
+pub fn sift_up<T>(values: &mut [T], i: usize)
+where
+    T: Ord
+{
+    sift_up_by(values, i, T::cmp);
+}
+
+/// Restores the max-heap property upwards from node `i`, with respect to a
+/// comparator function
+pub fn sift_up_by<T, F>(values: &mut [T], i: usize, mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering
+{
+    sift_up_with(
+        values,
+        i,
+        &mut |values: &[T], a, b| compare(&values[a], &values[b]),
+        &mut |values: &mut [T], a, b| values.swap(a, b),
+    );
+}
+
+/// Rearranges a slice into a max-heap in _O(n)_ time
+///
+/// This is `BUILD-MAX-HEAP` in CLRS. The elements in `values[n/2..]` are all
+/// leaves, so each is already a one-element heap. The remaining nodes are
+/// sifted down in reverse order, so the subtrees of each node are heaps by
+/// the time it is reached.
+///
+/// Sifting down node `i` costs _O(h)_, where _h_ is its height. There are at
+/// most _⌈n/2ʰ⁺¹⌉_ nodes of height _h_, so the total cost is bounded by
+/// _O(n Σ h/2ʰ)_, and that sum converges to 2. This is linear rather than the
+/// _O(n lg(n))_ of pushing each element one at a time.
+///
+/// # Loop invariant
+///
+/// At the start of each iteration of the for loop, each node `i + 1`,
+/// `i + 2`, ..., `n - 1` is the root of a max-heap.
+///
+/// *Initialization*: `i` starts at `n/2 - 1`, and every node after it is a
+/// leaf, which is a trivial max-heap.
+///
+/// *Maintenance*: The children of node `i` have greater indices, so they are
+/// both roots of max-heaps. That is the condition `sift_down` needs to make
+/// `i` the root of a max-heap too.
+///
+/// *Termination*: `i` is zero when the final iteration ends, so every node,
+/// including the root, is the root of a max-heap.
+pub fn build_heap<T>(values: &mut [T])
+where
+    T: Ord
+{
+    build_heap_by(values, T::cmp);
+}
+
+/// Rearranges a slice into a max-heap with respect to a comparator function
+pub fn build_heap_by<T, F>(values: &mut [T], mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering
+{
+    for i in (0..values.len() / 2).rev() {
+        sift_down_by(values, i, &mut compare);
+    }
+}
+
+/// Checks whether a slice satisfies the max-heap property
+pub fn is_heap<T>(values: &[T]) -> bool
+where
+    T: Ord
+{
+    is_heap_by(values, T::cmp)
+}
+
+/// Checks whether a slice satisfies the max-heap property with respect to a
+/// comparator function
+pub fn is_heap_by<T, F>(values: &[T], mut compare: F) -> bool
+where
+    F: FnMut(&T, &T) -> Ordering
+{
+    (1..values.len()).all(|i| compare(&values[parent(i)], &values[i]) != Ordering::Less)
+}
+
+/// Sifts node `i` down, comparing and swapping elements by their indices
+///
+/// Taking indices rather than elements lets callers track where elements
+/// move, such as the handles of a `BinaryHeap` or the instrumented heapsort.
+pub(crate) fn sift_down_with<T, C, S>(values: &mut [T], mut i: usize, compare: &mut C, swap: &mut S)
+where
+    C: FnMut(&[T], usize, usize) -> Ordering,
+    S: FnMut(&mut [T], usize, usize)
+{
+    loop {
+        let (l, r) = (left(i), right(i));
+        let mut largest = i;
+        if l < values.len() && compare(values, l, largest) == Ordering::Greater {
+            largest = l;
+        }
+        if r < values.len() && compare(values, r, largest) == Ordering::Greater {
+            largest = r;
+        }
+
+        if largest == i { break; }
+
+        swap(values, i, largest);
+        i = largest;
+    }
+}
+
+fn sift_up_with<T, C, S>(values: &mut [T], mut i: usize, compare: &mut C, swap: &mut S)
+where
+    C: FnMut(&[T], usize, usize) -> Ordering,
+    S: FnMut(&mut [T], usize, usize)
+{
+    while i > 0 && compare(values, parent(i), i) == Ordering::Less {
+        swap(values, i, parent(i));
+        i = parent(i);
+    }
+}
+
+/// Which end of the ordering is kept at the root of a heap
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Orientation {
+    /// The largest element is at the root
+    Max,
+    /// The smallest element is at the root
+    Min,
+}
+
+/// Refers to an element pushed onto a `BinaryHeap`
+///
+/// Handles stay valid as the element moves around the heap, so its key can be
+/// changed later on. Once the element is extracted, its slot is reused by a
+/// later push, but under a new generation, so the old handle doesn't refer to
+/// the new element.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Handle {
+    slot: usize,
+    generation: usize,
+}
+
+/// Where the element of a handle is in the heap
+#[derive(Clone, Copy, Debug)]
+struct Slot {
+    /// The index of the element, or `None` if the slot is free
+    position: Option<usize>,
+    /// How many times the slot has been freed
+    generation: usize,
+}
+
+/// A priority queue backed by a binary heap
+///
+/// Each element is stored alongside the slot of its handle, and the position
+/// of each slot's element is tracked as it moves, so that `increase_key` and
+/// `decrease_key` can find it in constant time. The slots of extracted
+/// elements are kept on a free list for reuse, so a long-lived queue holds no
+/// more slots than it has ever held elements at once.
+pub struct BinaryHeap<T> {
+    orientation: Orientation,
+    entries: Vec<(T, usize)>,
+    slots: Vec<Slot>,
+    free: Vec<usize>,
+    /// The number of elements the heap was created with
+    initial: usize,
+}
+
+impl<T: Ord> BinaryHeap<T> {
+    /// Creates an empty heap
+    pub fn new(orientation: Orientation) -> Self {
+        BinaryHeap {
+            orientation,
+            entries: Vec::new(),
+            slots: Vec::new(),
+            free: Vec::new(),
+            initial: 0,
+        }
+    }
+
+    /// Creates a heap from a vector in _O(n)_ time
+    ///
+    /// The handle of each element is the same as its index in `values`, which
+    /// can be turned into a `Handle` with `handle`.
+    pub fn from_vec(values: Vec<T>, orientation: Orientation) -> Self {
+        let mut heap = BinaryHeap {
+            orientation,
+            slots: (0..values.len()).map(|i| Slot { position: Some(i), generation: 0 }).collect(),
+            free: Vec::new(),
+            initial: values.len(),
+            entries: values.into_iter().enumerate().map(|(slot, value)| (value, slot)).collect(),
+        };
+
+        for i in (0..heap.entries.len() / 2).rev() {
+            heap.sift_down(i);
+        }
+
+        heap
+    }
+
+    /// Creates a heap from a slice in _O(n)_ time
+    pub fn from_slice(values: &[T], orientation: Orientation) -> Self
+    where
+        T: Clone
+    {
+        Self::from_vec(values.to_vec(), orientation)
+    }
+
+    /// Returns the handle of the element that was at `index` when the heap was
+    /// created with `from_vec` or `from_slice`
+    ///
+    /// Returns `None` if there was no such element. Elements pushed later only
+    /// have the handles returned by `push`. Once the element has been
+    /// extracted, the handle no longer refers to anything.
+    pub fn handle(&self, index: usize) -> Option<Handle> {
+        if index < self.initial { Some(Handle { slot: index, generation: 0 }) } else { None }
+    }
+
+    pub fn orientation(&self) -> Orientation {
+        self.orientation
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the element at the root without removing it
+    pub fn peek(&self) -> Option<&T> {
+        self.entries.first().map(|entry| &entry.0)
+    }
+
+    /// Returns the element referred to by a handle, if it is still in the heap
+    pub fn get(&self, handle: Handle) -> Option<&T> {
+        self.position(handle).map(|i| &self.entries[i].0)
+    }
+
+    /// Adds an element in _O(lg(n))_ time, returning its handle
+    pub fn push(&mut self, value: T) -> Handle {
+        let slot = match self.free.pop() {
+            Some(slot) => slot,
+            None => {
+                self.slots.push(Slot { position: None, generation: 0 });
+                self.slots.len() - 1
+            }
+        };
+
+        self.slots[slot].position = Some(self.entries.len());
+        self.entries.push((value, slot));
+
+        let last = self.entries.len() - 1;
+        self.sift_up(last);
+        Handle { slot, generation: self.slots[slot].generation }
+    }
+
+    /// Removes the element at the root in _O(lg(n))_ time
+    ///
+    /// This is the largest element of a max-heap, or the smallest element of
+    /// a min-heap.
+    pub fn extract(&mut self) -> Option<T> {
+        if self.entries.is_empty() { return None }
+
+        let last = self.entries.len() - 1;
+        self.swap(0, last);
+        let (value, slot) = self.entries.pop().unwrap();
+        self.slots[slot].position = None;
+        self.slots[slot].generation = self.slots[slot].generation.wrapping_add(1);
+        self.free.push(slot);
+        self.sift_down(0);
+
+        Some(value)
+    }
+
+    /// Replaces the element referred to by a handle with a larger one
+    ///
+    /// If the handle's element has already been extracted, or `key` is
+    /// smaller than it, `key` is returned as an error.
+    pub fn increase_key(&mut self, handle: Handle, key: T) -> Result<(), T> {
+        self.change_key(handle, key, Ordering::Less)
+    }
+
+    /// Replaces the element referred to by a handle with a smaller one
+    ///
+    /// If the handle's element has already been extracted, or `key` is
+    /// larger than it, `key` is returned as an error.
+    pub fn decrease_key(&mut self, handle: Handle, key: T) -> Result<(), T> {
+        self.change_key(handle, key, Ordering::Greater)
+    }
+
+    /// Removes every element, in the order they would be extracted
+    pub fn into_sorted_vec(mut self) -> Vec<T> {
+        let mut sorted = Vec::with_capacity(self.len());
+        while let Some(value) = self.extract() {
+            sorted.push(value);
+        }
+
+        sorted
+    }
+
+    fn change_key(&mut self, handle: Handle, key: T, invalid: Ordering) -> Result<(), T> {
+        let i = match self.position(handle) {
+            Some(i) => i,
+            None => return Err(key),
+        };
+
+        if key.cmp(&self.entries[i].0) == invalid { return Err(key) }
+
+        self.entries[i].0 = key;
+        self.sift_up(i);
+        let i = self.position(handle).unwrap();
+        self.sift_down(i);
+        Ok(())
+    }
+
+    fn position(&self, handle: Handle) -> Option<usize> {
+        self.slots
+            .get(handle.slot)
+            .filter(|slot| slot.generation == handle.generation)
+            .and_then(|slot| slot.position)
+    }
+
+    fn swap(&mut self, a: usize, b: usize) {
+        swap_entries(&mut self.slots, &mut self.entries, a, b);
+    }
+
+    fn sift_up(&mut self, i: usize) {
+        let orientation = self.orientation;
+        let slots = &mut self.slots;
+        sift_up_with(
+            &mut self.entries,
+            i,
+            &mut |entries: &[(T, usize)], a, b| orient(orientation, entries[a].0.cmp(&entries[b].0)),
+            &mut |entries: &mut [(T, usize)], a, b| swap_entries(slots, entries, a, b),
+        );
+    }
+
+    fn sift_down(&mut self, i: usize) {
+        let orientation = self.orientation;
+        let slots = &mut self.slots;
+        sift_down_with(
+            &mut self.entries,
+            i,
+            &mut |entries: &[(T, usize)], a, b| orient(orientation, entries[a].0.cmp(&entries[b].0)),
+            &mut |entries: &mut [(T, usize)], a, b| swap_entries(slots, entries, a, b),
+        );
+    }
+}
+
+fn orient(orientation: Orientation, ordering: Ordering) -> Ordering {
+    match orientation {
+        Orientation::Max => ordering,
+        Orientation::Min => ordering.reverse(),
+    }
+}
+
+fn swap_entries<T>(slots: &mut [Slot], entries: &mut [(T, usize)], a: usize, b: usize) {
+    entries.swap(a, b);
+    slots[entries[a].1].position = Some(a);
+    slots[entries[b].1].position = Some(b);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_heap() {
+        let mut values = [4, 1, 3, 2, 16, 9, 10, 14, 8, 7];
+        build_heap(&mut values);
+        assert_eq!(values, [16, 14, 10, 8, 7, 9, 3, 2, 4, 1]);
+        assert!(is_heap(&values));
+
+        let mut values = [4, 1, 3, 2, 16, 9, 10, 14, 8, 7];
+        build_heap_by(&mut values, |a, b| b.cmp(a));
+        assert!(is_heap_by(&values, |a, b| b.cmp(a)));
+        assert!(!is_heap(&values));
+    }
+
+    #[test]
+    fn test_sift_down() {
+        let mut values = [16, 4, 10, 14, 7, 9, 3, 2, 8, 1];
+        sift_down(&mut values, 1);
+        assert_eq!(values, [16, 14, 10, 8, 7, 9, 3, 2, 4, 1]);
+    }
+
+    #[test]
+    fn test_sift_up() {
+        let mut values = [16, 14, 10, 8, 7, 9, 3, 2, 15, 1];
+        sift_up(&mut values, 8);
+        assert_eq!(values, [16, 15, 10, 14, 7, 9, 3, 2, 8, 1]);
+    }
+
+    #[test]
+    fn test_binary_heap() {
+        let mut heap = BinaryHeap::from_slice(&[4, 1, 3, 2, 16, 9, 10, 14, 8, 7], Orientation::Max);
+        assert_eq!(heap.len(), 10);
+        assert_eq!(heap.peek(), Some(&16));
+        heap.push(11);
+        assert_eq!(heap.into_sorted_vec(), [16, 14, 11, 10, 9, 8, 7, 4, 3, 2, 1]);
+
+        let mut heap = BinaryHeap::new(Orientation::Min);
+        for &value in &[5, 2, 4, 6, 1, 3] {
+            heap.push(value);
+        }
+        assert_eq!(heap.extract(), Some(1));
+        assert_eq!(heap.extract(), Some(2));
+        assert_eq!(heap.into_sorted_vec(), [3, 4, 5, 6]);
+
+        let mut empty: BinaryHeap<u8> = BinaryHeap::new(Orientation::Max);
+        assert!(empty.is_empty());
+        assert_eq!(empty.extract(), None);
+    }
+
+    #[test]
+    fn test_change_key() {
+        let mut heap = BinaryHeap::new(Orientation::Max);
+        let a = heap.push(5);
+        let b = heap.push(3);
+        let c = heap.push(4);
+
+        assert_eq!(heap.increase_key(b, 9), Ok(()));
+        assert_eq!(heap.peek(), Some(&9));
+        assert_eq!(heap.get(b), Some(&9));
+
+        assert_eq!(heap.decrease_key(b, 1), Ok(()));
+        assert_eq!(heap.peek(), Some(&5));
+        assert_eq!(heap.increase_key(c, 2), Err(2));
+        assert_eq!(heap.decrease_key(c, 7), Err(7));
+
+        assert_eq!(heap.extract(), Some(5));
+        assert_eq!(heap.get(a), None);
+        assert_eq!(heap.increase_key(a, 10), Err(10));
+        assert_eq!(heap.into_sorted_vec(), [4, 1]);
+
+        let mut heap = BinaryHeap::from_vec(vec![5, 8, 2], Orientation::Min);
+        let eight = heap.handle(1).unwrap();
+        assert_eq!(heap.decrease_key(eight, 0), Ok(()));
+        assert_eq!(heap.extract(), Some(0));
+        assert_eq!(heap.handle(3), None);
+
+        // Pushed elements aren't at an index the heap was created with
+        let pushed = heap.push(9);
+        assert_eq!(heap.handle(3), None);
+        assert_eq!(heap.get(pushed), Some(&9));
+        assert_eq!(heap.handle(1).and_then(|handle| heap.get(handle)), None);
+        assert_eq!(heap.handle(2).and_then(|handle| heap.get(handle)), Some(&2));
+    }
+
+    #[test]
+    fn test_reuses_slots() {
+        let mut heap = BinaryHeap::new(Orientation::Max);
+        let first = heap.push(0);
+        for value in 1..1000 {
+            heap.push(value);
+            assert_eq!(heap.extract(), Some(value));
+        }
+
+        // The first element keeps its slot, and the rest share one
+        assert_eq!(heap.slots.len(), 2);
+        assert_eq!(heap.get(first), Some(&0));
+
+        heap.extract();
+        let second = heap.push(5);
+        assert_eq!(second.slot, first.slot);
+        assert_eq!(heap.get(first), None);
+        assert_eq!(heap.increase_key(first, 7), Err(7));
+        assert_eq!(heap.get(second), Some(&5));
+    }
+}
@@ -0,0 +1,3 @@
+//! Heaps
+
+pub mod binary;
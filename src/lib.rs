@@ -4,9 +4,13 @@
 //! wizardry.
 
 extern crate num;
+extern crate rand;
 
 pub mod bit;
+pub mod complexity;
 pub mod equation;
+pub mod gen;
+pub mod heap;
 pub mod math;
 pub mod search;
 pub mod sort;
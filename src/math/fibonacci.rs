@@ -17,6 +17,12 @@ impl<T: Unsigned> FibonacciSequence<T> {
     }
 }
 
+impl<T: Unsigned> Default for FibonacciSequence<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<T: Unsigned + Copy> Iterator for FibonacciSequence<T> {
     type Item = T;
 
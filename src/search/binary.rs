@@ -2,8 +2,47 @@
 //!
 //! The search space is halved after every iteration, hence we have a running
 //! time of _O(lg(n))_.
+//!
+//! `search` returns the index of any element equal to the target, which is
+//! arbitrary when there are duplicates. The bounds are precise:
+//!
+//! - `lower_bound` is the first element not less than the target,
+//! - `upper_bound` is the first element greater than the target,
+//! - `equal_range` is the range between the two,
+//! - `partition_point` generalises them all to any predicate that is true for
+//!   a prefix of the slice.
+//!
+//! ```
+//! use kb::search::binary;
+//!
+//! let values = [10, 20, 20, 20, 30];
+//! assert_eq!(binary::lower_bound(&values, &20), 1);
+//! assert_eq!(binary::upper_bound(&values, &20), 4);
+//! assert_eq!(binary::equal_range(&values, &25), 4..4);
+//! assert_eq!(binary::partition_point(&values, |&value| value < 30), 4);
+//! ```
+//!
+//! # Searching for answers
+//!
+//! The slice isn't needed at all if the answer to "is _x_ big enough?" can be
+//! computed directly, and never changes from yes back to no as _x_ grows.
+//! `bisect_int` finds the smallest integer that is big enough, and
+//! `bisect_f64` narrows down a real number to within a tolerance.
+//!
+//! ```
+//! use kb::search::binary;
+//!
+//! // The fewest servers, at 350 requests per second each, for 10⁴ requests per second
+//! assert_eq!(binary::bisect_int(1u32, 1000, |servers| servers * 350 >= 10_000), Some(29));
+//!
+//! let root = binary::bisect_f64(0.0, 2.0, |x| x * x >= 2.0, 1e-12).unwrap();
+//! assert!((root - 2f64.sqrt()).abs() < 1e-12);
+//! ```
 
 use std::cmp::Ordering;
+use std::ops::Range;
+
+use num::PrimInt;
 
 /// Returns the index of a target value if it appears in a sorted slice
 pub fn search<T>(values: &[T], target: &T) -> Option<usize>
@@ -12,7 +51,7 @@ where
 {
     let (mut lo, mut hi) = (0, values.len());
     while lo < hi {
-        let mid = (lo + hi) / 2;
+        let mid = lo + (hi - lo) / 2;
         match values[mid].cmp(target) {
             Ordering::Equal => return Some(mid),
             Ordering::Less => lo = mid + 1,
@@ -28,25 +67,40 @@ where
 /// The ugly if/else at the end is required to set the index to the _following_
 /// index if the current mid is _less than_ the target value. The return index
 /// must always be equal to or greater than the target, or `None`.
+///
+/// Among duplicates, the index is arbitrary. `lower_bound` is the first of
+/// them, and returns the length rather than `None` past the end.
 pub fn search_closest<T>(values: &[T], target: &T) -> Option<usize>
 where
     T: Ord
+{
+    search_closest_by(values, |value| value.cmp(target))
+}
+
+/// Returns the index of a target value, or the following index if not found,
+/// using a comparator function
+///
+/// The comparator returns the ordering of an element relative to the target,
+/// like `slice::binary_search_by`.
+pub fn search_closest_by<T, F>(values: &[T], mut compare: F) -> Option<usize>
+where
+    F: FnMut(&T) -> Ordering
 {
     if values.is_empty() { return None }
 
     let (mut lo, mut hi) = (0, values.len());
-    let mut mid = (lo + hi) / 2;
+    let mut mid = lo + (hi - lo) / 2;
     while lo < hi {
-        mid = (lo + hi) / 2;
-        match values[mid].cmp(target) {
+        mid = lo + (hi - lo) / 2;
+        match compare(&values[mid]) {
             Ordering::Equal => return Some(mid),
             Ordering::Less => lo = mid + 1,
             Ordering::Greater => hi = mid,
         }
     }
 
-    if &values[mid] < target {
-        if let Some(_) = values.get(mid + 1) {
+    if compare(&values[mid]) == Ordering::Less {
+        if values.get(mid + 1).is_some() {
             Some(mid + 1)
         } else {
             None
@@ -56,6 +110,244 @@ where
     }
 }
 
+/// Returns the index of the first element that is not less than `target`
+///
+/// Every element before the returned index is less than `target`, so it is
+/// where `target` could be inserted before any elements equal to it.
+pub fn lower_bound<T>(values: &[T], target: &T) -> usize
+where
+    T: Ord
+{
+    lower_bound_by(values, |value| value.cmp(target))
+}
+
+/// Returns the index of the first element that is not less than the target,
+/// using a comparator function
+///
+/// The comparator returns the ordering of an element relative to the target,
+/// like `slice::binary_search_by`.
+pub fn lower_bound_by<T, F>(values: &[T], mut compare: F) -> usize
+where
+    F: FnMut(&T) -> Ordering
+{
+    partition_point(values, |value| compare(value) == Ordering::Less)
+}
+
+/// Returns the index of the first element whose key is not less than `key`
+pub fn lower_bound_by_key<T, K, F>(values: &[T], key: &K, mut f: F) -> usize
+where
+    K: Ord,
+    F: FnMut(&T) -> K
+{
+    lower_bound_by(values, |value| f(value).cmp(key))
+}
+
+/// Returns the index of the first element that is greater than `target`
+///
+/// Every element before the returned index is less than or equal to
+/// `target`, so it is where `target` could be inserted after any elements
+/// equal to it.
+pub fn upper_bound<T>(values: &[T], target: &T) -> usize
+where
+    T: Ord
+{
+    upper_bound_by(values, |value| value.cmp(target))
+}
+
+/// Returns the index of the first element that is greater than the target,
+/// using a comparator function
+///
+/// The comparator returns the ordering of an element relative to the target.
+pub fn upper_bound_by<T, F>(values: &[T], mut compare: F) -> usize
+where
+    F: FnMut(&T) -> Ordering
+{
+    upper_bound_by_index(values.len(), |i| compare(&values[i]))
+}
+
+/// Returns the first index in `0..len` whose element is greater than the
+/// target, using a comparator function that is given the index of the element
+///
+/// This is for callers that need to know which element is being compared,
+/// such as an instrumented sort reporting its comparisons.
+pub fn upper_bound_by_index<F>(len: usize, mut compare: F) -> usize
+where
+    F: FnMut(usize) -> Ordering
+{
+    let (mut lo, mut hi) = (0, len);
+    while lo < hi {
+        // Can't overflow, unlike (lo + hi) / 2
+        let mid = lo + (hi - lo) / 2;
+        if compare(mid) == Ordering::Greater { hi = mid } else { lo = mid + 1 }
+    }
+
+    lo
+}
+
+/// Returns the index of the first element whose key is greater than `key`
+pub fn upper_bound_by_key<T, K, F>(values: &[T], key: &K, mut f: F) -> usize
+where
+    K: Ord,
+    F: FnMut(&T) -> K
+{
+    upper_bound_by(values, |value| f(value).cmp(key))
+}
+
+/// Returns the range of elements equal to `target`
+///
+/// The range is empty if there are none, and starts where `target` could be
+/// inserted. It is the same as `lower_bound(values, target)..upper_bound(values,
+/// target)`, but the two searches share their steps until they first land on
+/// an equal element.
+pub fn equal_range<T>(values: &[T], target: &T) -> Range<usize>
+where
+    T: Ord
+{
+    equal_range_by(values, |value| value.cmp(target))
+}
+
+/// Returns the range of elements equal to the target, using a comparator
+/// function
+///
+/// The comparator returns the ordering of an element relative to the target.
+pub fn equal_range_by<T, F>(values: &[T], mut compare: F) -> Range<usize>
+where
+    F: FnMut(&T) -> Ordering
+{
+    let (mut lo, mut hi) = (0, values.len());
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        match compare(&values[mid]) {
+            Ordering::Less => lo = mid + 1,
+            Ordering::Greater => hi = mid,
+            Ordering::Equal => {
+                // The range starts in values[lo..=mid] and ends in values[mid + 1..hi]
+                let start = lo + lower_bound_by(&values[lo..mid], &mut compare);
+                let end = mid + 1 + upper_bound_by(&values[mid + 1..hi], &mut compare);
+                return start..end;
+            }
+        }
+    }
+
+    lo..lo
+}
+
+/// Returns the range of elements whose key is equal to `key`
+pub fn equal_range_by_key<T, K, F>(values: &[T], key: &K, mut f: F) -> Range<usize>
+where
+    K: Ord,
+    F: FnMut(&T) -> K
+{
+    equal_range_by(values, |value| f(value).cmp(key))
+}
+
+/// Returns the index of the first element for which `pred` is false
+///
+/// The slice must be partitioned by `pred`: true for every element before
+/// some index, and false for every element from it onwards. Every other
+/// search here is this one with a different predicate. If the slice isn't
+/// partitioned, the result is some index where `pred` changes from true to
+/// false, or an end of the slice.
+///
+/// # Loop invariant
+///
+/// At the start of each iteration, `pred` is true for every element of
+/// `values[0..lo]`, and false for every element of `values[hi..n]`. The
+/// partition point is in _[lo, hi]_.
+///
+/// *Initialization*: To begin with, *lo = 0* and *hi = n*, so both slices are
+/// empty and the partition point could be anywhere in _[0, n]_.
+///
+/// *Maintenance*: `mid` is in _[lo, hi)_. If `pred` is true for `values[mid]`,
+/// it is true for everything before it, so *lo = mid + 1* keeps the
+/// invariant. Otherwise it is false for everything from `mid` onwards, so
+/// *hi = mid* does. Either way, _[lo, hi)_ gets smaller, at least halving.
+///
+/// *Termination*: The loop terminates when *lo = hi*, after at most
+/// _⌈lg(n + 1)⌉_ iterations. `pred` is true for all of `values[0..lo]` and
+/// false for all of `values[lo..n]`, so `lo` is the partition point.
+pub fn partition_point<T, F>(values: &[T], mut pred: F) -> usize
+where
+    F: FnMut(&T) -> bool
+{
+    let (mut lo, mut hi) = (0, values.len());
+    while lo < hi {
+        // Can't overflow, unlike (lo + hi) / 2
+        let mid = lo + (hi - lo) / 2;
+        if pred(&values[mid]) { lo = mid + 1 } else { hi = mid }
+    }
+
+    lo
+}
+
+/// Returns the smallest integer in `[lo, hi]` for which `pred` is true, or
+/// `None` if there isn't one
+///
+/// `pred` must be monotone: false up to some integer, and true from then on.
+/// The whole range of the type can be searched, as the midpoint never
+/// overflows.
+///
+/// # Loop invariant
+///
+/// At the start of each iteration, `pred` is false for every integer before
+/// `lo`, and true for `hi` if it is true for any integer in the range.
+///
+/// *Initialization*: To begin with, `lo` is the start of the range, and `hi`
+/// is the end, which is true if anything is.
+///
+/// *Maintenance*: `mid` is in _[lo, hi)_. If `pred` is true for it, so is
+/// *hi = mid*. Otherwise, it is false for everything up to `mid`, and
+/// *lo = mid + 1*, which can't overflow as *mid < hi*.
+///
+/// *Termination*: The loop terminates when *lo = hi*, and `lo` is the answer
+/// if `pred` is true for it.
+pub fn bisect_int<T, F>(mut lo: T, mut hi: T, mut pred: F) -> Option<T>
+where
+    T: PrimInt,
+    F: FnMut(T) -> bool
+{
+    if lo > hi { return None }
+
+    while lo < hi {
+        let mid = midpoint(lo, hi);
+        if pred(mid) { hi = mid } else { lo = mid + T::one() }
+    }
+
+    if pred(lo) { Some(lo) } else { None }
+}
+
+/// Returns the smallest number in `[lo, hi]` for which `pred` is true, to
+/// within `tolerance`, or `None` if `pred` isn't true for `hi`
+///
+/// `pred` must be monotone, as for `bisect_int`. The result is the upper end
+/// of the final interval, so `pred` is always true for it. The search also
+/// stops if the interval can't be split any further, so a tolerance smaller
+/// than the gap between neighbouring `f64`s still terminates.
+pub fn bisect_f64<F>(mut lo: f64, mut hi: f64, mut pred: F, tolerance: f64) -> Option<f64>
+where
+    F: FnMut(f64) -> bool
+{
+    if lo > hi || !pred(hi) { return None }
+
+    while hi - lo > tolerance {
+        // Halving first can't overflow to infinity, unlike (lo + hi) / 2
+        let mid = lo / 2.0 + hi / 2.0;
+        if mid <= lo || mid >= hi { break }
+
+        if pred(mid) { hi = mid } else { lo = mid }
+    }
+
+    Some(hi)
+}
+
+/// The midpoint of two integers, rounded down, without overflowing
+///
+/// The bits both have in common are in the sum twice, and the bits only one
+/// has are in it once, so the sum is _2(lo & hi) + (lo ^ hi)_.
+fn midpoint<T: PrimInt>(lo: T, hi: T) -> T {
+    (lo & hi) + ((lo ^ hi) >> 1)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -75,4 +367,143 @@ mod tests {
         assert_eq!(search_closest(&[1, 3, 4], &2), Some(1));
         assert_eq!(search_closest(&[1, 2, 4], &3), Some(2));
     }
+
+    #[test]
+    fn test_search_closest_by() {
+        let values = [(1, 'a'), (3, 'b'), (4, 'c')];
+        assert_eq!(search_closest_by(&values, |&(k, _)| k.cmp(&3)), Some(1));
+        assert_eq!(search_closest_by(&values, |&(k, _)| k.cmp(&2)), Some(1));
+        assert_eq!(search_closest_by(&values, |&(k, _)| k.cmp(&5)), None);
+    }
+
+    #[test]
+    fn test_bounds_by() {
+        let values = [1, 2, 2, 2, 3];
+        assert_eq!(lower_bound_by(&values, |v| v.cmp(&2)), 1);
+        assert_eq!(upper_bound_by(&values, |v| v.cmp(&2)), 4);
+        assert_eq!(lower_bound_by(&values, |v| v.cmp(&0)), 0);
+        assert_eq!(upper_bound_by(&values, |v| v.cmp(&4)), 5);
+        assert_eq!(lower_bound_by(&[0; 0], |v: &i32| v.cmp(&1)), 0);
+    }
+
+    #[test]
+    fn test_bounds_by_key() {
+        let values = [(1, 'a'), (2, 'b'), (2, 'c'), (3, 'd')];
+        assert_eq!(lower_bound_by_key(&values, &2, |&(k, _)| k), 1);
+        assert_eq!(upper_bound_by_key(&values, &2, |&(k, _)| k), 3);
+        assert_eq!(equal_range_by_key(&values, &2, |&(k, _)| k), 1..3);
+        assert_eq!(equal_range_by_key(&values, &4, |&(k, _)| k), 4..4);
+    }
+
+    #[test]
+    fn test_equal_range() {
+        let values = [1, 2, 2, 2, 3, 5, 5];
+        assert_eq!(equal_range(&values, &2), 1..4);
+        assert_eq!(equal_range(&values, &5), 5..7);
+        assert_eq!(equal_range(&values, &4), 5..5);
+        assert_eq!(equal_range(&values, &0), 0..0);
+        assert_eq!(equal_range(&[0; 0], &1), 0..0);
+        assert_eq!(equal_range(&[7; 9], &7), 0..9);
+    }
+
+    #[test]
+    fn test_partition_point() {
+        let values = [1, 3, 5, 7, 2, 4];
+        assert_eq!(partition_point(&values, |v| v % 2 == 1), 4);
+        assert_eq!(partition_point(&values, |_| true), 6);
+        assert_eq!(partition_point(&values, |_| false), 0);
+        assert_eq!(partition_point(&[0; 0], |_: &i32| true), 0);
+    }
+
+    #[test]
+    fn test_bounds_generated() {
+        use gen;
+
+        let mut values: Vec<u32> = gen::few_unique(300, 40, 2);
+        values.sort();
+        for target in 0..310 {
+            let lower = values.iter().take_while(|&&value| value < target).count();
+            let upper = values.iter().take_while(|&&value| value <= target).count();
+            assert_eq!(lower_bound(&values, &target), lower);
+            assert_eq!(upper_bound(&values, &target), upper);
+            assert_eq!(equal_range(&values, &target), lower..upper);
+            assert_eq!(lower, values.partition_point(|&value| value < target));
+        }
+    }
+
+    #[test]
+    fn test_bisect_int() {
+        // The integer square root of n is one less than the smallest x with x² > n
+        for n in 0..200u64 {
+            let root = bisect_int(0, n + 1, |x| x * x > n).unwrap() - 1;
+            assert!(root * root <= n && (root + 1) * (root + 1) > n, "{}", n);
+        }
+
+        assert_eq!(bisect_int(0u8, 255, |x| x >= 200), Some(200));
+        assert_eq!(bisect_int(0u8, 255, |x| x == 255), Some(255));
+        assert_eq!(bisect_int(0u8, 255, |_| false), None);
+        assert_eq!(bisect_int(5, 3, |_| true), None);
+        assert_eq!(bisect_int(7, 7, |_| true), Some(7));
+    }
+
+    #[test]
+    fn test_bisect_int_extremes() {
+        assert_eq!(bisect_int(i64::MIN, i64::MAX, |x| x >= -3), Some(-3));
+        assert_eq!(bisect_int(i64::MIN, i64::MAX, |_| true), Some(i64::MIN));
+        assert_eq!(bisect_int(i64::MIN, i64::MAX, |x| x == i64::MAX), Some(i64::MAX));
+        assert_eq!(bisect_int(0, u64::MAX, |x| x > u64::MAX - 2), Some(u64::MAX - 1));
+
+        assert_eq!(midpoint(i32::MIN, i32::MAX), -1);
+        assert_eq!(midpoint(u32::MAX - 1, u32::MAX), u32::MAX - 1);
+        assert_eq!(midpoint(-5, -2), -4);
+    }
+
+    #[test]
+    fn test_bisect_f64() {
+        let cbrt = bisect_f64(0.0, 10.0, |x| x * x * x >= 5.0, 1e-10).unwrap();
+        assert!((cbrt - 5f64.cbrt()).abs() < 1e-9, "{}", cbrt);
+        assert!(cbrt.powi(3) >= 5.0);
+
+        assert_eq!(bisect_f64(0.0, 1.0, |x| x > 2.0, 1e-6), None);
+        assert_eq!(bisect_f64(-1.0, 1.0, |_| true, 1e-3).map(|x| x <= -0.999), Some(true));
+
+        // A tolerance below the precision of f64 still terminates
+        let x = bisect_f64(1.0, 2.0, |x| x >= 1.5, 0.0).unwrap();
+        assert_eq!(x, 1.5);
+        let x = bisect_f64(f64::MIN, f64::MAX, |x| x >= 1e300, 0.0).unwrap();
+        assert_eq!(x, 1e300);
+    }
+
+    #[test]
+    fn test_search_generated() {
+        use gen;
+
+        let mut values: Vec<u32> = gen::few_unique(200, 50, 1);
+        values.sort();
+        for target in 0..210 {
+            let expected = values.iter().position(|&value| value >= target);
+            assert_eq!(search(&values, &target).is_some(), expected.map(|i| values[i]) == Some(target));
+            assert_eq!(search_closest(&values, &target).map(|i| values[i]), expected.map(|i| values[i]));
+        }
+    }
+
+    #[test]
+    fn test_complexity() {
+        use complexity::{self, Complexity};
+        use gen;
+
+        // Missing targets take the most comparisons
+        let sizes = complexity::geometric(16, 4, 6);
+        let fit = complexity::fit(&complexity::measure(&sizes, |n| {
+            let values: Vec<u32> = gen::sorted(n);
+            let mut comparisons = 0;
+            lower_bound_by(&values, |value| {
+                comparisons += 1;
+                value.cmp(&(n as u32))
+            });
+            comparisons as f64
+        }));
+
+        assert_eq!(fit.complexity, Complexity::Logarithmic, "{:?}", fit);
+    }
 }
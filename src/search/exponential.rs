@@ -0,0 +1,111 @@
+//! Exponential search
+//!
+//! Also known as *galloping* search. Rather than starting with the whole
+//! slice, the search probes indices _1, 2, 4, 8, …_ until it passes the
+//! target, then runs a binary search between the last two probes. If the
+//! target is at index _i_, that takes _O(lg(i))_ comparisons, which beats
+//! binary search when the target is near the start of a huge slice, and
+//! works on sequences whose length isn't known at all.
+//!
+//! # Complexity
+//!
+//! *Worst case*: _O(lg(i))_, where _i_ is the index of the target, or where it
+//! would be. That is at most _O(lg(n))_.
+
+use std::cmp::Ordering;
+
+/// Returns the index of a target value if it appears in a sorted slice
+pub fn search<T>(values: &[T], target: &T) -> Option<usize>
+where
+    T: Ord
+{
+    search_by(values, |value| value.cmp(target))
+}
+
+/// Returns the index of the target if it appears in a sorted slice, using a
+/// comparator function
+///
+/// The comparator returns the ordering of an element relative to the target,
+/// like `slice::binary_search_by`.
+pub fn search_by<T, F>(values: &[T], mut compare: F) -> Option<usize>
+where
+    F: FnMut(&T) -> Ordering
+{
+    search_unbounded(|i| values.get(i).map(&mut compare))
+}
+
+/// Returns the index of the target in a sorted sequence of unknown length
+///
+/// `compare(i)` returns the ordering of element `i` relative to the target,
+/// or `None` if the sequence has fewer than _i + 1_ elements. For an infinite
+/// sequence, it never returns `None`.
+///
+/// # Loop invariant
+///
+/// At the start of each iteration, every element before `bound / 2` is less
+/// than the target.
+///
+/// *Initialization*: To begin with, *bound = 1*, and there are no elements
+/// before index _0_.
+///
+/// *Maintenance*: The loop only continues if element `bound` is less than the
+/// target, and the sequence is sorted, so every element before `2 * bound`
+/// is too.
+///
+/// *Termination*: The loop terminates when element `bound` is past the end,
+/// or not less than the target. The target can only be in
+/// _[bound / 2, bound]_, which is binary searched.
+pub fn search_unbounded<F>(mut compare: F) -> Option<usize>
+where
+    F: FnMut(usize) -> Option<Ordering>
+{
+    let mut bound = 1;
+    while compare(bound) == Some(Ordering::Less) {
+        bound *= 2;
+    }
+
+    // Past the end counts as greater, which keeps the bracket sorted
+    let (mut lo, mut hi) = (bound / 2, bound + 1);
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        match compare(mid) {
+            Some(Ordering::Equal) => return Some(mid),
+            Some(Ordering::Less) => lo = mid + 1,
+            _ => hi = mid,
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use search::check_search;
+
+    #[test]
+    fn test_search() {
+        check_search(search);
+    }
+
+    #[test]
+    fn test_search_by() {
+        let values = ["a", "bb", "ccc", "dddd"];
+        assert_eq!(search_by(&values, |value| value.len().cmp(&3)), Some(2));
+        assert_eq!(search_by(&values, |value| value.len().cmp(&5)), None);
+    }
+
+    #[test]
+    fn test_search_unbounded() {
+        // The squares, without end
+        let mut probes = 0;
+        let found = search_unbounded(|i| {
+            probes += 1;
+            Some((i * i).cmp(&1_000_000))
+        });
+        assert_eq!(found, Some(1000));
+        assert!(probes < 30, "{} probes", probes);
+
+        assert_eq!(search_unbounded(|i| Some((i * i).cmp(&1_000_001))), None);
+    }
+}
@@ -0,0 +1,105 @@
+//! Fibonacci search
+//!
+//! Like binary search, but splits the range into two parts whose lengths are
+//! consecutive Fibonacci numbers, _F(k - 1)_ and _F(k - 2)_, rather than two
+//! halves. The probes only need addition and subtraction, never division,
+//! which mattered on early machines. The larger part is about _0.618_ of the
+//! range, so it takes about _1.44 lg(n)_ comparisons in the worst case,
+//! against _lg(n)_ for binary search.
+//!
+//! # Complexity
+//!
+//! *Worst case*: _O(lg(n))_.
+
+use std::cmp::Ordering;
+
+use math::fibonacci::FibonacciSequence;
+
+/// Returns the index of a target value if it appears in a sorted slice
+pub fn search<T>(values: &[T], target: &T) -> Option<usize>
+where
+    T: Ord
+{
+    search_by(values, |value| value.cmp(target))
+}
+
+/// Returns the index of the target if it appears in a sorted slice, using a
+/// comparator function
+///
+/// The comparator returns the ordering of an element relative to the target,
+/// like `slice::binary_search_by`.
+///
+/// # Loop invariant
+///
+/// At the start of each iteration, the target can only be in
+/// `values[lo..lo + F(k)]`, and the element after that range, if there is
+/// one, is greater than the target.
+///
+/// *Initialization*: To begin with, *lo = 0* and _F(k) ≥ n_, so the range
+/// covers the whole slice, and there is no element after it.
+///
+/// *Maintenance*: The range is split at *i = lo + F(k - 1)*. If `values[i]`
+/// is greater than the target, or past the end, the target can only be in
+/// the _F(k - 1)_ elements before it, and `values[i]` follows them. If it is
+/// less, the target can only be in the elements after it, up to the end of
+/// the range. There are _F(k - 2) - 1_ of those, so the next range of
+/// _F(k - 2)_ elements also covers the element after the old range, which is
+/// greater than the target.
+///
+/// *Termination*: The loop terminates when the target is found, or the range
+/// is empty.
+pub fn search_by<T, F>(values: &[T], mut compare: F) -> Option<usize>
+where
+    F: FnMut(&T) -> Ordering
+{
+    // F(0), F(1), ..., F(k), where F(k) is the first at least n
+    let mut fib = vec![0];
+    fib.extend(FibonacciSequence::<usize>::new().scan(false, |done, f| {
+        if *done { return None }
+        *done = f >= values.len();
+        Some(f)
+    }));
+
+    let (mut lo, mut k) = (0, fib.len() - 1);
+    while k > 0 {
+        let i = lo + fib[k - 1];
+        match values.get(i).map(&mut compare) {
+            Some(Ordering::Equal) => return Some(i),
+            Some(Ordering::Less) if k >= 2 => {
+                lo = i + 1;
+                k -= 2;
+            }
+            Some(Ordering::Less) => return None,
+            _ => k -= 1,
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use search::check_search;
+
+    #[test]
+    fn test_search() {
+        check_search(search);
+    }
+
+    #[test]
+    fn test_search_comparisons() {
+        let values: Vec<u32> = (0..1000).collect();
+        for target in 0..1001 {
+            let mut comparisons = 0;
+            let found = search_by(&values, |value| {
+                comparisons += 1;
+                value.cmp(&target)
+            });
+
+            assert_eq!(found, if target < 1000 { Some(target as usize) } else { None });
+            // Each comparison makes k smaller, and F(17) = 1597 is the first at least 1000
+            assert!(comparisons <= 17, "{} comparisons for {}", comparisons, target);
+        }
+    }
+}
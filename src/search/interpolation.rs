@@ -0,0 +1,91 @@
+//! Interpolation search
+//!
+//! Binary search always probes the middle, but a person looking up a name in
+//! a phone book opens it near the front for "Adams" and near the back for
+//! "Young". Interpolation search does the same with numbers: it guesses that
+//! the values between the ends of the range increase linearly, and probes
+//! where the target would be on that line.
+//!
+//! # Complexity
+//!
+//! *Average case*: _O(lg(lg(n)))_ for values drawn uniformly at random.
+//!
+//! *Worst case*: _O(n)_, when the values grow very unevenly, such as
+//! exponentially, and every guess lands next to the same end.
+
+use num::ToPrimitive;
+
+/// Returns the index of a target value if it appears in a sorted slice
+///
+/// # Loop invariant
+///
+/// At the start of each iteration, the target can only be in
+/// `values[lo..=hi]`.
+///
+/// *Initialization*: To begin with, `values[lo..=hi]` is the whole slice.
+///
+/// *Maintenance*: The loop only continues if `values[lo] <= target <=
+/// values[hi]`, so the probe is within the range. If `values[probe]` is less
+/// than the target, so is everything before it, and *lo = probe + 1* keeps
+/// the invariant. Likewise for greater, and *hi = probe - 1*.
+///
+/// *Termination*: The loop terminates when the range is empty, or the target
+/// is outside of its ends, so the target isn't in the slice. Otherwise it
+/// returns as soon as the target is found.
+pub fn search<T>(values: &[T], target: &T) -> Option<usize>
+where
+    T: Ord + ToPrimitive
+{
+    if values.is_empty() { return None }
+
+    let (mut lo, mut hi) = (0, values.len() - 1);
+    while lo <= hi && *target >= values[lo] && *target <= values[hi] {
+        let probe = if values[lo] == values[hi] {
+            lo
+        } else {
+            let (low, high, key) = (to_f64(&values[lo]), to_f64(&values[hi]), to_f64(target));
+            let offset = ((key - low) / (high - low) * (hi - lo) as f64) as usize;
+            lo + offset.min(hi - lo)
+        };
+
+        if values[probe] == *target {
+            return Some(probe);
+        } else if values[probe] < *target {
+            lo = probe + 1;
+        } else if probe == 0 {
+            break;
+        } else {
+            hi = probe - 1;
+        }
+    }
+
+    None
+}
+
+fn to_f64<T: ToPrimitive>(value: &T) -> f64 {
+    value.to_f64().expect("value must be convertible to f64")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use search::check_search;
+
+    #[test]
+    fn test_search() {
+        check_search(search);
+
+        // Other key types, and the first of a run of equal elements
+        assert_eq!(search(&[0u8; 0], &1), None);
+        assert_eq!(search(&[4, 4, 4], &4), Some(0));
+        assert_eq!(search(&[-3i64, 1, 2], &-3), Some(0));
+    }
+
+    #[test]
+    fn test_search_skewed() {
+        // Powers of two defeat the linear guess, but the search still works
+        let values: Vec<u64> = (0..64).map(|i| 1 << i).collect();
+        assert_eq!(search(&values, &(1 << 40)), Some(40));
+        assert_eq!(search(&values, &3), None);
+    }
+}
@@ -0,0 +1,275 @@
+//! Cache-friendly layouts for searching a static sorted slice
+//!
+//! Binary search over a sorted slice touches _lg(n)_ elements, spread all
+//! over memory. The first few probes are the same every time and stay in
+//! cache, but once the slice is much larger than the cache, nearly every
+//! later probe is a miss. Rearranging the elements so that the ones probed
+//! together are stored together helps a great deal.
+//!
+//! *Eytzinger layout*: the elements of an implicit binary search tree, in
+//! breadth-first order, like a binary heap. The children of node _k_ are
+//! _2k_ and _2k + 1_, so the four levels below a node are in 16 consecutive
+//! elements. For 4-byte keys that is one cache line, which is prefetched
+//! long before it is needed. Each step is branchless, choosing a child with
+//! arithmetic rather than a jump, so nothing is mispredicted.
+//!
+//! *B-tree layout*: the elements of an implicit B-tree with 16 keys per node,
+//! one node per cache line for 4-byte keys. Each node is searched by
+//! counting the keys less than the target, which compiles to vector
+//! instructions, then the search descends to the child with that index. The
+//! tree has _log₁₇(n)_ levels, each of which costs about one cache miss.
+//!
+//! Both are built once, in _O(n)_ time, and then searched in _O(lg(n))_ time.
+//! Each records where its elements came from, so results are indices into
+//! the original sorted slice, the same as from `binary::search`.
+//!
+//! ```
+//! use kb::search::layout::{BTree, Eytzinger};
+//!
+//! let sorted = [2, 3, 5, 7, 11, 13, 17, 19, 23];
+//! let eytzinger = Eytzinger::new(&sorted);
+//! let btree = BTree::new(&sorted);
+//!
+//! assert_eq!(eytzinger.search(&13), Some(5));
+//! assert_eq!(btree.search(&13), Some(5));
+//! assert_eq!(eytzinger.lower_bound(&14), 6);
+//! assert_eq!(btree.search(&14), None);
+//! ```
+
+/// The number of keys in a node of the B-tree layout
+pub const BLOCK: usize = 16;
+
+/// A sorted slice rearranged in Eytzinger order
+#[derive(Clone, Debug)]
+pub struct Eytzinger<T> {
+    /// The tree, starting at index 1, so that the children of `k` are `2k`
+    /// and `2k + 1`. Index 0 is a copy of the first element, and never read.
+    values: Vec<T>,
+    /// The index in the sorted slice of each element, with `n` at index 0
+    ranks: Vec<usize>,
+}
+
+impl<T: Clone + Ord> Eytzinger<T> {
+    /// Builds the layout from a sorted slice
+    pub fn new(sorted: &[T]) -> Self {
+        let n = sorted.len();
+        let mut values = sorted.first().map_or_else(Vec::new, |first| vec![first.clone(); n + 1]);
+        let mut ranks = vec![n; n + 1];
+
+        // An in-order traversal of the tree visits the elements in sorted order
+        let mut next = 0;
+        let mut stack = Vec::new();
+        let mut k = 1;
+        while k <= n || !stack.is_empty() {
+            if k <= n {
+                stack.push(k);
+                k *= 2;
+            } else {
+                k = stack.pop().unwrap();
+                values[k] = sorted[next].clone();
+                ranks[k] = next;
+                next += 1;
+                k = 2 * k + 1;
+            }
+        }
+
+        Eytzinger { values, ranks }
+    }
+
+    pub fn len(&self) -> usize {
+        self.ranks.len() - 1
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the node of the first element that is not less than `target`,
+    /// or _0_ if there isn't one
+    ///
+    /// The descent goes left when the node is not less than the target, and
+    /// right when it is, until it falls off the tree. The last left turn was
+    /// at the answer. Each right turn appends a 1 bit to `k`, and a left turn
+    /// a 0 bit, so shifting off the trailing ones and then the final zero
+    /// undoes the steps after it. No left turn leaves _0_.
+    fn descend(&self, target: &T) -> usize {
+        let n = self.len();
+        let mut k = 1;
+        while k <= n {
+            prefetch(&self.values, k * BLOCK);
+            k = 2 * k + (self.values[k] < *target) as usize;
+        }
+
+        k >> (k.trailing_ones() + 1)
+    }
+
+    /// Returns the index in the sorted slice of the first element that is
+    /// not less than `target`
+    pub fn lower_bound(&self, target: &T) -> usize {
+        self.ranks[self.descend(target)]
+    }
+
+    /// Returns the index in the sorted slice of a target value, if it appears
+    pub fn search(&self, target: &T) -> Option<usize> {
+        let k = self.descend(target);
+        if k > 0 && self.values[k] == *target { Some(self.ranks[k]) } else { None }
+    }
+}
+
+/// A sorted slice rearranged as an implicit B-tree
+///
+/// Node `k` holds `BLOCK` keys, and its children are `k * (BLOCK + 1) + i + 1`
+/// for `i` in `0..=BLOCK`. Unused slots are padded with copies of the
+/// largest key, which come after every real key in order.
+#[derive(Clone, Debug)]
+pub struct BTree<T> {
+    keys: Vec<T>,
+    /// The index in the sorted slice of each key, or `n` for padding
+    ranks: Vec<usize>,
+    len: usize,
+}
+
+impl<T: Clone + Ord> BTree<T> {
+    /// Builds the layout from a sorted slice
+    pub fn new(sorted: &[T]) -> Self {
+        let n = sorted.len();
+        let nodes = n.div_ceil(BLOCK);
+        let mut keys = sorted.last().map_or_else(Vec::new, |last| vec![last.clone(); nodes * BLOCK]);
+        let mut ranks = vec![n; nodes * BLOCK];
+
+        // An in-order traversal. Visiting (k, i) places key i - 1 of node k,
+        // which follows child i - 1, then visits child i.
+        let mut next = 0;
+        let mut stack = vec![(0, 0)];
+        while let Some((k, i)) = stack.pop() {
+            if k >= nodes { continue }
+
+            if i > 0 && next < n {
+                let slot = k * BLOCK + i - 1;
+                keys[slot] = sorted[next].clone();
+                ranks[slot] = next;
+                next += 1;
+            }
+
+            if i < BLOCK { stack.push((k, i + 1)) }
+            stack.push((k * (BLOCK + 1) + i + 1, 0));
+        }
+
+        BTree { keys, ranks, len: n }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the slot of the first key that is not less than `target`, or
+    /// `None` if there isn't one
+    fn lower_bound_slot(&self, target: &T) -> Option<usize> {
+        let nodes = self.keys.len() / BLOCK;
+        let mut found = None;
+        let mut k = 0;
+        while k < nodes {
+            let node = &self.keys[k * BLOCK..(k + 1) * BLOCK];
+            let i = node.iter().filter(|key| **key < *target).count();
+            if i < BLOCK { found = Some(k * BLOCK + i) }
+
+            k = k * (BLOCK + 1) + i + 1;
+        }
+
+        found
+    }
+
+    /// Returns the index in the sorted slice of the first element that is
+    /// not less than `target`
+    pub fn lower_bound(&self, target: &T) -> usize {
+        self.lower_bound_slot(target).map_or(self.len, |slot| self.ranks[slot])
+    }
+
+    /// Returns the index in the sorted slice of a target value, if it appears
+    pub fn search(&self, target: &T) -> Option<usize> {
+        let slot = self.lower_bound_slot(target)?;
+        if self.ranks[slot] < self.len && self.keys[slot] == *target { Some(self.ranks[slot]) } else { None }
+    }
+}
+
+/// Hints that `values[i]` will be read soon, if it exists
+#[inline]
+fn prefetch<T>(values: &[T], i: usize) {
+    #[cfg(target_arch = "x86_64")]
+    {
+        use std::arch::x86_64::{_mm_prefetch, _MM_HINT_T0};
+
+        if i < values.len() {
+            // Safe as the address is within the slice, and a prefetch only
+            // loads it into cache
+            unsafe { _mm_prefetch::<_MM_HINT_T0>(values.as_ptr().add(i) as *const i8) }
+        }
+    }
+
+    #[cfg(not(target_arch = "x86_64"))]
+    let _ = (values, i);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use search::binary;
+
+    #[test]
+    fn test_eytzinger_order() {
+        let eytzinger = Eytzinger::new(&[1, 2, 3, 4, 5, 6, 7]);
+        assert_eq!(eytzinger.values[1..], [4, 2, 6, 1, 3, 5, 7]);
+        assert_eq!(eytzinger.ranks[1..], [3, 1, 5, 0, 2, 4, 6]);
+    }
+
+    #[test]
+    fn test_btree_order() {
+        // The root's first two keys each follow a full child, and the rest
+        // have no children
+        let sorted: Vec<u32> = (0..40).collect();
+        let btree = BTree::new(&sorted);
+        assert_eq!(btree.keys[..9], [16, 33, 34, 35, 36, 37, 38, 39, 39]);
+        assert_eq!(btree.ranks[7..9], [39, 40]);
+        assert_eq!(btree.keys[BLOCK..2 * BLOCK], sorted[..16]);
+        assert_eq!(btree.keys[2 * BLOCK..3 * BLOCK], sorted[17..33]);
+    }
+
+    #[test]
+    fn test_empty() {
+        let eytzinger = Eytzinger::<u32>::new(&[]);
+        assert!(eytzinger.is_empty());
+        assert_eq!(eytzinger.lower_bound(&1), 0);
+        assert_eq!(eytzinger.search(&1), None);
+
+        let btree = BTree::<u32>::new(&[]);
+        assert!(btree.is_empty());
+        assert_eq!(btree.lower_bound(&1), 0);
+        assert_eq!(btree.search(&1), None);
+    }
+
+    #[test]
+    fn test_agrees_with_binary_search() {
+        use gen;
+
+        for len in (0..300).chain(vec![1000, 4096, 4913, 5000]) {
+            let mut sorted: Vec<u32> = gen::few_unique(len, len / 3 + 1, len as u64);
+            sorted.sort();
+            let (eytzinger, btree) = (Eytzinger::new(&sorted), BTree::new(&sorted));
+            assert_eq!((eytzinger.len(), btree.len()), (len, len));
+
+            for target in 0..=len as u32 + 1 {
+                let lower = binary::lower_bound(&sorted, &target);
+                assert_eq!(eytzinger.lower_bound(&target), lower, "len {}, target {}", len, target);
+                assert_eq!(btree.lower_bound(&target), lower, "len {}, target {}", len, target);
+
+                let found = binary::search(&sorted, &target).map(|_| lower);
+                assert_eq!(eytzinger.search(&target), found);
+                assert_eq!(btree.search(&target), found);
+            }
+        }
+    }
+}
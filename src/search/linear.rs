@@ -21,6 +21,39 @@
 //! insignificant terms.
 //!
 //! *Worst case*: _O(n)_ because _n_ checks have to be made.
+//!
+//! # Variants
+//!
+//! Every search takes the values first and the target second, like
+//! `binary::search`.
+//!
+//! - `find_all` and `rfind` return every match, or the last.
+//! - `sentinel` makes sure the loop will stop at the target, so the loop only
+//!   compares each element with the target and doesn't test for the end.
+//!   Indexing still checks the bounds, so it is no faster than `linear`.
+//! - `chunked` checks a fixed size chunk at a time without branching, which
+//!   the compiler can turn into vector instructions for primitive types.
+//! - `move_to_front` and `transpose` rearrange the values as they are
+//!   searched, so that frequently searched values drift towards the front.
+//!   When a few values are searched far more often than the rest, the
+//!   average search gets much shorter.
+//!
+//! ```
+//! use kb::search::linear;
+//!
+//! let mut values = [3, 1, 4, 1, 5];
+//! assert_eq!(linear::linear(&values, &1), Some(1));
+//! assert_eq!(linear::rfind(&values, &1), Some(3));
+//! assert_eq!(linear::find_all(&values, &1), [1, 3]);
+//!
+//! assert_eq!(linear::move_to_front(&mut values, &5), Some(4));
+//! assert_eq!(values, [5, 3, 1, 4, 1]);
+//! ```
+
+use std::mem;
+
+/// The number of elements `chunked` checks at a time
+const CHUNK: usize = 32;
 
 /// My own implementation of CLRS exercise 2.1-3
 ///
@@ -40,27 +73,229 @@
 ///
 /// *Termination*: If we found the value, its index is returned. If not, we
 /// have exhausted the search.
-pub fn linear<T>(target: &T, values: &[T]) -> Option<usize>
+pub fn linear<T>(values: &[T], target: &T) -> Option<usize>
+where
+    T: PartialEq
+{
+    values.iter().position(|value| value == target)
+}
+
+/// Returns the index of every element equal to the target, in order
+pub fn find_all<T>(values: &[T], target: &T) -> Vec<usize>
+where
+    T: PartialEq
+{
+    (0..values.len()).filter(|&i| values[i] == *target).collect()
+}
+
+/// Returns the index of the last element equal to the target
+pub fn rfind<T>(values: &[T], target: &T) -> Option<usize>
 where
     T: PartialEq
 {
-    for i in 0..values.len() {
-        if values[i] == *target {
-            return Some(i);
+    values.iter().rposition(|value| value == target)
+}
+
+/// Knuth's Algorithm Q, from TAOCP 6.1
+///
+/// The loop in `linear` checks both whether it has reached the end, and
+/// whether it has found the target. Algorithm Q places the target after the
+/// last element, as a *sentinel*, so the search is sure to find it and only
+/// the second check is needed. Afterwards, finding the sentinel means the
+/// target wasn't there.
+///
+/// Rather than growing the slice, this version puts the sentinel in place of
+/// the last element for the duration of the search, then checks that element
+/// separately. The slice is left as it was, even if a comparison panics.
+///
+/// The compiler can't tell that the sentinel stops the loop, so indexing the
+/// slice still checks the bounds at every step. The check for the end that
+/// Algorithm Q removes is only moved, not saved.
+pub fn sentinel<T>(values: &mut [T], target: &T) -> Option<usize>
+where
+    T: PartialEq + Clone
+{
+    let last = values.len().checked_sub(1)?;
+    let saved = mem::replace(&mut values[last], target.clone());
+    let restore = Restore { values, saved: Some(saved) };
+
+    let mut i = 0;
+    while restore.values[i] != *target {
+        i += 1;
+    }
+
+    if i < last || restore.saved.as_ref() == Some(target) { Some(i) } else { None }
+}
+
+/// Puts the last element of a slice back when dropped, after `sentinel` has
+/// replaced it
+struct Restore<'a, T: 'a> {
+    values: &'a mut [T],
+    saved: Option<T>,
+}
+
+impl<'a, T> Drop for Restore<'a, T> {
+    fn drop(&mut self) {
+        if let Some(saved) = self.saved.take() {
+            let last = self.values.len() - 1;
+            self.values[last] = saved;
+        }
+    }
+}
+
+/// Returns the index of the first element equal to the target, checking
+/// `CHUNK` elements at a time
+///
+/// Each chunk is checked for a match without branching or stopping early,
+/// which the compiler turns into a few vector comparisons for primitive
+/// types. Only the chunk with the match is searched element by element.
+pub fn chunked<T>(values: &[T], target: &T) -> Option<usize>
+where
+    T: PartialEq + Copy
+{
+    let target = *target;
+    let chunks = values.chunks_exact(CHUNK);
+    let remainder = chunks.remainder();
+
+    for (c, chunk) in chunks.enumerate() {
+        if chunk.iter().fold(false, |found, &value| found | (value == target)) {
+            return linear(chunk, &target).map(|i| c * CHUNK + i);
         }
     }
 
-    None
+    linear(remainder, &target).map(|i| values.len() - remainder.len() + i)
+}
+
+/// Searches for the target, then moves it to the front of the slice
+///
+/// Returns the index the target was found at, which is how far the search
+/// had to go. The elements before it each move back one place.
+///
+/// Move-to-front adapts quickly when the values searched for change, and is
+/// never more than twice as slow as the best possible static order, but it
+/// can throw an element to the front after a single search.
+pub fn move_to_front<T>(values: &mut [T], target: &T) -> Option<usize>
+where
+    T: PartialEq
+{
+    let i = linear(values, target)?;
+    values[..=i].rotate_right(1);
+    Some(i)
+}
+
+/// Searches for the target, then swaps it with the element before it
+///
+/// Returns the index the target was found at. Transposition moves elements
+/// forward one step at a time, so it takes longer to adapt than
+/// `move_to_front`, but a single rare search doesn't disturb the order much.
+pub fn transpose<T>(values: &mut [T], target: &T) -> Option<usize>
+where
+    T: PartialEq
+{
+    let i = linear(values, target)?;
+    if i > 0 { values.swap(i - 1, i) }
+    Some(i)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use gen;
+    use std::panic::{self, AssertUnwindSafe};
 
     #[test]
     fn test_linear() {
-        assert_eq!(linear(&11, &[]), None);
-        assert_eq!(linear(&22, &[11, 22, 33]), Some(1));
-        assert_eq!(linear(&44, &[11, 22, 33]), None);
+        assert_eq!(linear(&[], &11), None);
+        assert_eq!(linear(&[11, 22, 33], &22), Some(1));
+        assert_eq!(linear(&[11, 22, 33], &44), None);
+    }
+
+    #[test]
+    fn test_find_all() {
+        assert_eq!(find_all(&[1, 2, 1, 1, 3], &1), [0, 2, 3]);
+        assert_eq!(find_all(&[1, 2, 3], &4), []);
+        assert_eq!(rfind(&[1, 2, 1, 1, 3], &1), Some(3));
+        assert_eq!(rfind(&[1, 2, 3], &4), None);
+        assert_eq!(rfind(&[], &4), None);
+    }
+
+    #[test]
+    fn test_sentinel() {
+        let mut values = [11, 22, 33, 22];
+        assert_eq!(sentinel(&mut values, &22), Some(1));
+        assert_eq!(sentinel(&mut values, &33), Some(2));
+        assert_eq!(sentinel(&mut values, &44), None);
+        assert_eq!(values, [11, 22, 33, 22]);
+
+        assert_eq!(sentinel(&mut [5], &5), Some(0));
+        assert_eq!(sentinel(&mut [5], &6), None);
+        assert_eq!(sentinel(&mut [0; 0], &6), None);
+    }
+
+    #[test]
+    fn test_sentinel_panic() {
+        /// A key that panics when a zero is compared
+        #[derive(Clone, Debug)]
+        struct Key(u32);
+
+        impl PartialEq for Key {
+            fn eq(&self, other: &Key) -> bool {
+                assert!(self.0 != 0, "compared zero");
+                self.0 == other.0
+            }
+        }
+
+        let mut values = vec![Key(1), Key(0), Key(3)];
+        let result = panic::catch_unwind(AssertUnwindSafe(|| sentinel(&mut values, &Key(5))));
+        assert!(result.is_err());
+
+        // The last element was put back while unwinding
+        assert_eq!(values.iter().map(|key| key.0).collect::<Vec<_>>(), [1, 0, 3]);
+    }
+
+    #[test]
+    fn test_variants_agree() {
+        for len in (0..100).chain(vec![255, 1000]) {
+            let values: Vec<u32> = gen::few_unique(len, 20, len as u64);
+            for target in 0..=len as u32 {
+                let expected = values.iter().position(|&value| value == target);
+                assert_eq!(linear(&values, &target), expected);
+                assert_eq!(chunked(&values, &target), expected, "len {}, target {}", len, target);
+                assert_eq!(sentinel(&mut values.clone(), &target), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_self_organizing() {
+        let mut values = ['a', 'b', 'c', 'd'];
+        assert_eq!(move_to_front(&mut values, &'c'), Some(2));
+        assert_eq!(values, ['c', 'a', 'b', 'd']);
+        assert_eq!(transpose(&mut values, &'d'), Some(3));
+        assert_eq!(values, ['c', 'a', 'd', 'b']);
+        assert_eq!(transpose(&mut values, &'c'), Some(0));
+        assert_eq!(move_to_front(&mut values, &'z'), None);
+        assert_eq!(values, ['c', 'a', 'd', 'b']);
+    }
+
+    #[test]
+    fn test_self_organizing_skewed() {
+        // Searches follow Zipf's law over 200 values, which start in reverse
+        // order of popularity
+        let searches: Vec<u32> = (0..25).flat_map(|seed| gen::zipf::<u32>(200, 1.0, seed)).collect();
+        let reversed: Vec<u32> = gen::reversed(200);
+        let cost = |search: &dyn Fn(&mut [u32], &u32) -> Option<usize>| {
+            let mut values = reversed.clone();
+            searches.iter().map(|target| search(&mut values, target).unwrap() + 1).sum::<usize>()
+        };
+
+        let fixed = cost(&|values, target| linear(values, target));
+        let front = cost(&|values, target| move_to_front(values, target));
+        let transposed = cost(&|values, target| transpose(values, target));
+        assert!(front * 3 < fixed, "{} against {}", front, fixed);
+
+        // Transposition is slower to adapt from the worst possible order
+        assert!(transposed * 4 < fixed * 3, "{} against {}", transposed, fixed);
+        assert!(front < transposed);
     }
 }
@@ -1,4 +1,41 @@
 //! Searching functions
 
 pub mod binary;
+pub mod exponential;
+pub mod fibonacci;
+pub mod interpolation;
+pub mod layout;
 pub mod linear;
+pub mod ternary;
+
+/// Checks a search of sorted `u32`s, on a small slice of distinct elements
+/// and on generated slices full of duplicates
+#[cfg(test)]
+fn check_search<F>(search: F)
+where
+    F: Fn(&[u32], &u32) -> Option<usize>
+{
+    use gen;
+
+    let values = [11, 22, 33, 44, 55, 66, 77];
+    for (i, value) in values.iter().enumerate() {
+        assert_eq!(search(&values, value), Some(i));
+    }
+
+    assert_eq!(search(&values, &10), None);
+    assert_eq!(search(&values, &50), None);
+    assert_eq!(search(&values, &80), None);
+    assert_eq!(search(&[], &1), None);
+    assert_eq!(search(&[1], &1), Some(0));
+
+    for len in 0..70 {
+        let mut values: Vec<u32> = gen::few_unique(len, 10, len as u64);
+        values.sort();
+        for target in 0..=len as u32 {
+            match search(&values, &target) {
+                Some(i) => assert_eq!(values[i], target),
+                None => assert!(!values.contains(&target)),
+            }
+        }
+    }
+}
@@ -0,0 +1,175 @@
+//! Ternary search
+//!
+//! Splits the range into thirds with two probes, rather than halves with one.
+//!
+//! On a sorted slice, that is worse than binary search: each iteration keeps
+//! a third of the range for two comparisons, about _2 log₃(n) ≈ 1.26 lg(n)_
+//! comparisons in all.
+//!
+//! Where it earns its keep is on *unimodal* sequences, which strictly increase
+//! to a peak, then strictly decrease. Comparing a single element tells you
+//! nothing about where the peak is, but comparing two does: the peak can't be
+//! on the far side of the smaller one. The same works for a continuous
+//! function on an interval.
+//!
+//! # Complexity
+//!
+//! *Worst case*: _O(lg(n))_, or _O(lg(w/ε))_ for a function on an interval
+//! of width _w_ to a tolerance of _ε_.
+
+use std::cmp::Ordering;
+
+/// Returns the index of a target value if it appears in a sorted slice
+pub fn search<T>(values: &[T], target: &T) -> Option<usize>
+where
+    T: Ord
+{
+    let (mut lo, mut hi) = (0, values.len());
+    while lo < hi {
+        let third = (hi - lo) / 3;
+        let (m1, m2) = (lo + third, hi - 1 - third);
+
+        match (values[m1].cmp(target), values[m2].cmp(target)) {
+            (Ordering::Equal, _) => return Some(m1),
+            (_, Ordering::Equal) => return Some(m2),
+            (Ordering::Greater, _) => hi = m1,
+            (_, Ordering::Less) => lo = m2 + 1,
+            _ => {
+                lo = m1 + 1;
+                hi = m2;
+            }
+        }
+    }
+
+    None
+}
+
+/// Returns the index of the largest element of a unimodal slice, or `None`
+/// if it is empty
+///
+/// The result is only meaningful if the slice strictly increases and then
+/// strictly decreases, either of which may be empty. A plateau can hide which
+/// side the peak is on.
+pub fn peak<T>(values: &[T]) -> Option<usize>
+where
+    T: Ord
+{
+    peak_by(values, T::cmp)
+}
+
+/// Returns the index of the largest element of a unimodal slice, with
+/// respect to a comparator function
+///
+/// # Loop invariant
+///
+/// At the start of each iteration, the peak is in `values[lo..hi]`.
+///
+/// *Initialization*: To begin with, `values[lo..hi]` is the whole slice.
+///
+/// *Maintenance*: If `values[m1]` is less than `values[m2]`, `m1` is on the
+/// increasing side, so the peak is after it, and *lo = m1 + 1*. Otherwise,
+/// `m2` is on the decreasing side, so the peak is at or before `m2`, and
+/// *hi = m2 + 1*. At least four elements remain, so the probes are distinct
+/// and the range gets smaller.
+///
+/// *Termination*: The loop terminates with at most three elements left, and
+/// the largest of them is the peak.
+pub fn peak_by<T, F>(values: &[T], mut compare: F) -> Option<usize>
+where
+    F: FnMut(&T, &T) -> Ordering
+{
+    if values.is_empty() { return None }
+
+    let (mut lo, mut hi) = (0, values.len());
+    while hi - lo > 3 {
+        let third = (hi - lo) / 3;
+        let (m1, m2) = (lo + third, hi - 1 - third);
+
+        if compare(&values[m1], &values[m2]) == Ordering::Less {
+            lo = m1 + 1;
+        } else {
+            hi = m2 + 1;
+        }
+    }
+
+    let mut peak = lo;
+    for i in lo + 1..hi {
+        if compare(&values[peak], &values[i]) == Ordering::Less { peak = i }
+    }
+
+    Some(peak)
+}
+
+/// Returns the index of the element with the largest key in a unimodal slice
+pub fn peak_by_key<T, K, F>(values: &[T], mut key: F) -> Option<usize>
+where
+    K: Ord,
+    F: FnMut(&T) -> K
+{
+    peak_by(values, |a, b| key(a).cmp(&key(b)))
+}
+
+/// Returns the point where a unimodal function is largest on `[lo, hi]`, to
+/// within `tolerance`
+///
+/// The search also stops if the interval can't be split any further, so a
+/// tolerance smaller than the gap between neighbouring `f64`s still
+/// terminates.
+pub fn maximize<F>(mut f: F, mut lo: f64, mut hi: f64, tolerance: f64) -> f64
+where
+    F: FnMut(f64) -> f64
+{
+    while hi - lo > tolerance {
+        let third = (hi - lo) / 3.0;
+        let (m1, m2) = (lo + third, hi - third);
+        if m1 <= lo || m2 >= hi { break }
+
+        if f(m1) < f(m2) { lo = m1 } else { hi = m2 }
+    }
+
+    (lo + hi) / 2.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use search::check_search;
+
+    #[test]
+    fn test_search() {
+        check_search(search);
+    }
+
+    #[test]
+    fn test_peak() {
+        assert_eq!(peak(&[1, 3, 8, 12, 4, 2]), Some(3));
+        assert_eq!(peak(&[1, 2, 3]), Some(2));
+        assert_eq!(peak(&[3, 2, 1]), Some(0));
+        assert_eq!(peak(&[5]), Some(0));
+        assert_eq!(peak::<u8>(&[]), None);
+
+        for len in 1..40 {
+            for top in 0..len {
+                let values: Vec<i32> = (0..len as i32).map(|i| -(i - top as i32).abs()).collect();
+                assert_eq!(peak(&values), Some(top), "{:?}", values);
+            }
+        }
+
+        let words = ["a", "abc", "abcde", "ab"];
+        assert_eq!(peak_by_key(&words, |word| word.len()), Some(2));
+    }
+
+    #[test]
+    fn test_maximize() {
+        let x = maximize(|x| -(x - 1.5) * (x - 1.5), -10.0, 10.0, 1e-9);
+        assert!((x - 1.5).abs() < 1e-6, "{}", x);
+
+        let x = maximize(f64::sin, 0.0, 3.0, 1e-9);
+        assert!((x - ::std::f64::consts::FRAC_PI_2).abs() < 1e-6, "{}", x);
+
+        // No tolerance, so only running out of points between lo and hi stops it
+        let x = maximize(|x| -(x - 1.5) * (x - 1.5), -10.0, 10.0, 0.0);
+        assert!((x - 1.5).abs() < 1e-6, "{}", x);
+        assert_eq!(maximize(|x| x, 1.0, 2.0, f64::NAN), 1.5);
+    }
+}
@@ -0,0 +1,124 @@
+//! Bucket sort
+//!
+//! Sorts values drawn uniformly from the range _[0, 1)_ in _O(n)_ time on
+//! average.
+//!
+//! The range is divided into _n_ equally sized buckets, and each value is
+//! placed in the bucket covering it. Uniformly distributed input spreads out
+//! evenly, so only a few values are expected in each bucket. Each bucket is
+//! sorted with insertion sort, then the buckets are concatenated in order.
+//!
+//! *Average case*: _O(n)_. If _nᵢ_ values land in bucket _i_, sorting it costs
+//! _O(nᵢ²)_. For uniform input, _E[nᵢ²] = 2 - 1/n_, so the expected total
+//! across all _n_ buckets is linear.
+//!
+//! *Worst case*: _O(n²)_, when every value lands in the same bucket.
+//!
+//! # Example
+//!
+//! ```text
+//!   +-----+       +---+
+//! 0 |     |     0 | / |
+//!   +-----+       +---+     +-----+     +-----+
+//! 1 | .78 |     1 | --+---> | .12 +---> | .17 |
+//!   +-----+       +---+     +-----+     +-----+
+//! 2 | .17 |     2 | --+---> | .21 +---> | .23 +---> ...
+//!   +-----+       +---+     +-----+     +-----+
+//! 3 | .39 |     3 | --+---> | .39 |
+//!   +-----+       +---+     +-----+
+//!   | ... |       |...|
+//! ```
+
+use super::insertion;
+use super::instrument::{Instrument, Probe, Slot};
+use super::{Complexity, Properties, Sorter};
+
+/// The solution presented in CLRS
+///
+/// # Panics
+///
+/// Panics if a value is outside of the range _[0, 1)_.
+pub fn bucket_sort(values: &mut [f64]) {
+    bucket_sort_with(values, &mut Probe::new(&mut ()));
+}
+
+/// The CLRS solution, reporting each operation to a probe
+///
+/// Each bucket is copied back into place before being sorted, so insertion
+/// sort works on `values` itself. While in a bucket, the element from
+/// `values[i]` is reported as being in `Slot::Buffer(i)`.
+fn bucket_sort_with<I>(values: &mut [f64], probe: &mut Probe<I>)
+where
+    I: Instrument + ?Sized
+{
+    let n = values.len();
+    let mut buckets = vec![Vec::new(); n];
+    probe.allocate(n);
+    for (i, &value) in values.iter().enumerate() {
+        assert!((0.0..1.0).contains(&value), "{} is outside of [0, 1)", value);
+        buckets[(value * n as f64) as usize].push((i, value));
+        probe.write(Slot::Buffer(i), Slot::Values(i));
+    }
+
+    let mut k = 0;
+    for bucket in &buckets {
+        for (offset, &(i, value)) in bucket.iter().enumerate() {
+            values[k + offset] = value;
+            probe.write(Slot::Values(k + offset), Slot::Buffer(i));
+        }
+
+        let end = k + bucket.len();
+        insertion::clrs_with(&mut values[k..end], &mut super::partial_order, &mut probe.at(k));
+        k = end;
+    }
+}
+
+/// Bucket sort as a `Sorter`
+pub struct BucketSort;
+
+impl Sorter<f64> for BucketSort {
+    fn properties(&self) -> Properties {
+        Properties {
+            name: "Bucket Sort",
+            stable: true,
+            in_place: false,
+            worst_case: Complexity::Quadratic,
+            average_case: Complexity::Linear,
+            space: Complexity::Linear,
+        }
+    }
+
+    fn sort(&self, values: &mut [f64]) {
+        bucket_sort(values);
+    }
+
+    fn sort_instrumented(&self, values: &mut [f64], instrument: &mut dyn Instrument) {
+        bucket_sort_with(values, &mut Probe::new(instrument));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bucket_sort() {
+        let mut values = [0.78, 0.17, 0.39, 0.26, 0.72, 0.94, 0.21, 0.12, 0.23, 0.68];
+        bucket_sort(&mut values);
+        assert_eq!(values, [0.12, 0.17, 0.21, 0.23, 0.26, 0.39, 0.68, 0.72, 0.78, 0.94]);
+
+        let mut values = [0.5, 0.0, 0.999, 0.5];
+        bucket_sort(&mut values);
+        assert_eq!(values, [0.0, 0.5, 0.5, 0.999]);
+
+        let mut empty: [f64; 0] = [];
+        bucket_sort(&mut empty);
+        assert_eq!(empty, []);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_bucket_sort_out_of_range() {
+        bucket_sort(&mut [0.5, 1.0]);
+    }
+}
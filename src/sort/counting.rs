@@ -0,0 +1,228 @@
+//! Counting sort
+//!
+//! Sorts _n_ elements whose keys are integers in the range _0..k_ in
+//! _O(n + k)_ time, without comparing elements to each other.
+//!
+//! Every comparison sort needs _Ω(n lg(n))_ comparisons in the worst case,
+//! because a decision tree that can reach all _n!_ orderings of the input has
+//! a height of at least _lg(n!)_. Counting sort side steps this by using the
+//! keys themselves as indices.
+//!
+//! For each key, we count how many elements have it. A running total of the
+//! counts then gives, for each key, the number of elements with a key less
+//! than or equal to it, which is one past the last position in the output an
+//! element with that key can occupy. Walking the input backwards and placing
+//! each element at the position for its key, then decrementing it, puts
+//! elements in sorted order.
+//!
+//! Walking backwards means that equal keys fill their positions from the back
+//! in reverse order, so elements with equal keys keep their original relative
+//! order. This stability is what makes counting sort useful as a subroutine of
+//! radix sort.
+//!
+//! When _k = O(n)_, the sort runs in _O(n)_ time. A single large key makes _k_
+//! large however many elements there are, so `counting_sort` falls back to
+//! radix sort when _k_ is much larger than _n_, rather than allocating a
+//! counter for every value below it.
+//!
+//! # Example
+//!
+//! ```text
+//!             0   1   2   3   4   5   6   7
+//!           +---+---+---+---+---+---+---+---+
+//!     input | 2 | 5 | 3 | 0 | 2 | 3 | 0 | 3 |
+//!           +---+---+---+---+---+---+---+---+
+//!
+//!             0   1   2   3   4   5
+//!           +---+---+---+---+---+---+
+//!    counts | 2 | 0 | 2 | 3 | 0 | 1 |
+//!           +---+---+---+---+---+---+
+//!
+//!           +---+---+---+---+---+---+
+//!    totals | 2 | 2 | 4 | 7 | 7 | 8 |
+//!           +---+---+---+---+---+---+
+//!
+//!             0   1   2   3   4   5   6   7
+//!           +---+---+---+---+---+---+---+---+
+//!    output | 0 | 0 | 2 | 2 | 3 | 3 | 3 | 5 |
+//!           +---+---+---+---+---+---+---+---+
+//! ```
+
+use num::{PrimInt, Unsigned};
+
+use super::instrument::{Instrument, Probe, Slot};
+use super::radix;
+use super::{Complexity, Properties, Sorter};
+
+/// The most keys per element that `counting_sort` counts, before it falls
+/// back to radix sort
+const MAX_KEYS_PER_ELEMENT: usize = 8;
+
+/// Sorts unsigned integers, using each value as its own key
+///
+/// _k_ is one more than the largest value, so this is only efficient for
+/// small values, such as `u8`s. If _k_ is more than `MAX_KEYS_PER_ELEMENT`
+/// times _n_, or doesn't fit in a `usize`, this uses LSD radix sort instead,
+/// which is also stable.
+pub fn counting_sort<T>(values: &mut [T])
+where
+    T: PrimInt + Unsigned
+{
+    counting_sort_with(values, &mut Probe::new(&mut ()));
+}
+
+fn counting_sort_with<T, I>(values: &mut [T], probe: &mut Probe<I>)
+where
+    T: PrimInt + Unsigned,
+    I: Instrument + ?Sized
+{
+    // Small inputs can count up to a byte's worth of keys whatever their size
+    let limit = MAX_KEYS_PER_ELEMENT * values.len().max(32);
+    let k = values.iter().max().map_or(Some(0), |max| max.to_usize().and_then(|max| max.checked_add(1)));
+
+    match k {
+        Some(k) if k <= limit => {
+            counting_sort_by_key_with(values, k, &mut |value: &T| value.to_usize().unwrap(), probe);
+        }
+        _ => radix::lsd_with(values, probe),
+    }
+}
+
+/// The solution presented in CLRS, ordering elements by a key in the range
+/// _0..k_
+///
+/// CLRS writes the output to a second array. Here the output is collected in
+/// a buffer and moved back into `values`.
+///
+/// # Loop invariant
+///
+/// At the start of each iteration of the placement loop, `counts[key]` is the
+/// number of elements with a key less than `key`, plus the number of elements
+/// with a key equal to `key` that are yet to be placed.
+///
+/// *Initialization*: No elements have been placed, so `counts[key]` is the
+/// running total for `key`.
+///
+/// *Maintenance*: The element is placed at `counts[key] - 1`, the last free
+/// position for its key, and `counts[key]` is decremented because one fewer
+/// element with that key is yet to be placed.
+///
+/// *Termination*: Every element has been placed, so `counts[key]` is the number
+/// of elements with a smaller key, which is the first position of `key` in the
+/// output.
+///
+/// # Panics
+///
+/// Panics if a key is not less than `k`.
+pub fn counting_sort_by_key<T, F>(values: &mut [T], k: usize, mut key: F)
+where
+    T: Clone,
+    F: FnMut(&T) -> usize
+{
+    counting_sort_by_key_with(values, k, &mut key, &mut Probe::new(&mut ()));
+}
+
+/// The CLRS solution, reporting each move to and from the buffer to a probe
+pub(super) fn counting_sort_by_key_with<T, F, I>(values: &mut [T], k: usize, key: &mut F, probe: &mut Probe<I>)
+where
+    T: Clone,
+    F: FnMut(&T) -> usize,
+    I: Instrument + ?Sized
+{
+    let keys: Vec<usize> = values.iter().map(key).collect();
+
+    let mut counts = vec![0; k];
+    probe.allocate(k);
+    for &key in &keys {
+        counts[key] += 1;
+    }
+
+    for i in 1..k {
+        counts[i] += counts[i - 1];
+    }
+
+    let mut sorted = vec![None; values.len()];
+    probe.allocate(sorted.len());
+    for (i, (value, &key)) in values.iter().zip(&keys).enumerate().rev() {
+        counts[key] -= 1;
+        sorted[counts[key]] = Some(value.clone());
+        probe.write(Slot::Buffer(counts[key]), Slot::Values(i));
+    }
+
+    for (i, (value, sorted)) in values.iter_mut().zip(sorted).enumerate() {
+        *value = sorted.unwrap();
+        probe.write(Slot::Values(i), Slot::Buffer(i));
+    }
+}
+
+/// Counting sort as a `Sorter`
+pub struct CountingSort;
+
+impl<T: PrimInt + Unsigned> Sorter<T> for CountingSort {
+    fn properties(&self) -> Properties {
+        Properties {
+            name: "Counting Sort",
+            stable: true,
+            in_place: false,
+            worst_case: Complexity::LinearPlusRange,
+            average_case: Complexity::Linear,
+            space: Complexity::LinearPlusRange,
+        }
+    }
+
+    fn sort(&self, values: &mut [T]) {
+        counting_sort(values);
+    }
+
+    fn sort_instrumented(&self, values: &mut [T], instrument: &mut dyn Instrument) {
+        counting_sort_with(values, &mut Probe::new(instrument));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counting_sort() {
+        let mut values = [2u8, 5, 3, 0, 2, 3, 0, 3];
+        counting_sort(&mut values);
+        assert_eq!(values, [0, 0, 2, 2, 3, 3, 3, 5]);
+
+        let mut values = [1000u32, 7, 999, 7];
+        counting_sort(&mut values);
+        assert_eq!(values, [7, 7, 999, 1000]);
+
+        let mut empty = [0u8; 0];
+        counting_sort(&mut empty);
+        assert_eq!(empty, []);
+    }
+
+    #[test]
+    fn test_counting_sort_large_keys() {
+        use sort::instrument::Metrics;
+
+        // k doesn't fit in a usize, or would need a counter per u32
+        let mut values = [usize::MAX, 3, usize::MAX - 1, 0];
+        counting_sort(&mut values);
+        assert_eq!(values, [0, 3, usize::MAX - 1, usize::MAX]);
+
+        let mut values = [u64::MAX, 7, 1 << 40];
+        counting_sort(&mut values);
+        assert_eq!(values, [7, 1 << 40, u64::MAX]);
+
+        let mut values = [u32::MAX, 2, 1];
+        let mut metrics = Metrics::new();
+        CountingSort.sort_instrumented(&mut values, &mut metrics);
+        assert_eq!(values, [1, 2, u32::MAX]);
+        // Four radix passes of 256 counters, rather than 2³² counters
+        assert!(metrics.allocated < 2000, "{:?}", metrics);
+    }
+
+    #[test]
+    fn test_counting_sort_by_key() {
+        let mut values = [(2, 'a'), (1, 'b'), (2, 'c'), (1, 'd'), (0, 'e')];
+        counting_sort_by_key(&mut values, 3, |&(k, _)| k);
+        assert_eq!(values, [(0, 'e'), (1, 'b'), (1, 'd'), (2, 'a'), (2, 'c')]);
+    }
+}
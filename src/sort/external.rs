@@ -0,0 +1,408 @@
+//! External merge sort
+//!
+//! Sorts a stream of records that may be far larger than memory.
+//!
+//! The input is read in chunks of at most `run_len` records, and at most
+//! `budget` bytes of them as estimated by `Codec::size`. Each chunk is sorted
+//! in place with heapsort, so sorting needs no more memory than the chunk
+//! itself, and spilled to a temporary file as a sorted *run*. The runs are
+//! then combined with a *k-way merge*: the first record of every run goes
+//! into a min-heap, and the smallest is repeatedly written out and replaced
+//! by the next record from the same run. This takes _O(n lg(k))_ time for _k_
+//! runs, and holds only one record per run in memory.
+//!
+//! Each run needs an open file, so at most `fan_in` runs are merged at once.
+//! When there are more, groups of them are merged into longer runs first, in
+//! as many passes as needed.
+//!
+//! Records are read and written with a `Codec`. Heapsort isn't stable, so
+//! each record in a chunk is paired with its index to break ties. Ties in the
+//! merge are broken by the index of the run, and earlier runs hold earlier
+//! records, so the sort is stable.
+//!
+//! # Example
+//!
+//! ```
+//! use kb::sort::external::{ExternalSort, Lines};
+//!
+//! let input = "pear\napple\nfig\n";
+//! let mut output = Vec::new();
+//! let stats = ExternalSort::new(Lines, 2).sort(input.as_bytes(), &mut output).unwrap();
+//!
+//! assert_eq!(output, b"apple\nfig\npear\n");
+//! assert_eq!(stats.runs, 2);
+//! ```
+
+use std::env;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use heap::binary::{BinaryHeap, Orientation};
+use super::heap;
+
+/// The number of runs merged at once, unless configured otherwise
+pub const DEFAULT_FAN_IN: usize = 64;
+
+/// Reads and writes records as bytes
+pub trait Codec {
+    type Record;
+
+    fn encode<W: Write>(&self, record: &Self::Record, writer: &mut W) -> io::Result<()>;
+
+    /// Reads the next record, or returns `None` at the end of the input
+    fn decode<R: BufRead>(&self, reader: &mut R) -> io::Result<Option<Self::Record>>;
+
+    /// Returns roughly how many bytes a record takes up in memory, which
+    /// counts towards the budget of a chunk
+    fn size(&self, _record: &Self::Record) -> usize {
+        mem::size_of::<Self::Record>()
+    }
+}
+
+/// Newline terminated UTF-8 strings
+///
+/// The newline isn't part of the record, so records can't contain one. The
+/// last line of the input doesn't need a newline.
+#[derive(Clone, Copy, Debug)]
+pub struct Lines;
+
+impl Codec for Lines {
+    type Record = String;
+
+    fn encode<W: Write>(&self, record: &String, writer: &mut W) -> io::Result<()> {
+        writer.write_all(record.as_bytes())?;
+        writer.write_all(b"\n")
+    }
+
+    fn decode<R: BufRead>(&self, reader: &mut R) -> io::Result<Option<String>> {
+        let mut bytes = Vec::new();
+        if reader.read_until(b'\n', &mut bytes)? == 0 { return Ok(None) }
+        if bytes.last() == Some(&b'\n') { bytes.pop(); }
+
+        String::from_utf8(bytes)
+            .map(Some)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    fn size(&self, record: &String) -> usize {
+        mem::size_of::<String>() + record.len()
+    }
+}
+
+/// Unsigned 64-bit integers, as eight little-endian bytes
+#[derive(Clone, Copy, Debug)]
+pub struct U64;
+
+impl Codec for U64 {
+    type Record = u64;
+
+    fn encode<W: Write>(&self, record: &u64, writer: &mut W) -> io::Result<()> {
+        writer.write_all(&record.to_le_bytes())
+    }
+
+    fn decode<R: BufRead>(&self, reader: &mut R) -> io::Result<Option<u64>> {
+        if reader.fill_buf()?.is_empty() { return Ok(None) }
+
+        let mut bytes = [0; 8];
+        reader.read_exact(&mut bytes)?;
+        Ok(Some(u64::from_le_bytes(bytes)))
+    }
+}
+
+/// What an external sort did
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Stats {
+    pub records: usize,
+    /// Sorted runs spilled from the input, which is zero when it fits in a
+    /// single chunk
+    pub runs: usize,
+    /// Passes over the data to merge runs, including the final merge into the
+    /// output
+    pub passes: usize,
+}
+
+/// Sorts streams of records, holding at most `run_len` of them in memory
+pub struct ExternalSort<C> {
+    codec: C,
+    run_len: usize,
+    budget: usize,
+    fan_in: usize,
+    dir: PathBuf,
+}
+
+impl<C> ExternalSort<C>
+where
+    C: Codec,
+    C::Record: Ord + Clone
+{
+    /// Creates a sort that spills runs of at most `run_len` records to the
+    /// system's temporary directory
+    pub fn new(codec: C, run_len: usize) -> Self {
+        ExternalSort {
+            codec,
+            run_len: run_len.max(1),
+            budget: usize::MAX,
+            fan_in: DEFAULT_FAN_IN,
+            dir: env::temp_dir(),
+        }
+    }
+
+    /// Sets the most bytes of records to hold in a chunk, as estimated by
+    /// `Codec::size`
+    ///
+    /// A chunk always holds at least one record, however large it is. The
+    /// next record is read ahead of the chunk to find the end of the input,
+    /// so it is held in memory as well, along with the index of each record
+    /// in the chunk.
+    pub fn budget(mut self, bytes: usize) -> Self {
+        self.budget = bytes;
+        self
+    }
+
+    /// Sets the most runs to merge at once, which is at least two
+    pub fn fan_in(mut self, fan_in: usize) -> Self {
+        self.fan_in = fan_in.max(2);
+        self
+    }
+
+    /// Sets the directory to spill runs to
+    pub fn dir<P: AsRef<Path>>(mut self, dir: P) -> Self {
+        self.dir = dir.as_ref().to_path_buf();
+        self
+    }
+
+    /// Sorts the records read from `input`, writing them to `output`
+    ///
+    /// Temporary files are removed once they have been merged, or if the sort
+    /// fails.
+    pub fn sort<R: Read, W: Write>(&self, input: R, output: W) -> io::Result<Stats> {
+        let mut reader = BufReader::new(input);
+        let mut writer = BufWriter::new(output);
+        let mut stats = Stats::default();
+
+        // The next record is read before the current chunk is spilled, so input
+        // that ends with a full chunk is known to fit in it
+        let mut next = self.codec.decode(&mut reader)?;
+        let mut runs = Vec::new();
+        loop {
+            let mut chunk = Vec::new();
+            let mut bytes = 0usize;
+            while let Some(record) = next.take() {
+                let size = self.codec.size(&record);
+                if !chunk.is_empty() && (chunk.len() == self.run_len || bytes.saturating_add(size) > self.budget) {
+                    next = Some(record);
+                    break;
+                }
+
+                bytes = bytes.saturating_add(size);
+                chunk.push((record, chunk.len()));
+                next = self.codec.decode(&mut reader)?;
+            }
+
+            stats.records += chunk.len();
+            let last = next.is_none();
+            heap::heapsort(&mut chunk);
+
+            // Input that fits in a single chunk never touches the disk
+            if last && runs.is_empty() {
+                for (record, _) in &chunk {
+                    self.codec.encode(record, &mut writer)?;
+                }
+
+                writer.flush()?;
+                return Ok(stats);
+            }
+
+            let run = Run::create(&self.dir)?;
+            {
+                let mut writer = BufWriter::new(File::create(&run.path)?);
+                for (record, _) in &chunk {
+                    self.codec.encode(record, &mut writer)?;
+                }
+                writer.flush()?;
+            }
+            runs.push(run);
+
+            if last { break }
+        }
+
+        stats.runs = runs.len();
+        while runs.len() > self.fan_in {
+            let mut merged = Vec::new();
+            let mut groups = runs.into_iter().peekable();
+            while groups.peek().is_some() {
+                let group: Vec<Run> = groups.by_ref().take(self.fan_in).collect();
+                let run = Run::create(&self.dir)?;
+                self.merge(&group, File::create(&run.path)?)?;
+                merged.push(run);
+            }
+
+            runs = merged;
+            stats.passes += 1;
+        }
+
+        self.merge(&runs, &mut writer)?;
+        stats.passes += 1;
+        Ok(stats)
+    }
+
+    /// Merges sorted runs into `output` with a k-way merge
+    fn merge<W: Write>(&self, runs: &[Run], output: W) -> io::Result<()> {
+        let mut writer = BufWriter::new(output);
+
+        let mut readers = Vec::with_capacity(runs.len());
+        let mut heads = Vec::with_capacity(runs.len());
+        for run in runs {
+            let mut reader = BufReader::new(File::open(&run.path)?);
+            if let Some(record) = self.codec.decode(&mut reader)? {
+                heads.push((record, readers.len()));
+                readers.push(reader);
+            }
+        }
+
+        // Each run keeps the handle of its index, and its next record replaces
+        // the one written out, so the heap never holds more than one record
+        // per run
+        let mut heap = BinaryHeap::from_vec(heads, Orientation::Min);
+        while let Some(i) = heap.peek().map(|&(_, i)| i) {
+            self.codec.encode(&heap.peek().unwrap().0, &mut writer)?;
+
+            match self.codec.decode(&mut readers[i])? {
+                Some(record) => {
+                    let handle = heap.handle(i).unwrap();
+                    if heap.increase_key(handle, (record, i)).is_err() {
+                        return Err(io::Error::new(io::ErrorKind::InvalidData, "run is not sorted"));
+                    }
+                }
+                None => { heap.extract(); }
+            }
+        }
+
+        writer.flush()
+    }
+}
+
+/// A temporary file holding a sorted run, which is removed when dropped
+struct Run {
+    path: PathBuf,
+}
+
+impl Run {
+    fn create(dir: &Path) -> io::Result<Self> {
+        static COUNT: AtomicUsize = AtomicUsize::new(0);
+
+        let name = format!("kb-external-{}-{}.run", process::id(), COUNT.fetch_add(1, Ordering::Relaxed));
+        let path = dir.join(name);
+        OpenOptions::new().write(true).create_new(true).open(&path)?;
+        Ok(Run { path })
+    }
+}
+
+impl Drop for Run {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Creates an empty directory to spill runs to
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("kb-external-test-{}-{}", process::id(), name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn encode(values: &[u64]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        for value in values {
+            U64.encode(value, &mut bytes).unwrap();
+        }
+
+        bytes
+    }
+
+    #[test]
+    fn test_sort_lines() {
+        let dir = temp_dir("lines");
+        let input = "31\n41\n59\n26\n41\n58\n";
+        let mut output = Vec::new();
+        let stats = ExternalSort::new(Lines, 2).fan_in(2).dir(&dir).sort(input.as_bytes(), &mut output).unwrap();
+
+        assert_eq!(String::from_utf8(output).unwrap(), "26\n31\n41\n41\n58\n59\n");
+        assert_eq!(stats, Stats { records: 6, runs: 3, passes: 2 });
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+        fs::remove_dir(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_sort_u64() {
+        let dir = temp_dir("u64");
+        let values: Vec<u64> = (0..2000u64).map(|i| i.wrapping_mul(0x9e37_79b9_7f4a_7c15) % 1000).collect();
+        let mut expected = values.clone();
+        expected.sort();
+
+        for &(run_len, fan_in) in &[(3, 2), (7, 3), (100, 4), (2000, 2), (5000, 2)] {
+            let mut output = Vec::new();
+            let sort = ExternalSort::new(U64, run_len).fan_in(fan_in).dir(&dir);
+            let stats = sort.sort(&encode(&values)[..], &mut output).unwrap();
+
+            assert_eq!(output, encode(&expected), "run_len {}", run_len);
+            assert_eq!(stats.records, values.len());
+            assert_eq!(stats.runs, if run_len >= values.len() { 0 } else { values.len().div_ceil(run_len) });
+            assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+        }
+
+        fs::remove_dir(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_sort_budget() {
+        let dir = temp_dir("budget");
+        let values: Vec<u64> = (0..10).rev().collect();
+        let mut output = Vec::new();
+        let stats = ExternalSort::new(U64, 100).budget(24).dir(&dir).sort(&encode(&values)[..], &mut output).unwrap();
+
+        assert_eq!(output, encode(&(0..10).collect::<Vec<_>>()));
+        assert_eq!(stats.runs, 4);
+
+        // Any two of the lines are over the budget, so each is a run of its own
+        let input = "pear\napple\nfig\n";
+        let mut output = Vec::new();
+        let budget = Lines.size(&String::from("apple"));
+        let stats = ExternalSort::new(Lines, 100).budget(budget).dir(&dir).sort(input.as_bytes(), &mut output).unwrap();
+
+        assert_eq!(output, b"apple\nfig\npear\n");
+        assert_eq!(stats.runs, 3);
+
+        let stats = ExternalSort::new(U64, 100).budget(80).dir(&dir).sort(&encode(&values)[..], &mut Vec::new()).unwrap();
+        assert_eq!(stats.runs, 0);
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+        fs::remove_dir(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_sort_empty() {
+        let mut output = Vec::new();
+        let stats = ExternalSort::new(Lines, 2).sort(&b""[..], &mut output).unwrap();
+        assert!(output.is_empty());
+        assert_eq!(stats, Stats::default());
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        let mut output = Vec::new();
+        let err = ExternalSort::new(U64, 2).sort(&[1, 2, 3][..], &mut output).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+
+        let err = ExternalSort::new(Lines, 2).sort(&[0xff, b'\n'][..], &mut output).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,160 @@
+//! Heapsort
+//!
+//! Sorts elements in _O(n lg(n))_ time, in place, using a binary heap.
+//!
+//! The slice is first rearranged into a max-heap, so the largest element is
+//! at the root, `values[0]`. It is swapped with the last element of the heap,
+//! putting it into its final position, and the heap shrinks by one. The new
+//! root might violate the max-heap property, so it is sifted down. This
+//! repeats until the heap is a single element, the smallest.
+//!
+//! *Best case*: _O(n lg(n))_. Building the heap takes _O(n)_ time, and each of
+//! the _n - 1_ sifts takes _O(lg(n))_ time.
+//!
+//! *Worst case*: _O(n lg(n))_, for the same reasons.
+//!
+//! Swapping the root past elements equal to it means heapsort isn't stable.
+//!
+//! # Loop invariant
+//!
+//! At the start of each iteration of the for loop, `values[..end + 1]` is a
+//! max-heap containing the *end + 1* smallest elements, and `values[end + 1..]`
+//! contains the *n - end - 1* largest elements, in sorted order.
+//!
+//! *Initialization*: *end = n - 1*, so the heap is the whole slice and the
+//! sorted region is empty.
+//!
+//! *Maintenance*: The root is the largest element of the heap, so swapping it
+//! into `values[end]` extends the sorted region. Sifting the new root down
+//! makes `values[..end]` a max-heap again.
+//!
+//! *Termination*: *end = 0*, so `values[1..]` holds the *n - 1* largest
+//! elements in sorted order, and `values[0]` is the smallest.
+
+use std::cell::RefCell;
+use std::cmp::Ordering;
+
+use heap::binary;
+use super::instrument::{Instrument, Probe, Slot};
+use super::{Complexity, Properties, Sorter};
+
+/// The solution presented in CLRS
+pub fn heapsort<T>(values: &mut [T])
+where
+    T: Ord
+{
+    heapsort_by(values, T::cmp);
+}
+
+/// The CLRS solution, ordering elements with a comparator function
+pub fn heapsort_by<T, F>(values: &mut [T], mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering
+{
+    binary::build_heap_by(values, &mut compare);
+    for end in (1..values.len()).rev() {
+        values.swap(0, end);
+        binary::sift_down_by(&mut values[..end], 0, &mut compare);
+    }
+}
+
+/// The CLRS solution, reporting each comparison and swap to a probe
+fn heapsort_with<T, F, I>(values: &mut [T], compare: &mut F, probe: &mut Probe<I>)
+where
+    F: FnMut(&T, &T) -> Ordering,
+    I: Instrument + ?Sized
+{
+    // Both closures given to the sift report to the probe
+    let probe = RefCell::new(probe);
+    let mut sift_down = |values: &mut [T], i| {
+        binary::sift_down_with(
+            values,
+            i,
+            &mut |values: &[T], a, b| {
+                probe.borrow_mut().compare(Slot::Values(a), Slot::Values(b), compare(&values[a], &values[b]))
+            },
+            &mut |values: &mut [T], a, b| {
+                values.swap(a, b);
+                probe.borrow_mut().swap(a, b);
+            },
+        );
+    };
+
+    for i in (0..values.len() / 2).rev() {
+        sift_down(values, i);
+    }
+
+    for end in (1..values.len()).rev() {
+        values.swap(0, end);
+        probe.borrow_mut().swap(0, end);
+        sift_down(&mut values[..end], 0);
+    }
+}
+
+/// The CLRS solution, ordering elements by a key extraction function
+pub fn heapsort_by_key<T, K, F>(values: &mut [T], mut key: F)
+where
+    K: Ord,
+    F: FnMut(&T) -> K
+{
+    heapsort_by(values, |a, b| key(a).cmp(&key(b)));
+}
+
+/// Heapsort as a `Sorter`
+pub struct Heapsort;
+
+impl<T: Ord> Sorter<T> for Heapsort {
+    fn properties(&self) -> Properties {
+        Properties {
+            name: "Heapsort",
+            stable: false,
+            in_place: true,
+            worst_case: Complexity::Linearithmic,
+            average_case: Complexity::Linearithmic,
+            space: Complexity::Constant,
+        }
+    }
+
+    fn sort(&self, values: &mut [T]) {
+        heapsort(values);
+    }
+
+    fn sort_instrumented(&self, values: &mut [T], instrument: &mut dyn Instrument) {
+        heapsort_with(values, &mut T::cmp, &mut Probe::new(instrument));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_heapsort() {
+        let mut values = [5, 2, 4, 6, 1, 3];
+        heapsort(&mut values);
+        assert_eq!(values, [1, 2, 3, 4, 5, 6]);
+
+        let mut values = [31, 41, 59, 26, 41, 58];
+        heapsort(&mut values);
+        assert_eq!(values, [26, 31, 41, 41, 58, 59]);
+
+        let mut empty = [0; 0];
+        heapsort(&mut empty);
+        assert_eq!(empty, []);
+
+        let mut single = [1];
+        heapsort(&mut single);
+        assert_eq!(single, [1]);
+    }
+
+    #[test]
+    fn test_heapsort_by() {
+        let mut values = [5, 2, 4, 6, 1, 3];
+        heapsort_by(&mut values, |a, b| b.cmp(a));
+        assert_eq!(values, [6, 5, 4, 3, 2, 1]);
+
+        let mut values = [(3, 'a'), (1, 'b'), (2, 'c')];
+        heapsort_by_key(&mut values, |&(k, _)| k);
+        assert_eq!(values, [(1, 'b'), (2, 'c'), (3, 'a')]);
+    }
+}
@@ -0,0 +1,485 @@
+//! Hybrid sort
+//!
+//! Sorts elements in _O(n lg(n))_ time in the worst case, and _O(n)_ time for
+//! input that is already sorted, reverse sorted, or made up of a few sorted
+//! stretches. This is the approach taken by Tim Peters' *Timsort*.
+//!
+//! Real data is rarely random. It tends to contain *runs*: stretches that are
+//! already in nondecreasing order, or in strictly decreasing order, which can
+//! be reversed in place. Rather than recursing down to single elements like
+//! merge sort, the slice is scanned from left to right for these runs, and
+//! neighbouring runs are merged.
+//!
+//! *Short runs*: Merging lots of tiny runs is slow, so a run shorter than
+//! `min_run` is extended to `min_run` elements with binary insertion sort,
+//! which is fast on small inputs. `min_run` is chosen between 32 and 64 so
+//! that _n / min_run_ is a power of two, or slightly less, which keeps the
+//! final merges balanced.
+//!
+//! *Merge order*: The runs found so far are kept on a stack. With _A_, _B_ and
+//! _C_ being the lengths of the top three runs, from oldest to newest, merges
+//! happen until
+//!
+//! ```text
+//! A > B + C
+//! B > C
+//! ```
+//!
+//! holds for every three consecutive runs on the stack. The run lengths then
+//! grow at least as fast as the Fibonacci numbers from the top of the stack
+//! down, so the stack holds _O(lg(n))_ runs, and runs of similar lengths are
+//! merged together.
+//!
+//! *Galloping*: Merging runs where one contributes long stretches of elements
+//! in a row, such as interleaved sorted blocks, would compare every element.
+//! When one run wins `MIN_GALLOP` comparisons in a row, the merge switches to
+//! an exponential search for how many more elements it will win, and copies
+//! them all at once. This finds _k_ elements in _O(lg(k))_ comparisons.
+//!
+//! *Memory*: Before merging, elements of the first run that are already in
+//! place before the start of the second, and elements of the second already
+//! after the end of the first, are skipped. The shorter of the remaining runs
+//! is copied into a buffer and the merge fills the gap from the other end.
+//! One buffer of at most _n/2_ elements is reused for every merge.
+//!
+//! Equal elements are never moved past each other, so the sort is stable.
+
+use std::cmp::{self, Ordering};
+
+use super::insertion;
+use super::instrument::{Instrument, Probe, Slot};
+use super::{Complexity, Properties, Sorter};
+
+/// Runs shorter than this are always sorted entirely with insertion sort
+const MIN_MERGE: usize = 64;
+
+/// The number of consecutive wins after which a merge starts galloping
+const MIN_GALLOP: usize = 7;
+
+/// Sorts a slice with the hybrid sort
+pub fn timsort<T>(values: &mut [T])
+where
+    T: Copy + PartialOrd
+{
+    timsort_by(values, super::partial_order);
+}
+
+/// Sorts a slice with the hybrid sort, ordering elements with a comparator
+/// function
+pub fn timsort_by<T, F>(values: &mut [T], mut compare: F)
+where
+    T: Copy,
+    F: FnMut(&T, &T) -> Ordering
+{
+    timsort_with(values, &mut compare, &mut Probe::new(&mut ()));
+}
+
+fn timsort_with<T, F, I>(values: &mut [T], compare: &mut F, probe: &mut Probe<I>)
+where
+    T: Copy,
+    F: FnMut(&T, &T) -> Ordering,
+    I: Instrument + ?Sized
+{
+    let len = values.len();
+    if len < 2 { return }
+
+    let min_run = min_run(len);
+    let mut buffer = Vec::with_capacity(len / 2);
+    probe.allocate(buffer.capacity());
+    let mut runs: Vec<(usize, usize)> = Vec::new();
+
+    let mut start = 0;
+    while start < len {
+        let mut run = count_run(&mut values[start..], compare, &mut probe.at(start));
+        if run < min_run {
+            let end = cmp::min(len, start + min_run);
+            insertion::clrs_bsearch_from(&mut values[start..end], run, compare, &mut probe.at(start));
+            run = end - start;
+        }
+
+        runs.push((start, run));
+        start += run;
+
+        collapse(values, &mut runs, &mut buffer, compare, probe, false);
+    }
+
+    collapse(values, &mut runs, &mut buffer, compare, probe, true);
+}
+
+/// Sorts a slice with the hybrid sort, ordering elements by a key extraction
+/// function
+pub fn timsort_by_key<T, K, F>(values: &mut [T], mut key: F)
+where
+    T: Copy,
+    K: Ord,
+    F: FnMut(&T) -> K
+{
+    timsort_by(values, |a, b| key(a).cmp(&key(b)));
+}
+
+/// Calculates the minimum length of a run for a slice of `n` elements
+///
+/// Takes the six most significant bits of `n`, adding one if any of the
+/// remaining bits are set.
+fn min_run(mut n: usize) -> usize {
+    let mut r = 0;
+    while n >= MIN_MERGE {
+        r |= n & 1;
+        n >>= 1;
+    }
+
+    n + r
+}
+
+/// Returns the length of the run at the start of `values`
+///
+/// A strictly decreasing run is reversed in place. It must be strictly
+/// decreasing, otherwise reversing it would reorder equal elements.
+fn count_run<T, F, I>(values: &mut [T], compare: &mut F, probe: &mut Probe<I>) -> usize
+where
+    F: FnMut(&T, &T) -> Ordering,
+    I: Instrument + ?Sized
+{
+    if values.len() < 2 { return values.len() }
+
+    let mut ordered = |values: &[T], i: usize| {
+        probe.compare(Slot::Values(i), Slot::Values(i - 1), compare(&values[i], &values[i - 1]))
+    };
+
+    let mut end = 2;
+    if ordered(values, 1) == Ordering::Less {
+        while end < values.len() && ordered(values, end) == Ordering::Less {
+            end += 1;
+        }
+
+        values[..end].reverse();
+        for i in 0..end / 2 {
+            probe.swap(i, end - 1 - i);
+        }
+    } else {
+        while end < values.len() && ordered(values, end) != Ordering::Less {
+            end += 1;
+        }
+    }
+
+    end
+}
+
+/// Merges runs on the stack until the invariants hold, or until one run
+/// remains if `force` is set
+///
+/// The second condition checks the invariant one level deeper than the top
+/// three runs, because merging can break it further down the stack.
+fn collapse<T, F, I>(
+    values: &mut [T],
+    runs: &mut Vec<(usize, usize)>,
+    buffer: &mut Vec<T>,
+    compare: &mut F,
+    probe: &mut Probe<I>,
+    force: bool,
+) where
+    T: Copy,
+    F: FnMut(&T, &T) -> Ordering,
+    I: Instrument + ?Sized
+{
+    while runs.len() > 1 {
+        let n = runs.len();
+        let len = |i: usize| runs[i].1;
+
+        let at = if (n >= 3 && len(n - 3) <= len(n - 2) + len(n - 1))
+            || (n >= 4 && len(n - 4) <= len(n - 3) + len(n - 2))
+        {
+            if len(n - 3) < len(n - 1) { n - 3 } else { n - 2 }
+        } else if force || len(n - 2) <= len(n - 1) {
+            n - 2
+        } else {
+            break;
+        };
+
+        let (start, first) = runs[at];
+        let second = runs[at + 1].1;
+        merge(&mut values[start..start + first + second], first, buffer, compare, &mut probe.at(start));
+
+        runs[at].1 = first + second;
+        runs.remove(at + 1);
+    }
+}
+
+/// Merges the sorted runs `values[..mid]` and `values[mid..]`
+fn merge<T, F, I>(values: &mut [T], mid: usize, buffer: &mut Vec<T>, compare: &mut F, probe: &mut Probe<I>)
+where
+    T: Copy,
+    F: FnMut(&T, &T) -> Ordering,
+    I: Instrument + ?Sized
+{
+    let len = values.len();
+
+    let start = gallop(mid, |i| {
+        probe.compare(Slot::Values(i), Slot::Values(mid), compare(&values[i], &values[mid])) != Ordering::Greater
+    });
+    if start == mid { return }
+
+    let after = gallop(len - mid, |i| {
+        let ordering = compare(&values[len - 1 - i], &values[mid - 1]);
+        probe.compare(Slot::Values(len - 1 - i), Slot::Values(mid - 1), ordering) != Ordering::Less
+    });
+    let values = &mut values[start..len - after];
+    let mid = mid - start;
+
+    if mid <= values.len() - mid {
+        merge_lo(values, mid, buffer, compare, &mut probe.at(start));
+    } else {
+        merge_hi(values, mid, buffer, compare, &mut probe.at(start));
+    }
+}
+
+/// Merges from the front, with the first run in the buffer
+///
+/// The next element to be written is always at or before the next unread
+/// element of the second run, so nothing unread is overwritten.
+fn merge_lo<T, F, I>(values: &mut [T], mid: usize, buffer: &mut Vec<T>, compare: &mut F, probe: &mut Probe<I>)
+where
+    T: Copy,
+    F: FnMut(&T, &T) -> Ordering,
+    I: Instrument + ?Sized
+{
+    buffer.clear();
+    buffer.extend_from_slice(&values[..mid]);
+    probe.write_range(Slot::Buffer(0), Slot::Values(0), mid);
+
+    let len = values.len();
+    let (mut i, mut j, mut k) = (0, mid, 0);
+    while i < buffer.len() && j < len {
+        let (mut left_wins, mut right_wins) = (0, 0);
+        while i < buffer.len() && j < len && left_wins < MIN_GALLOP && right_wins < MIN_GALLOP {
+            if probe.compare(Slot::Values(j), Slot::Buffer(i), compare(&values[j], &buffer[i])) == Ordering::Less {
+                values[k] = values[j];
+                probe.write(Slot::Values(k), Slot::Values(j));
+                j += 1;
+                right_wins += 1;
+                left_wins = 0;
+            } else {
+                values[k] = buffer[i];
+                probe.write(Slot::Values(k), Slot::Buffer(i));
+                i += 1;
+                left_wins += 1;
+                right_wins = 0;
+            }
+
+            k += 1;
+        }
+
+        if i == buffer.len() || j == len { break }
+
+        if left_wins == MIN_GALLOP {
+            let count = gallop(buffer.len() - i, |n| {
+                let ordering = compare(&buffer[i + n], &values[j]);
+                probe.compare(Slot::Buffer(i + n), Slot::Values(j), ordering) != Ordering::Greater
+            });
+            values[k..k + count].copy_from_slice(&buffer[i..i + count]);
+            probe.write_range(Slot::Values(k), Slot::Buffer(i), count);
+            i += count;
+            k += count;
+        } else {
+            let count = gallop(len - j, |n| {
+                probe.compare(Slot::Values(j + n), Slot::Buffer(i), compare(&values[j + n], &buffer[i])) == Ordering::Less
+            });
+            values.copy_within(j..j + count, k);
+            probe.write_range(Slot::Values(k), Slot::Values(j), count);
+            j += count;
+            k += count;
+        }
+    }
+
+    values[k..k + buffer.len() - i].copy_from_slice(&buffer[i..]);
+    probe.write_range(Slot::Values(k), Slot::Buffer(i), buffer.len() - i);
+}
+
+/// Merges from the back, with the second run in the buffer
+///
+/// The mirror image of `merge_lo`, used when the second run is shorter.
+fn merge_hi<T, F, I>(values: &mut [T], mid: usize, buffer: &mut Vec<T>, compare: &mut F, probe: &mut Probe<I>)
+where
+    T: Copy,
+    F: FnMut(&T, &T) -> Ordering,
+    I: Instrument + ?Sized
+{
+    buffer.clear();
+    buffer.extend_from_slice(&values[mid..]);
+    probe.write_range(Slot::Buffer(0), Slot::Values(mid), buffer.len());
+
+    let (mut i, mut j, mut k) = (mid, buffer.len(), values.len());
+    while i > 0 && j > 0 {
+        let (mut left_wins, mut right_wins) = (0, 0);
+        while i > 0 && j > 0 && left_wins < MIN_GALLOP && right_wins < MIN_GALLOP {
+            let ordering = compare(&buffer[j - 1], &values[i - 1]);
+            if probe.compare(Slot::Buffer(j - 1), Slot::Values(i - 1), ordering) == Ordering::Less {
+                values[k - 1] = values[i - 1];
+                probe.write(Slot::Values(k - 1), Slot::Values(i - 1));
+                i -= 1;
+                left_wins += 1;
+                right_wins = 0;
+            } else {
+                values[k - 1] = buffer[j - 1];
+                probe.write(Slot::Values(k - 1), Slot::Buffer(j - 1));
+                j -= 1;
+                right_wins += 1;
+                left_wins = 0;
+            }
+
+            k -= 1;
+        }
+
+        if i == 0 || j == 0 { break }
+
+        if left_wins == MIN_GALLOP {
+            let count = gallop(i, |n| {
+                let ordering = compare(&buffer[j - 1], &values[i - 1 - n]);
+                probe.compare(Slot::Buffer(j - 1), Slot::Values(i - 1 - n), ordering) == Ordering::Less
+            });
+            values.copy_within(i - count..i, k - count);
+            probe.write_range(Slot::Values(k - count), Slot::Values(i - count), count);
+            i -= count;
+            k -= count;
+        } else {
+            let count = gallop(j, |n| {
+                let ordering = compare(&buffer[j - 1 - n], &values[i - 1]);
+                probe.compare(Slot::Buffer(j - 1 - n), Slot::Values(i - 1), ordering) != Ordering::Less
+            });
+            values[k - count..k].copy_from_slice(&buffer[j - count..j]);
+            probe.write_range(Slot::Values(k - count), Slot::Buffer(j - count), count);
+            j -= count;
+            k -= count;
+        }
+    }
+
+    values[..j].copy_from_slice(&buffer[..j]);
+    probe.write_range(Slot::Values(0), Slot::Buffer(0), j);
+}
+
+/// Counts how many of the first `len` positions satisfy `pred`, which holds
+/// for a prefix of the positions and fails for the rest
+///
+/// Positions 0, 1, 3, 7, ... are checked until one fails, which brackets the
+/// answer between two powers of two, then a binary search narrows it down.
+/// This takes _O(lg(k))_ checks, where _k_ is the answer, which is cheaper than
+/// a binary search over all `len` positions when _k_ is small.
+fn gallop<F>(len: usize, mut pred: F) -> usize
+where
+    F: FnMut(usize) -> bool
+{
+    let mut hi = 1;
+    while hi <= len && pred(hi - 1) {
+        hi *= 2;
+    }
+
+    let (mut lo, mut hi) = (hi / 2, cmp::min(hi - 1, len));
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        if pred(mid) { lo = mid + 1 } else { hi = mid }
+    }
+
+    lo
+}
+
+/// The hybrid sort as a `Sorter`
+pub struct Timsort;
+
+impl<T: Copy + PartialOrd> Sorter<T> for Timsort {
+    fn properties(&self) -> Properties {
+        Properties {
+            name: "Timsort",
+            stable: true,
+            in_place: false,
+            worst_case: Complexity::Linearithmic,
+            average_case: Complexity::Linearithmic,
+            space: Complexity::Linear,
+        }
+    }
+
+    fn sort(&self, values: &mut [T]) {
+        timsort(values);
+    }
+
+    fn sort_instrumented(&self, values: &mut [T], instrument: &mut dyn Instrument) {
+        timsort_with(values, &mut super::partial_order, &mut Probe::new(instrument));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scrambled(n: usize) -> Vec<usize> {
+        (0..n).map(|i| (i * 7919 + 13) % 1009).collect()
+    }
+
+    #[test]
+    fn test_timsort() {
+        let mut values = [5, 2, 4, 6, 1, 3];
+        timsort(&mut values);
+        assert_eq!(values, [1, 2, 3, 4, 5, 6]);
+
+        let mut empty = [0; 0];
+        timsort(&mut empty);
+        assert_eq!(empty, []);
+
+        let mut values: Vec<_> = (0..1000).rev().collect();
+        timsort(&mut values);
+        assert_eq!(values, (0..1000).collect::<Vec<_>>());
+
+        let mut values = scrambled(5000);
+        let mut expected = values.clone();
+        expected.sort();
+        timsort(&mut values);
+        assert_eq!(values, expected);
+    }
+
+    #[test]
+    fn test_timsort_runs() {
+        let mut values: Vec<_> = (0..500).chain(250..750).chain((0..300).rev()).collect();
+        let mut expected = values.clone();
+        expected.sort();
+        timsort(&mut values);
+        assert_eq!(values, expected);
+
+        let mut values: Vec<_> = (0..2000).map(|i| if i % 2 == 0 { i } else { 2000 - i }).collect();
+        let mut expected = values.clone();
+        expected.sort();
+        timsort(&mut values);
+        assert_eq!(values, expected);
+    }
+
+    #[test]
+    fn test_timsort_by_key() {
+        let mut values: Vec<_> = scrambled(3000).into_iter().map(|k| k % 10).enumerate().collect();
+        let mut expected = values.clone();
+        expected.sort_by_key(|&(_, k)| k);
+        timsort_by_key(&mut values, |&(_, k)| k);
+        assert_eq!(values, expected);
+
+        let mut values: Vec<_> = (0..1000).map(|i| (i / 100, i)).rev().collect();
+        let mut expected = values.clone();
+        expected.sort_by_key(|&(k, _)| k);
+        timsort_by_key(&mut values, |&(k, _)| k);
+        assert_eq!(values, expected);
+    }
+
+    #[test]
+    fn test_gallop() {
+        assert_eq!(gallop(0, |_| true), 0);
+        assert_eq!(gallop(10, |_| false), 0);
+        assert_eq!(gallop(10, |_| true), 10);
+        for k in 0..20 {
+            assert_eq!(gallop(20, |i| i < k), k);
+        }
+    }
+
+    #[test]
+    fn test_min_run() {
+        assert_eq!(min_run(63), 63);
+        assert_eq!(min_run(64), 32);
+        assert_eq!(min_run(65), 33);
+        assert_eq!(min_run(2112), 33);
+    }
+}
@@ -74,6 +74,20 @@
 //! |    |    |    |    |    |    |
 //! +----+----+----+----+----+----+
 //! ```
+//!
+//! The diagram is drawn from a trace of `Clrs` by `trace::Trace`, and a test
+//! checks that it is kept up to date.
+//!
+//! Every sort here has `_by` and `_by_key` forms, which order elements with a
+//! comparator or key extraction function instead of `PartialOrd`. This allows
+//! sorting in descending order or by a field of a struct without changing the
+//! algorithm.
+
+use std::cmp::Ordering;
+
+use search::binary;
+use super::instrument::{Instrument, Probe, Slot};
+use super::{Complexity, Properties, Sorter};
 
 /// The solution presented in CLRS
 ///
@@ -106,20 +120,53 @@
 pub fn clrs<T>(arr: &mut [T])
 where
     T: Copy + PartialOrd
+{
+    clrs_by(arr, super::partial_order);
+}
+
+/// The CLRS solution, ordering elements with a comparator function
+pub fn clrs_by<T, F>(arr: &mut [T], mut compare: F)
+where
+    T: Copy,
+    F: FnMut(&T, &T) -> Ordering
+{
+    clrs_with(arr, &mut compare, &mut Probe::new(&mut ()));
+}
+
+pub(super) fn clrs_with<T, F, I>(arr: &mut [T], compare: &mut F, probe: &mut Probe<I>)
+where
+    T: Copy,
+    F: FnMut(&T, &T) -> Ordering,
+    I: Instrument + ?Sized
 {
     for j in 1..arr.len() {
         let key = arr[j];
+        probe.write(Slot::Key, Slot::Values(j));
         let mut i = j;
 
-        while i > 0 && arr[i - 1] > key {
+        while i > 0
+            && probe.compare(Slot::Values(i - 1), Slot::Key, compare(&arr[i - 1], &key)) == Ordering::Greater
+        {
             arr[i] = arr[i - 1];
+            probe.write(Slot::Values(i), Slot::Values(i - 1));
             i -= 1
         }
 
         arr[i] = key;
+        probe.write(Slot::Values(i), Slot::Key);
     }
 }
 
+/// The CLRS solution, ordering elements by a key extraction function
+pub fn clrs_by_key<T, K, F>(arr: &mut [T], mut key: F)
+where
+    T: Copy,
+    K: Ord,
+    F: FnMut(&T) -> K
+{
+    clrs_by(arr, |a, b| key(a).cmp(&key(b)));
+}
+
 /// Same as CLRS but with a reduced number of comparisons made
 ///
 /// In the CLRS inner loop, we compare each element in the sorted section of the
@@ -128,28 +175,66 @@ where
 /// logarithmic time. We must still shift the elements over in linear time, so
 /// this doesn't improve the algorithm's overall running time.
 ///
+/// The search finds the upper bound of the key, the first element greater
+/// than it, which is past any elements equal to it. Equal elements therefore
+/// keep their original relative order, as they do in the CLRS version.
+///
 /// This is for CLRS exercise 2.3-6
 pub fn clrs_bsearch<T>(values: &mut [T])
 where
     T: Copy + Ord
 {
-    use super::super::search::binary;
+    clrs_bsearch_by(values, T::cmp);
+}
+
+/// The binary search solution, ordering elements with a comparator function
+pub fn clrs_bsearch_by<T, F>(values: &mut [T], mut compare: F)
+where
+    T: Copy,
+    F: FnMut(&T, &T) -> Ordering
+{
+    clrs_bsearch_from(values, 1, &mut compare, &mut Probe::new(&mut ()));
+}
 
-    for j in 1..values.len() {
+/// The binary search solution, for a slice whose first `start` elements are
+/// already sorted
+///
+/// Used to extend sorted runs in the hybrid sort.
+pub(super) fn clrs_bsearch_from<T, F, I>(values: &mut [T], start: usize, compare: &mut F, probe: &mut Probe<I>)
+where
+    T: Copy,
+    F: FnMut(&T, &T) -> Ordering,
+    I: Instrument + ?Sized
+{
+    for j in start.max(1)..values.len() {
         let key = values[j];
+        probe.write(Slot::Key, Slot::Values(j));
         let mut i = j;
 
-        if let Some(adj) = binary::search_closest(&values[0..i], &key) {
-            while i > adj {
-                values[i] = values[i - 1];
-                i -= 1
-            }
+        let adj = binary::upper_bound_by_index(i, |mid| {
+            probe.compare(Slot::Values(mid), Slot::Key, compare(&values[mid], &key))
+        });
+        while i > adj {
+            values[i] = values[i - 1];
+            probe.write(Slot::Values(i), Slot::Values(i - 1));
+            i -= 1
         }
 
         values[i] = key;
+        probe.write(Slot::Values(i), Slot::Key);
     }
 }
 
+/// The binary search solution, ordering elements by a key extraction function
+pub fn clrs_bsearch_by_key<T, K, F>(values: &mut [T], mut key: F)
+where
+    T: Copy,
+    K: Ord,
+    F: FnMut(&T) -> K
+{
+    clrs_bsearch_by(values, |a, b| key(a).cmp(&key(b)));
+}
+
 /// The solution found in [this Code Review
 /// response](https://codereview.stackexchange.com/a/142070)
 ///
@@ -163,11 +248,28 @@ where
 pub fn shepmaster<T>(arr: &mut [T])
 where
     T: PartialOrd
+{
+    shepmaster_by(arr, super::partial_order);
+}
+
+/// The Code Review solution, ordering elements with a comparator function
+pub fn shepmaster_by<T, F>(arr: &mut [T], mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering
+{
+    shepmaster_with(arr, &mut compare, &mut Probe::new(&mut ()));
+}
+
+fn shepmaster_with<T, F, I>(arr: &mut [T], compare: &mut F, probe: &mut Probe<I>)
+where
+    F: FnMut(&T, &T) -> Ordering,
+    I: Instrument + ?Sized
 {
     for i in 0..arr.len() {
         for j in (0..i).rev() {
-            if arr[j] >= arr[j + 1] {
+            if probe.compare(Slot::Values(j), Slot::Values(j + 1), compare(&arr[j], &arr[j + 1])) != Ordering::Less {
                 arr.swap(j, j + 1);
+                probe.swap(j, j + 1);
             } else {
                 break;
             }
@@ -175,30 +277,117 @@ where
     }
 }
 
+/// The Code Review solution, ordering elements by a key extraction function
+pub fn shepmaster_by_key<T, K, F>(arr: &mut [T], mut key: F)
+where
+    K: Ord,
+    F: FnMut(&T) -> K
+{
+    shepmaster_by(arr, |a, b| key(a).cmp(&key(b)));
+}
+
 /// The CLRS implementation sorting in nonincreasing order
 ///
+/// This is the CLRS solution with the comparison reversed.
+///
 /// CLRS exercise 2.1-2
 #[doc(hidden)]
 pub fn clrs_nonincreasing<T>(arr: &mut [T])
 where
     T: Copy + PartialOrd
 {
-    for j in 1..arr.len() {
-        let key = arr[j];
-        let mut i = j;
+    clrs_by(arr, |a, b| super::partial_order(b, a));
+}
 
-        while i > 0 && arr[i - 1] < key {
-            arr[i] = arr[i - 1];
-            i -= 1
+/// The CLRS solution as a `Sorter`
+pub struct Clrs;
+
+impl<T: Copy + PartialOrd> Sorter<T> for Clrs {
+    fn properties(&self) -> Properties {
+        Properties {
+            name: "Insertion Sort",
+            stable: true,
+            in_place: true,
+            worst_case: Complexity::Quadratic,
+            average_case: Complexity::Quadratic,
+            space: Complexity::Constant,
         }
+    }
 
-        arr[i] = key;
+    fn sort(&self, values: &mut [T]) {
+        clrs(values);
+    }
+
+    fn sort_instrumented(&self, values: &mut [T], instrument: &mut dyn Instrument) {
+        clrs_with(values, &mut super::partial_order, &mut Probe::new(instrument));
+    }
+}
+
+/// The binary search solution as a `Sorter`
+pub struct ClrsBsearch;
+
+impl<T: Copy + Ord> Sorter<T> for ClrsBsearch {
+    fn properties(&self) -> Properties {
+        Properties {
+            name: "Insertion Sort (binary search)",
+            stable: true,
+            in_place: true,
+            worst_case: Complexity::Quadratic,
+            average_case: Complexity::Quadratic,
+            space: Complexity::Constant,
+        }
+    }
+
+    fn sort(&self, values: &mut [T]) {
+        clrs_bsearch(values);
+    }
+
+    fn sort_instrumented(&self, values: &mut [T], instrument: &mut dyn Instrument) {
+        clrs_bsearch_from(values, 1, &mut T::cmp, &mut Probe::new(instrument));
+    }
+}
+
+/// The Code Review solution as a `Sorter`
+///
+/// Elements are swapped past those equal to them, so it is not stable.
+pub struct Shepmaster;
+
+impl<T: PartialOrd> Sorter<T> for Shepmaster {
+    fn properties(&self) -> Properties {
+        Properties {
+            name: "Insertion Sort (swapping)",
+            stable: false,
+            in_place: true,
+            worst_case: Complexity::Quadratic,
+            average_case: Complexity::Quadratic,
+            space: Complexity::Constant,
+        }
+    }
+
+    fn sort(&self, values: &mut [T]) {
+        shepmaster(values);
+    }
+
+    fn sort_instrumented(&self, values: &mut [T], instrument: &mut dyn Instrument) {
+        shepmaster_with(values, &mut super::partial_order, &mut Probe::new(instrument));
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use sort::docs;
+    use sort::trace::Trace;
+
+    #[test]
+    fn test_example_matches_docs() {
+        let values = [31, 41, 59, 26, 41, 58];
+        let mut trace = Trace::new();
+        Clrs.sort_instrumented(&mut values.clone(), &mut trace);
+
+        let diagram = trace.render_moves(&values);
+        assert!(docs(include_str!("insertion.rs")).contains(&diagram), "\n{}", diagram);
+    }
 
     #[test]
     fn test_clrs() {
@@ -245,6 +434,39 @@ mod tests {
         assert_eq!(single, [1]);
     }
 
+    #[test]
+    fn test_clrs_by() {
+        let mut arr = [5, 2, 4, 6, 1, 3];
+        clrs_by(&mut arr, |a, b| b.cmp(a));
+        assert_eq!(arr, [6, 5, 4, 3, 2, 1]);
+
+        let mut arr = [(2, 'a'), (1, 'b'), (2, 'c'), (1, 'd'), (0, 'e')];
+        clrs_by_key(&mut arr, |&(k, _)| k);
+        assert_eq!(arr, [(0, 'e'), (1, 'b'), (1, 'd'), (2, 'a'), (2, 'c')]);
+    }
+
+    #[test]
+    fn test_clrs_bsearch_by() {
+        let mut arr = [5, 2, 4, 6, 1, 3];
+        clrs_bsearch_by(&mut arr, |a, b| b.cmp(a));
+        assert_eq!(arr, [6, 5, 4, 3, 2, 1]);
+
+        let mut arr = [(2, 'a'), (1, 'b'), (2, 'c'), (1, 'd'), (2, 'e'), (1, 'f')];
+        clrs_bsearch_by_key(&mut arr, |&(k, _)| k);
+        assert_eq!(arr, [(1, 'b'), (1, 'd'), (1, 'f'), (2, 'a'), (2, 'c'), (2, 'e')]);
+    }
+
+    #[test]
+    fn test_shepmaster_by() {
+        let mut arr = [5, 2, 4, 6, 1, 3];
+        shepmaster_by(&mut arr, |a, b| b.cmp(a));
+        assert_eq!(arr, [6, 5, 4, 3, 2, 1]);
+
+        let mut arr = [(3, "c"), (1, "a"), (2, "b")];
+        shepmaster_by_key(&mut arr, |&(k, _)| k);
+        assert_eq!(arr, [(1, "a"), (2, "b"), (3, "c")]);
+    }
+
     #[test]
     fn test_clrs_nonincreasing() {
         let mut arr = [31, 41, 59, 26, 41, 58];
@@ -0,0 +1,209 @@
+//! Instrumentation of sorting algorithms
+//!
+//! The complexity of a sort is usually argued by counting the basic operations
+//! it performs, such as comparisons and element moves. Every algorithm in this
+//! module reports those operations to an `Instrument` as it runs, so the
+//! counts can be checked against the analysis in its documentation.
+//!
+//! `Metrics` tallies each kind of operation. Sorts run through their free
+//! functions report to `()`, which ignores everything, so the instrumentation
+//! compiles away when it isn't used.
+//!
+//! Operations on an element of the slice being sorted refer to it by its index
+//! in the whole slice, even when the algorithm is working on a subslice.
+
+use std::cmp::Ordering;
+
+/// The location of an element involved in an operation
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Slot {
+    /// An index of the slice being sorted
+    Values(usize),
+    /// An index of the left half copied out by a merge
+    Left(usize),
+    /// An index of the right half copied out by a merge
+    Right(usize),
+    /// An index of any other auxiliary storage
+    Buffer(usize),
+    /// An element held outside of the slice, such as the key in insertion
+    /// sort
+    Key,
+}
+
+/// Receives the operations performed by a sort
+///
+/// Every method does nothing by default, so implementations only need to
+/// handle the operations they care about.
+pub trait Instrument {
+    /// Two elements are compared
+    fn compare(&mut self, _a: Slot, _b: Slot) {}
+
+    /// Two elements of the slice being sorted are swapped
+    fn swap(&mut self, _i: usize, _j: usize) {}
+
+    /// An element is copied or moved from one slot to another
+    fn write(&mut self, _to: Slot, _from: Slot) {}
+
+    /// Auxiliary storage is allocated for `len` elements
+    fn allocate(&mut self, _len: usize) {}
+}
+
+impl Instrument for () {}
+
+/// Counts the operations performed by a sort
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Metrics {
+    pub comparisons: usize,
+    pub swaps: usize,
+    /// Elements copied or moved, not counting swaps
+    pub writes: usize,
+    /// Separate allocations of auxiliary storage
+    pub allocations: usize,
+    /// Total elements of auxiliary storage allocated
+    pub allocated: usize,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Instrument for Metrics {
+    fn compare(&mut self, _a: Slot, _b: Slot) {
+        self.comparisons += 1;
+    }
+
+    fn swap(&mut self, _i: usize, _j: usize) {
+        self.swaps += 1;
+    }
+
+    fn write(&mut self, _to: Slot, _from: Slot) {
+        self.writes += 1;
+    }
+
+    fn allocate(&mut self, len: usize) {
+        self.allocations += 1;
+        self.allocated += len;
+    }
+}
+
+/// Reports operations on a subslice to an `Instrument`
+///
+/// Indices of the subslice are offset to be indices of the whole slice.
+/// Narrowing a probe to a subslice with `at` gives another probe of the same
+/// type, so recursive sorts don't build up nested types.
+pub(crate) struct Probe<'a, I: Instrument + ?Sized + 'a> {
+    instrument: &'a mut I,
+    offset: usize,
+}
+
+impl<'a, I: Instrument + ?Sized> Probe<'a, I> {
+    pub fn new(instrument: &'a mut I) -> Self {
+        Probe { instrument, offset: 0 }
+    }
+
+    /// Narrows the probe to the subslice starting at `offset`
+    pub fn at(&mut self, offset: usize) -> Probe<'_, I> {
+        Probe { instrument: &mut *self.instrument, offset: self.offset + offset }
+    }
+
+    /// Reports a comparison, passing its result through
+    pub fn compare(&mut self, a: Slot, b: Slot, ordering: Ordering) -> Ordering {
+        let (a, b) = (self.slot(a), self.slot(b));
+        self.instrument.compare(a, b);
+        ordering
+    }
+
+    pub fn swap(&mut self, i: usize, j: usize) {
+        self.instrument.swap(self.offset + i, self.offset + j);
+    }
+
+    pub fn write(&mut self, to: Slot, from: Slot) {
+        let (to, from) = (self.slot(to), self.slot(from));
+        self.instrument.write(to, from);
+    }
+
+    /// Reports `len` writes between consecutive slots, as made by a copy of a
+    /// range of elements
+    pub fn write_range(&mut self, to: Slot, from: Slot, len: usize) {
+        for n in 0..len {
+            self.write(shift(to, n), shift(from, n));
+        }
+    }
+
+    pub fn allocate(&mut self, len: usize) {
+        self.instrument.allocate(len);
+    }
+
+    fn slot(&self, slot: Slot) -> Slot {
+        match slot {
+            Slot::Values(i) => Slot::Values(self.offset + i),
+            other => other,
+        }
+    }
+}
+
+fn shift(slot: Slot, n: usize) -> Slot {
+    match slot {
+        Slot::Values(i) => Slot::Values(i + n),
+        Slot::Left(i) => Slot::Left(i + n),
+        Slot::Right(i) => Slot::Right(i + n),
+        Slot::Buffer(i) => Slot::Buffer(i + n),
+        Slot::Key => Slot::Key,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sort::{insertion, merge, selection, Sorter};
+
+    fn count<S: Sorter<u32>>(sorter: S, values: &mut [u32]) -> Metrics {
+        let mut metrics = Metrics::new();
+        sorter.sort_instrumented(values, &mut metrics);
+        metrics
+    }
+
+    #[test]
+    fn test_insertion_sort_best_case() {
+        let mut values: Vec<u32> = (0..100).collect();
+        let metrics = count(insertion::Clrs, &mut values);
+        assert_eq!(metrics.comparisons, 99);
+        assert_eq!(metrics.allocations, 0);
+    }
+
+    #[test]
+    fn test_insertion_sort_worst_case() {
+        // Each key is compared with, and shifts past, every element before it
+        let mut values: Vec<u32> = (0..100).rev().collect();
+        let metrics = count(insertion::Clrs, &mut values);
+        assert_eq!(metrics.comparisons, 100 * 99 / 2);
+    }
+
+    #[test]
+    fn test_selection_sort() {
+        let mut values: Vec<u32> = (0..100).map(|i| (i * 37) % 101).collect();
+        let metrics = count(selection::Selection, &mut values);
+        assert_eq!(metrics.comparisons, 100 * 99 / 2);
+        assert_eq!(metrics.swaps, 99);
+    }
+
+    #[test]
+    fn test_merge_sort() {
+        // T(n) = 2T(n/2) + cn, where each merge copies every element out and
+        // back, so c = 2 and the lg(n) levels above the leaves write 2n lg(n)
+        let (n, lg) = (1024, 10);
+        let mut values: Vec<u32> = (0..n as u32).map(|i| (i * 7919) % 1031).collect();
+        let metrics = count(merge::ClrsMergeSort, &mut values);
+        assert_eq!(metrics.writes, 2 * n * lg);
+        assert!(metrics.comparisons >= n * lg / 2);
+        assert!(metrics.comparisons <= n * lg - n + 1);
+        assert_eq!(metrics.allocations, 2 * (n - 1));
+        assert_eq!(metrics.allocated, n * lg);
+
+        // Every merge with sentinels makes one comparison per element
+        let metrics = count(merge::ClrsMergeSortSentinel, &mut values);
+        assert_eq!(metrics.comparisons, n * lg);
+    }
+}
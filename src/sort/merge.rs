@@ -110,25 +110,208 @@
 //! |    |   |    |   |    |   |    |   |    |   |    |   |    |   |    |
 //! +----+   +----+   +----+   +----+   +----+   +----+   +----+   +----+
 //! ```
+//!
+//! A test checks the diagram against one drawn from a trace of
+//! `ClrsMergeSort` by `trace::Trace`, which spaces the labels and arrows a
+//! little more evenly.
+//!
+//! # Merging iterators
+//!
+//! The combine step works just as well on iterators. `kmerge` lazily merges
+//! any number of sorted iterators and `merge_iters` merges two, while `dedup`,
+//! `union`, `intersection` and `difference` combine sorted streams as sets.
+//!
+//! ```
+//! use kb::sort::merge;
+//!
+//! let merged: Vec<u32> = merge::kmerge(vec![vec![1, 4], vec![2, 5], vec![3]]).collect();
+//! assert_eq!(merged, [1, 2, 3, 4, 5]);
+//!
+//! let common: Vec<u32> = merge::intersection(vec![1, 2, 4], vec![2, 3, 4]).collect();
+//! assert_eq!(common, [2, 4]);
+//! ```
+
+use std::cmp::Ordering;
+use std::iter::Peekable;
+use std::thread;
+
+use heap::binary as heap;
+use search::binary;
+use super::instrument::{Instrument, Probe, Slot};
+use super::{Complexity, Properties, Sorter};
 
 /// The solution presented in CLRS
-pub fn clrs_merge_sort(mut values: &mut [f64], p: usize, r: usize) {
+///
+/// Sorts the subsequence `values[p..r]`. To sort the whole slice, `p` is `0`
+/// and `r` is `values.len()`.
+pub fn clrs_merge_sort<T>(values: &mut [T], p: usize, r: usize)
+where
+    T: Ord + Clone
+{
+    clrs_merge_sort_by(values, p, r, T::cmp);
+}
+
+/// The CLRS solution, ordering elements with a comparator function
+pub fn clrs_merge_sort_by<T, F>(values: &mut [T], p: usize, r: usize, mut compare: F)
+where
+    T: Clone,
+    F: FnMut(&T, &T) -> Ordering
+{
+    merge_sort_with(values, p, r, &mut compare, &mut Probe::new(&mut ()));
+}
+
+/// The CLRS solution, ordering elements by a key extraction function
+pub fn clrs_merge_sort_by_key<T, K, F>(values: &mut [T], p: usize, r: usize, mut key: F)
+where
+    T: Clone,
+    K: Ord,
+    F: FnMut(&T) -> K
+{
+    clrs_merge_sort_by(values, p, r, |a, b| key(a).cmp(&key(b)));
+}
+
+pub(super) fn merge_sort_with<T, F, I>(values: &mut [T], p: usize, r: usize, compare: &mut F, probe: &mut Probe<I>)
+where
+    T: Clone,
+    F: FnMut(&T, &T) -> Ordering,
+    I: Instrument + ?Sized
+{
     if r > 0 && p < r - 1 {
         let q = (p + r) / 2;
 
-        clrs_merge_sort(&mut values, p, q);
-        clrs_merge_sort(&mut values, q, r);
-        clrs_merge(&mut values, p, q, r);
+        merge_sort_with(values, p, q, compare, probe);
+        merge_sort_with(values, q, r, compare, probe);
+        merge_with(values, p, q, r, compare, probe);
     }
 }
 
 /// CLRS version of the merge operation used by merge sort
 ///
+/// Merges the two sorted subsequences `values[p..q]` and `values[q..r]` in
+/// _O(n)_ time, where *n = r - p*.
+///
+/// Rather than placing a sentinel at the end of each copied subsequence, the
+/// loop stops as soon as either `left` or `right` has had all of its elements
+/// copied back into `values`. The remainder of the other is then copied back
+/// as is, because it is already sorted and every element in it is at least as
+/// large as those already copied. This frees the merge from needing a logical
+/// maximum value, so it works for any ordered type.
+///
+/// When `left[i]` and `right[j]` are equal, `left[i]` is copied first, so
+/// equal elements keep their original relative order and the sort is stable.
+///
+/// This is for CLRS exercise 2.3-2
+///
+/// # Loop invariant
+///
+/// At the start of each iteration of the merge loop, `values[p..k]` contains
+/// the *k - p* smallest elements of `left[0..n1]` and `right[0..n2]`, in
+/// sorted order. Also, `left[i]` and `right[j]` are the smallest elements of
+/// their slices that have yet to be copied back into `values`.
+///
+/// *Initialization*: To begin with, *k = p*, so `values[p..k]` is empty, which
+/// contains the zero smallest elements of `left` and `right`. Also, `i` and `j`
+/// point to the first elements of their slices, which are the smallest elements
+/// because the slices are sorted.
+///
+/// *Maintenance*: If `left[i]` is less than or equal to `right[j]`, `left[i]`
+/// will be copied, meaning `values[p..k + 1]` will now contain the *k - p + 1*
+/// smallest elements. *k* and *i* are both incremented, reestablishing the
+/// loop invariant for the next iteration. This process is symmetric if `right`
+/// holds the next smallest element.
+///
+/// *Termination*: The loop terminates when *i = n1* or *j = n2*. One of the
+/// slices has been entirely copied, and `values[p..k]` holds the *k - p*
+/// smallest elements. The rest of the other slice holds the *r - k* largest
+/// elements, in sorted order, and is copied into `values[k..r]`.
+pub fn clrs_merge<T>(values: &mut [T], p: usize, q: usize, r: usize)
+where
+    T: Ord + Clone
+{
+    clrs_merge_by(values, p, q, r, T::cmp);
+}
+
+/// CLRS version of the merge operation, ordering elements with a comparator
+/// function
+pub fn clrs_merge_by<T, F>(values: &mut [T], p: usize, q: usize, r: usize, mut compare: F)
+where
+    T: Clone,
+    F: FnMut(&T, &T) -> Ordering
+{
+    merge_with(values, p, q, r, &mut compare, &mut Probe::new(&mut ()));
+}
+
+fn merge_with<T, F, I>(values: &mut [T], p: usize, q: usize, r: usize, compare: &mut F, probe: &mut Probe<I>)
+where
+    T: Clone,
+    F: FnMut(&T, &T) -> Ordering,
+    I: Instrument + ?Sized
+{
+    let left = values[p..q].to_vec();
+    probe.allocate(left.len());
+    probe.write_range(Slot::Left(0), Slot::Values(p), left.len());
+
+    let right = values[q..r].to_vec();
+    probe.allocate(right.len());
+    probe.write_range(Slot::Right(0), Slot::Values(q), right.len());
+
+    let (mut i, mut j, mut k) = (0, 0, p);
+    while i < left.len() && j < right.len() {
+        let ordering = compare(&left[i], &right[j]);
+        if probe.compare(Slot::Left(i), Slot::Right(j), ordering) != Ordering::Greater {
+            values[k] = left[i].clone();
+            probe.write(Slot::Values(k), Slot::Left(i));
+            i += 1;
+        } else {
+            values[k] = right[j].clone();
+            probe.write(Slot::Values(k), Slot::Right(j));
+            j += 1;
+        }
+
+        k += 1;
+    }
+
+    if i < left.len() {
+        values[k..r].clone_from_slice(&left[i..]);
+        probe.write_range(Slot::Values(k), Slot::Left(i), r - k);
+    } else {
+        values[k..r].clone_from_slice(&right[j..]);
+        probe.write_range(Slot::Values(k), Slot::Right(j), r - k);
+    }
+}
+
+/// The CLRS solution, using the sentinel version of the merge operation
+pub fn clrs_merge_sort_sentinel<T>(values: &mut [T], p: usize, r: usize)
+where
+    T: Ord + Clone
+{
+    merge_sort_sentinel_with(values, p, r, &mut Probe::new(&mut ()));
+}
+
+fn merge_sort_sentinel_with<T, I>(values: &mut [T], p: usize, r: usize, probe: &mut Probe<I>)
+where
+    T: Ord + Clone,
+    I: Instrument + ?Sized
+{
+    if r > 0 && p < r - 1 {
+        let q = (p + r) / 2;
+
+        merge_sort_sentinel_with(values, p, q, probe);
+        merge_sort_sentinel_with(values, q, r, probe);
+        merge_sentinel_with(values, p, q, r, probe);
+    }
+}
+
+/// CLRS version of the merge operation exactly as it appears in the book
+///
 /// Merges two sorted subsequences in _O(n)_ time, where *n = r - p*.
 ///
-/// This implementation includes a sentinel value to simplify the
-/// implementation. It limits the sort to types that have a logical maximum
-/// value.
+/// This implementation includes a sentinel value, ∞, at the end of `left` and
+/// `right` to simplify the implementation. Neither slice can then be exhausted
+/// before `values[p..r]` is filled, so there is no need to check for it. In
+/// the book, ∞ is a logical maximum value. Not every type has one, so here
+/// elements are wrapped in `Option` and `None` plays the part of ∞, comparing
+/// greater than every `Some`.
 ///
 /// # Loop invariant
 ///
@@ -142,8 +325,8 @@ pub fn clrs_merge_sort(mut values: &mut [f64], p: usize, r: usize) {
 /// point to the first elements of their slices, which are the smallest elements
 /// because the slices are sorted.
 ///
-/// *Maintenance*: If `left[i]` is less than `right[j]`, `left[i]` will be
-/// copied, meaning `values[p..k + 1]` will now contain the *k - p + 1*
+/// *Maintenance*: If `left[i]` is less than or equal to `right[j]`, `left[i]`
+/// will be copied, meaning `values[p..k + 1]` will now contain the *k - p + 1*
 /// smallest elements. *k* and *i* are both incremented, reestablishing the
 /// loop invariant for the next iteration. This process is symmetric if `right`
 /// holds the next smallest element.
@@ -151,7 +334,7 @@ pub fn clrs_merge_sort(mut values: &mut [f64], p: usize, r: usize) {
 /// *Termination*: Upon termination, *k = r*, so `values[p..k]` is
 /// `values[p..r]`, and holds the *k - p* smallest elements in `left` and
 /// `right`. `left` and `right` are both pointing to their sentinel values,
-/// which are the smallest values in their respective slices.
+/// which are the only values yet to be copied from their respective slices.
 ///
 /// # Example
 ///
@@ -313,25 +496,46 @@ pub fn clrs_merge_sort(mut values: &mut [f64], p: usize, r: usize) {
 ///   +---+---+---+---+---+       +---+---+---+---+---+
 ///                     i                           j
 /// ```
-pub fn clrs_merge(values: &mut [f64], p: usize, q: usize, r: usize) {
-    use std::f64;
+pub fn clrs_merge_sentinel<T>(values: &mut [T], p: usize, q: usize, r: usize)
+where
+    T: Ord + Clone
+{
+    merge_sentinel_with(values, p, q, r, &mut Probe::new(&mut ()));
+}
 
+fn merge_sentinel_with<T, I>(values: &mut [T], p: usize, q: usize, r: usize, probe: &mut Probe<I>)
+where
+    T: Ord + Clone,
+    I: Instrument + ?Sized
+{
     let mut left = Vec::with_capacity(q - p + 1);
-    for i in 0..left.capacity() - 1 { left.push(values[p + i]); }
-    left.push(f64::MAX);
+    probe.allocate(left.capacity());
+    left.extend(values[p..q].iter().cloned().map(Some));
+    probe.write_range(Slot::Left(0), Slot::Values(p), q - p);
+    left.push(None);
 
     let mut right = Vec::with_capacity(r - q + 1);
-    for j in 0..right.capacity() - 1 { right.push(values[q + j]); }
-    right.push(f64::MAX);
+    probe.allocate(right.capacity());
+    right.extend(values[q..r].iter().cloned().map(Some));
+    probe.write_range(Slot::Right(0), Slot::Values(q), r - q);
+    right.push(None);
 
     let mut i = 0;
     let mut j = 0;
-    for k in p..r {
-        if left[i] < right[j] {
-            values[k] = left[i];
+    for (k, value) in (p..r).zip(&mut values[p..r]) {
+        let ordering = match (&left[i], &right[j]) {
+            (Some(l), Some(r)) => l.cmp(r),
+            (Some(_), None) => Ordering::Less,
+            (None, _) => Ordering::Greater,
+        };
+
+        if probe.compare(Slot::Left(i), Slot::Right(j), ordering) != Ordering::Greater {
+            *value = left[i].take().unwrap();
+            probe.write(Slot::Values(k), Slot::Left(i));
             i += 1;
         } else {
-            values[k] = right[j];
+            *value = right[j].take().unwrap();
+            probe.write(Slot::Values(k), Slot::Right(j));
             j += 1;
         }
     }
@@ -345,24 +549,69 @@ pub fn clrs_merge(values: &mut [f64], p: usize, q: usize, r: usize) {
 pub fn krw_merge_sort<T>(values: &mut [T])
 where
     T: Copy + PartialOrd
+{
+    krw_merge_sort_by(values, super::partial_order);
+}
+
+/// My own implementation, ordering elements with a comparator function
+///
+/// The left element is taken whenever it is not greater than the right one,
+/// so equal elements keep their original relative order.
+pub fn krw_merge_sort_by<T, F>(values: &mut [T], mut compare: F)
+where
+    T: Copy,
+    F: FnMut(&T, &T) -> Ordering
+{
+    krw_sort_with(values, &mut compare, &mut Probe::new(&mut ()));
+}
+
+/// My own implementation, ordering elements by a key extraction function
+pub fn krw_merge_sort_by_key<T, K, F>(values: &mut [T], mut key: F)
+where
+    T: Copy,
+    K: Ord,
+    F: FnMut(&T) -> K
+{
+    krw_merge_sort_by(values, |a, b| key(a).cmp(&key(b)));
+}
+
+fn krw_sort_with<T, F, I>(values: &mut [T], compare: &mut F, probe: &mut Probe<I>)
+where
+    T: Copy,
+    F: FnMut(&T, &T) -> Ordering,
+    I: Instrument + ?Sized
 {
     if values.len() > 1 {
         let mid = values.len() / 2;
-        krw_merge_sort(&mut values[..mid]);
-        krw_merge_sort(&mut values[mid..]);
+        krw_sort_with(&mut values[..mid], compare, &mut probe.at(0));
+        krw_sort_with(&mut values[mid..], compare, &mut probe.at(mid));
 
         let left = values[..mid].to_vec();
-        let mut left_iter = left.iter().peekable();
+        probe.allocate(left.len());
+        probe.write_range(Slot::Left(0), Slot::Values(0), left.len());
+        let mut left_iter = left.iter().enumerate().peekable();
+
         let right = values[mid..].to_vec();
-        let mut right_iter = right.iter().peekable();
-
-        for k in values.iter_mut() {
-            if left_iter.peek().is_some() && left_iter.peek() < right_iter.peek() {
-                *k = *left_iter.next().unwrap();
-            } else if let Some(r) = right_iter.next() {
-                *k = *r;
-            } else if let Some(l) = left_iter.next() {
-                *k = *l;
+        probe.allocate(right.len());
+        probe.write_range(Slot::Right(0), Slot::Values(mid), right.len());
+        let mut right_iter = right.iter().enumerate().peekable();
+
+        for (k, value) in values.iter_mut().enumerate() {
+            let take_left = match (left_iter.peek(), right_iter.peek()) {
+                (Some(&(i, l)), Some(&(j, r))) => {
+                    probe.compare(Slot::Left(i), Slot::Right(j), compare(l, r)) != Ordering::Greater
+                }
+                (Some(_), None) => true,
+                (None, _) => false,
+            };
+
+            if take_left {
+                let (i, l) = left_iter.next().unwrap();
+                *value = *l;
+                probe.write(Slot::Values(k), Slot::Left(i));
+            } else if let Some((j, r)) = right_iter.next() {
+                *value = *r;
+                probe.write(Slot::Values(k), Slot::Right(j));
             } else {
                 unreachable!();
             }
@@ -370,33 +619,680 @@ where
     }
 }
 
+/// A grain size that keeps the cost of spawning a thread small next to the
+/// work it does
+pub const DEFAULT_GRAIN: usize = 4096;
+
+/// A parallel version of my own implementation, using scoped threads
+///
+/// The two halves are sorted on separate threads, then merged in parallel,
+/// using a buffer of _n_ elements which is allocated once. Slices of at most
+/// `grain` elements are sorted or merged sequentially. `DEFAULT_GRAIN` suits
+/// most inputs.
+///
+/// No more threads run at once than the available parallelism. Once they are
+/// all busy, the work is still split down to the grain size, but both halves
+/// run on the current thread.
+///
+/// The sort is stable, so the output is identical to `krw_merge_sort` for any
+/// grain size.
+pub fn par_merge_sort<T>(values: &mut [T], grain: usize)
+where
+    T: Copy + PartialOrd + Send + Sync
+{
+    par_merge_sort_by(values, grain, super::partial_order);
+}
+
+/// The parallel version, ordering elements with a comparator function
+///
+/// The comparator is shared between threads, so it must be `Fn` and `Sync`.
+pub fn par_merge_sort_by<T, F>(values: &mut [T], grain: usize, compare: F)
+where
+    T: Copy + Send + Sync,
+    F: Fn(&T, &T) -> Ordering + Sync
+{
+    let threads = thread::available_parallelism().map_or(1, |threads| threads.get());
+    let mut buffer = values.to_vec();
+    par_sort_by(values, &mut buffer, grain.max(1), threads, &compare);
+}
+
+/// The parallel version, ordering elements by a key extraction function
+pub fn par_merge_sort_by_key<T, K, F>(values: &mut [T], grain: usize, key: F)
+where
+    T: Copy + Send + Sync,
+    K: Ord,
+    F: Fn(&T) -> K + Sync
+{
+    par_merge_sort_by(values, grain, |a, b| key(a).cmp(&key(b)));
+}
+
+/// Sorts `values`, using `buffer`, which is the same length, for the merge
+fn par_sort_by<T, F>(values: &mut [T], buffer: &mut [T], grain: usize, threads: usize, compare: &F)
+where
+    T: Copy + Send + Sync,
+    F: Fn(&T, &T) -> Ordering + Sync
+{
+    if values.len() <= grain {
+        krw_sort_with(values, &mut |a: &T, b: &T| compare(a, b), &mut Probe::new(&mut ()));
+        return;
+    }
+
+    let mid = values.len() / 2;
+    {
+        let (left, right) = values.split_at_mut(mid);
+        let (left_buffer, right_buffer) = buffer.split_at_mut(mid);
+        join(
+            threads,
+            |threads| par_sort_by(left, left_buffer, grain, threads, compare),
+            |threads| par_sort_by(right, right_buffer, grain, threads, compare),
+        );
+    }
+
+    buffer.copy_from_slice(values);
+    let (left, right) = buffer.split_at(mid);
+    par_merge_by(left, right, values, grain, threads, compare);
+}
+
+/// Merges the sorted slices `left` and `right` into `out`, splitting the work
+/// between threads
+///
+/// The middle element of the longer slice is the pivot. A binary search finds
+/// where it splits the shorter slice, which gives the pivot's position in
+/// `out`, and the elements on either side of it are merged independently.
+///
+/// For stability, elements of `left` equal to the pivot must stay before it
+/// when it comes from `right`, and elements of `right` equal to the pivot
+/// must stay after it when it comes from `left`. So `right` is split at the
+/// first element not less than a pivot from `left`, and `left` is split at
+/// the first element greater than a pivot from `right`.
+fn par_merge_by<T, F>(left: &[T], right: &[T], out: &mut [T], grain: usize, threads: usize, compare: &F)
+where
+    T: Copy + Send + Sync,
+    F: Fn(&T, &T) -> Ordering + Sync
+{
+    if out.len() <= grain || left.is_empty() || right.is_empty() {
+        merge_into(left, right, out, compare);
+        return;
+    }
+
+    let (pivot, (left_lower, left_upper), (right_lower, right_upper)) = if left.len() >= right.len() {
+        let i = left.len() / 2;
+        let j = binary::lower_bound_by(right, |value| compare(value, &left[i]));
+        (left[i], (&left[..i], &left[i + 1..]), right.split_at(j))
+    } else {
+        let j = right.len() / 2;
+        let i = binary::upper_bound_by(left, |value| compare(value, &right[j]));
+        (right[j], left.split_at(i), (&right[..j], &right[j + 1..]))
+    };
+
+    let (lower, rest) = out.split_at_mut(left_lower.len() + right_lower.len());
+    let (middle, upper) = rest.split_first_mut().unwrap();
+    *middle = pivot;
+    join(
+        threads,
+        |threads| par_merge_by(left_lower, right_lower, lower, grain, threads, compare),
+        |threads| par_merge_by(left_upper, right_upper, upper, grain, threads, compare),
+    );
+}
+
+/// Runs `a` on a new scoped thread and `b` on the current one, sharing out
+/// `threads` between them, or runs both on the current thread if there is
+/// only one
+fn join<A, B>(threads: usize, a: A, b: B)
+where
+    A: FnOnce(usize) + Send,
+    B: FnOnce(usize)
+{
+    if threads > 1 {
+        thread::scope(|scope| {
+            scope.spawn(|| a(threads / 2));
+            b(threads - threads / 2);
+        });
+    } else {
+        a(1);
+        b(1);
+    }
+}
+
+/// Merges the sorted slices `left` and `right` into `out` on the current
+/// thread, taking from `left` when elements are equal
+fn merge_into<T, F>(left: &[T], right: &[T], out: &mut [T], compare: &F)
+where
+    T: Copy,
+    F: Fn(&T, &T) -> Ordering
+{
+    let (mut i, mut j) = (0, 0);
+    for value in out.iter_mut() {
+        if j == right.len() || (i < left.len() && compare(&left[i], &right[j]) != Ordering::Greater) {
+            *value = left[i];
+            i += 1;
+        } else {
+            *value = right[j];
+            j += 1;
+        }
+    }
+}
+
+/// The comparator of iterators over `Ord` elements, which is `Ord::cmp`
+pub type Natural<T> = fn(&T, &T) -> Ordering;
+
+/// Lazily merges any number of sorted iterators into one sorted iterator
+///
+/// The front element of each iterator is kept in a min-heap, so each element
+/// is produced in _O(lg(k))_ time for _k_ iterators. Equal elements are
+/// produced in the order of the iterators they came from, so the merge is
+/// stable.
+pub struct KMerge<I: Iterator, F> {
+    iters: Vec<I>,
+    heads: Vec<(I::Item, usize)>,
+    compare: F,
+}
+
+/// Merges sorted iterators
+pub fn kmerge<I>(iters: I) -> KMerge<<I::Item as IntoIterator>::IntoIter, Natural<<I::Item as IntoIterator>::Item>>
+where
+    I: IntoIterator,
+    I::Item: IntoIterator,
+    <I::Item as IntoIterator>::Item: Ord
+{
+    kmerge_by(iters, Ord::cmp)
+}
+
+/// Merges iterators that are sorted with respect to a comparator function
+pub fn kmerge_by<I, F>(iters: I, mut compare: F) -> KMerge<<I::Item as IntoIterator>::IntoIter, F>
+where
+    I: IntoIterator,
+    I::Item: IntoIterator,
+    F: FnMut(&<I::Item as IntoIterator>::Item, &<I::Item as IntoIterator>::Item) -> Ordering
+{
+    let mut iters: Vec<_> = iters.into_iter().map(IntoIterator::into_iter).collect();
+    let mut heads: Vec<_> = iters.iter_mut().enumerate().filter_map(|(i, iter)| iter.next().map(|head| (head, i))).collect();
+    heap::build_heap_by(&mut heads, |a, b| reverse(&mut compare, a, b));
+
+    KMerge { iters, heads, compare }
+}
+
+impl<I, F> Iterator for KMerge<I, F>
+where
+    I: Iterator,
+    F: FnMut(&I::Item, &I::Item) -> Ordering
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<I::Item> {
+        if self.heads.is_empty() { return None }
+
+        let i = self.heads[0].1;
+        let head = match self.iters[i].next() {
+            Some(next) => std::mem::replace(&mut self.heads[0], (next, i)).0,
+            None => self.heads.swap_remove(0).0,
+        };
+
+        let compare = &mut self.compare;
+        heap::sift_down_by(&mut self.heads, 0, |a, b| reverse(compare, a, b));
+        Some(head)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.heads.len();
+        self.iters.iter().fold((len, Some(len)), |(lo, hi), iter| {
+            let (l, h) = iter.size_hint();
+            (lo.saturating_add(l), hi.and_then(|hi| h.and_then(|h| hi.checked_add(h))))
+        })
+    }
+}
+
+/// Orders heads for a max-heap so the smallest, from the earliest iterator, is
+/// at the root
+fn reverse<T, F>(compare: &mut F, a: &(T, usize), b: &(T, usize)) -> Ordering
+where
+    F: FnMut(&T, &T) -> Ordering
+{
+    compare(&b.0, &a.0).then(b.1.cmp(&a.1))
+}
+
+/// Lazily merges two sorted iterators, taking from the first when elements
+/// are equal
+pub struct MergeIters<A: Iterator, B: Iterator, F> {
+    a: Peekable<A>,
+    b: Peekable<B>,
+    compare: F,
+}
+
+/// Merges two sorted iterators
+pub fn merge_iters<A, B>(a: A, b: B) -> MergeIters<A::IntoIter, B::IntoIter, Natural<A::Item>>
+where
+    A: IntoIterator,
+    B: IntoIterator<Item = A::Item>,
+    A::Item: Ord
+{
+    merge_iters_by(a, b, Ord::cmp)
+}
+
+/// Merges two iterators that are sorted with respect to a comparator function
+pub fn merge_iters_by<A, B, F>(a: A, b: B, compare: F) -> MergeIters<A::IntoIter, B::IntoIter, F>
+where
+    A: IntoIterator,
+    B: IntoIterator<Item = A::Item>,
+    F: FnMut(&A::Item, &A::Item) -> Ordering
+{
+    MergeIters { a: a.into_iter().peekable(), b: b.into_iter().peekable(), compare }
+}
+
+impl<A, B, F> Iterator for MergeIters<A, B, F>
+where
+    A: Iterator,
+    B: Iterator<Item = A::Item>,
+    F: FnMut(&A::Item, &A::Item) -> Ordering
+{
+    type Item = A::Item;
+
+    fn next(&mut self) -> Option<A::Item> {
+        let take_a = match (self.a.peek(), self.b.peek()) {
+            (Some(a), Some(b)) => (self.compare)(a, b) != Ordering::Greater,
+            (Some(_), None) => true,
+            (None, _) => false,
+        };
+
+        if take_a { self.a.next() } else { self.b.next() }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let ((a_lo, a_hi), (b_lo, b_hi)) = (self.a.size_hint(), self.b.size_hint());
+        (a_lo.saturating_add(b_lo), a_hi.and_then(|a| b_hi.and_then(|b| a.checked_add(b))))
+    }
+}
+
+/// Removes consecutive equal elements from a sorted iterator, keeping the
+/// first of each
+pub struct Dedup<I: Iterator, F> {
+    iter: Peekable<I>,
+    compare: F,
+}
+
+/// Removes duplicates from a sorted iterator
+pub fn dedup<I>(iter: I) -> Dedup<I::IntoIter, Natural<I::Item>>
+where
+    I: IntoIterator,
+    I::Item: Ord
+{
+    dedup_by(iter, Ord::cmp)
+}
+
+/// Removes elements that a comparator function finds equal to the one before
+pub fn dedup_by<I, F>(iter: I, compare: F) -> Dedup<I::IntoIter, F>
+where
+    I: IntoIterator,
+    F: FnMut(&I::Item, &I::Item) -> Ordering
+{
+    Dedup { iter: iter.into_iter().peekable(), compare }
+}
+
+impl<I, F> Iterator for Dedup<I, F>
+where
+    I: Iterator,
+    F: FnMut(&I::Item, &I::Item) -> Ordering
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<I::Item> {
+        let first = self.iter.next()?;
+        while let Some(next) = self.iter.peek() {
+            if (self.compare)(&first, next) != Ordering::Equal { break }
+            self.iter.next();
+        }
+
+        Some(first)
+    }
+}
+
+/// The front elements of two sorted iterators, compared
+///
+/// Used by the set operations, which treat each iterator as a sorted multiset.
+/// An element of one is matched with at most one equal element of the other,
+/// so an element that appears twice in `a` and once in `b` is in the union
+/// twice, the intersection once and the difference once.
+struct Fronts<A: Iterator, B: Iterator, F> {
+    a: Peekable<A>,
+    b: Peekable<B>,
+    compare: F,
+}
+
+impl<A, B, F> Fronts<A, B, F>
+where
+    A: Iterator,
+    B: Iterator<Item = A::Item>,
+    F: FnMut(&A::Item, &A::Item) -> Ordering
+{
+    fn new(a: A, b: B, compare: F) -> Self {
+        Fronts { a: a.peekable(), b: b.peekable(), compare }
+    }
+
+    /// Compares the front elements, treating a missing element as larger than
+    /// any other, or returns `None` if both iterators are empty
+    fn cmp(&mut self) -> Option<Ordering> {
+        match (self.a.peek(), self.b.peek()) {
+            (Some(a), Some(b)) => Some((self.compare)(a, b)),
+            (Some(_), None) => Some(Ordering::Less),
+            (None, Some(_)) => Some(Ordering::Greater),
+            (None, None) => None,
+        }
+    }
+}
+
+/// The elements of either of two sorted iterators, in sorted order
+pub struct Union<A: Iterator, B: Iterator, F>(Fronts<A, B, F>);
+
+/// The elements of both of two sorted iterators, in sorted order
+pub struct Intersection<A: Iterator, B: Iterator, F>(Fronts<A, B, F>);
+
+/// The elements of one sorted iterator that aren't in another, in sorted
+/// order
+pub struct Difference<A: Iterator, B: Iterator, F>(Fronts<A, B, F>);
+
+/// Returns the union of two sorted iterators
+pub fn union<A, B>(a: A, b: B) -> Union<A::IntoIter, B::IntoIter, Natural<A::Item>>
+where
+    A: IntoIterator,
+    B: IntoIterator<Item = A::Item>,
+    A::Item: Ord
+{
+    union_by(a, b, Ord::cmp)
+}
+
+/// Returns the union of two iterators sorted with respect to a comparator
+/// function, taking elements from `a` when they are in both
+pub fn union_by<A, B, F>(a: A, b: B, compare: F) -> Union<A::IntoIter, B::IntoIter, F>
+where
+    A: IntoIterator,
+    B: IntoIterator<Item = A::Item>,
+    F: FnMut(&A::Item, &A::Item) -> Ordering
+{
+    Union(Fronts::new(a.into_iter(), b.into_iter(), compare))
+}
+
+/// Returns the intersection of two sorted iterators
+pub fn intersection<A, B>(a: A, b: B) -> Intersection<A::IntoIter, B::IntoIter, Natural<A::Item>>
+where
+    A: IntoIterator,
+    B: IntoIterator<Item = A::Item>,
+    A::Item: Ord
+{
+    intersection_by(a, b, Ord::cmp)
+}
+
+/// Returns the intersection of two iterators sorted with respect to a
+/// comparator function, taking elements from `a`
+pub fn intersection_by<A, B, F>(a: A, b: B, compare: F) -> Intersection<A::IntoIter, B::IntoIter, F>
+where
+    A: IntoIterator,
+    B: IntoIterator<Item = A::Item>,
+    F: FnMut(&A::Item, &A::Item) -> Ordering
+{
+    Intersection(Fronts::new(a.into_iter(), b.into_iter(), compare))
+}
+
+/// Returns the elements of a sorted iterator `a` that aren't in `b`
+pub fn difference<A, B>(a: A, b: B) -> Difference<A::IntoIter, B::IntoIter, Natural<A::Item>>
+where
+    A: IntoIterator,
+    B: IntoIterator<Item = A::Item>,
+    A::Item: Ord
+{
+    difference_by(a, b, Ord::cmp)
+}
+
+/// Returns the elements of `a` that aren't in `b`, where both are sorted with
+/// respect to a comparator function
+pub fn difference_by<A, B, F>(a: A, b: B, compare: F) -> Difference<A::IntoIter, B::IntoIter, F>
+where
+    A: IntoIterator,
+    B: IntoIterator<Item = A::Item>,
+    F: FnMut(&A::Item, &A::Item) -> Ordering
+{
+    Difference(Fronts::new(a.into_iter(), b.into_iter(), compare))
+}
+
+impl<A, B, F> Iterator for Union<A, B, F>
+where
+    A: Iterator,
+    B: Iterator<Item = A::Item>,
+    F: FnMut(&A::Item, &A::Item) -> Ordering
+{
+    type Item = A::Item;
+
+    fn next(&mut self) -> Option<A::Item> {
+        let fronts = &mut self.0;
+        match fronts.cmp()? {
+            Ordering::Less => fronts.a.next(),
+            Ordering::Greater => fronts.b.next(),
+            Ordering::Equal => {
+                fronts.b.next();
+                fronts.a.next()
+            }
+        }
+    }
+}
+
+impl<A, B, F> Iterator for Intersection<A, B, F>
+where
+    A: Iterator,
+    B: Iterator<Item = A::Item>,
+    F: FnMut(&A::Item, &A::Item) -> Ordering
+{
+    type Item = A::Item;
+
+    fn next(&mut self) -> Option<A::Item> {
+        let fronts = &mut self.0;
+        loop {
+            // Nothing else can match once either iterator is empty
+            if fronts.a.peek().is_none() || fronts.b.peek().is_none() { return None }
+
+            match fronts.cmp()? {
+                Ordering::Less => { fronts.a.next(); }
+                Ordering::Greater => { fronts.b.next(); }
+                Ordering::Equal => {
+                    fronts.b.next();
+                    return fronts.a.next();
+                }
+            }
+        }
+    }
+}
+
+impl<A, B, F> Iterator for Difference<A, B, F>
+where
+    A: Iterator,
+    B: Iterator<Item = A::Item>,
+    F: FnMut(&A::Item, &A::Item) -> Ordering
+{
+    type Item = A::Item;
+
+    fn next(&mut self) -> Option<A::Item> {
+        let fronts = &mut self.0;
+        loop {
+            fronts.a.peek()?;
+
+            match fronts.cmp()? {
+                Ordering::Less => return fronts.a.next(),
+                Ordering::Greater => { fronts.b.next(); }
+                Ordering::Equal => {
+                    fronts.a.next();
+                    fronts.b.next();
+                }
+            }
+        }
+    }
+}
+
+/// The CLRS solution as a `Sorter`
+pub struct ClrsMergeSort;
+
+impl<T: Ord + Clone> Sorter<T> for ClrsMergeSort {
+    fn properties(&self) -> Properties {
+        Properties {
+            name: "Merge Sort",
+            stable: true,
+            in_place: false,
+            worst_case: Complexity::Linearithmic,
+            average_case: Complexity::Linearithmic,
+            space: Complexity::Linear,
+        }
+    }
+
+    fn sort(&self, values: &mut [T]) {
+        let len = values.len();
+        clrs_merge_sort(values, 0, len);
+    }
+
+    fn sort_instrumented(&self, values: &mut [T], instrument: &mut dyn Instrument) {
+        let len = values.len();
+        merge_sort_with(values, 0, len, &mut T::cmp, &mut Probe::new(instrument));
+    }
+}
+
+/// The CLRS solution with the sentinel merge as a `Sorter`
+pub struct ClrsMergeSortSentinel;
+
+impl<T: Ord + Clone> Sorter<T> for ClrsMergeSortSentinel {
+    fn properties(&self) -> Properties {
+        Properties {
+            name: "Merge Sort (sentinel)",
+            stable: true,
+            in_place: false,
+            worst_case: Complexity::Linearithmic,
+            average_case: Complexity::Linearithmic,
+            space: Complexity::Linear,
+        }
+    }
+
+    fn sort(&self, values: &mut [T]) {
+        let len = values.len();
+        clrs_merge_sort_sentinel(values, 0, len);
+    }
+
+    fn sort_instrumented(&self, values: &mut [T], instrument: &mut dyn Instrument) {
+        let len = values.len();
+        merge_sort_sentinel_with(values, 0, len, &mut Probe::new(instrument));
+    }
+}
+
+/// My own implementation as a `Sorter`
+pub struct KrwMergeSort;
+
+impl<T: Copy + PartialOrd> Sorter<T> for KrwMergeSort {
+    fn properties(&self) -> Properties {
+        Properties {
+            name: "Merge Sort (iterators)",
+            stable: true,
+            in_place: false,
+            worst_case: Complexity::Linearithmic,
+            average_case: Complexity::Linearithmic,
+            space: Complexity::Linear,
+        }
+    }
+
+    fn sort(&self, values: &mut [T]) {
+        krw_merge_sort(values);
+    }
+
+    fn sort_instrumented(&self, values: &mut [T], instrument: &mut dyn Instrument) {
+        krw_sort_with(values, &mut super::partial_order, &mut Probe::new(instrument));
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use sort::docs;
+    use sort::trace::Trace;
+
+    #[test]
+    fn test_example_matches_docs() {
+        let values = [3, 41, 52, 26, 38, 57, 9, 49];
+        let mut trace = Trace::new();
+        ClrsMergeSort.sort_instrumented(&mut values.clone(), &mut trace);
+
+        // The diagram in the docs was drawn by hand, so only the boxes, arrows
+        // and labels in each line are compared, not the spacing between them
+        let skeleton = |text: &str| -> String { text.chars().filter(|&c| c != ' ' && c != '-').collect() };
+        let diagram = trace.render_merges(&values);
+        assert!(skeleton(&docs(include_str!("merge.rs"))).contains(&skeleton(&diagram)), "\n{}", diagram);
+    }
 
     #[test]
     fn test_clrs_merge_sort() {
-        let mut values = [];
+        let mut values = [0u8; 0];
         clrs_merge_sort(&mut values, 0, 0);
         assert_eq!(values, []);
 
-        let mut values = [1.0];
+        let mut values = [1];
         clrs_merge_sort(&mut values, 0, 1);
-        assert_eq!(values, [1.0]);
+        assert_eq!(values, [1]);
 
-        let mut values = [31.0, 41.0, 59.0, 26.0, 41.0, 58.0];
+        let mut values = [31, 41, 59, 26, 41, 58];
         let len = values.len();
         clrs_merge_sort(&mut values, 0, len);
-        assert_eq!(values, [26.0, 31.0, 41.0, 41.0, 58.0, 59.0]);
+        assert_eq!(values, [26, 31, 41, 41, 58, 59]);
 
-        let mut values = [5.0, 2.0, 4.0, 6.0, 1.0, 3.0];
+        let mut values = [5, 2, 4, 6, 1, 3];
         let len = values.len();
         clrs_merge_sort(&mut values, 0, len);
-        assert_eq!(values, [1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+        assert_eq!(values, [1, 2, 3, 4, 5, 6]);
 
-        let mut values = [2.0, 4.0, 5.0, 7.0, 1.0, 2.0, 3.0, 6.0];
+        let mut values = ["merge", "sort", "by", "any", "ordered", "type"];
+        let len = values.len();
+        clrs_merge_sort(&mut values, 0, len);
+        assert_eq!(values, ["any", "by", "merge", "ordered", "sort", "type"]);
+
+        let mut values = [2, 4, 5, 7, 1, 2, 3, 6];
         clrs_merge(&mut values, 0, 4, 8);
-        assert_eq!(values, [1.0, 2.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0]);
+        assert_eq!(values, [1, 2, 2, 3, 4, 5, 6, 7]);
+
+        let mut values = [9, 1, 5, 3, 2, 9];
+        clrs_merge_sort(&mut values, 1, 4);
+        assert_eq!(values, [9, 1, 3, 5, 2, 9]);
+    }
+
+    #[test]
+    fn test_clrs_merge_sort_by() {
+        let mut values = [31.0, 41.0, 59.0, 26.0, 41.0, 58.0];
+        let len = values.len();
+        clrs_merge_sort_by(&mut values, 0, len, |a, b| a.partial_cmp(b).unwrap());
+        assert_eq!(values, [26.0, 31.0, 41.0, 41.0, 58.0, 59.0]);
+
+        let mut values = [5, 2, 4, 6, 1, 3];
+        let len = values.len();
+        clrs_merge_sort_by(&mut values, 0, len, |a, b| b.cmp(a));
+        assert_eq!(values, [6, 5, 4, 3, 2, 1]);
+    }
+
+    #[test]
+    fn test_clrs_merge_sort_by_key() {
+        let mut values = [(2, 'a'), (1, 'b'), (2, 'c'), (1, 'd'), (0, 'e')];
+        let len = values.len();
+        clrs_merge_sort_by_key(&mut values, 0, len, |&(k, _)| k);
+        assert_eq!(values, [(0, 'e'), (1, 'b'), (1, 'd'), (2, 'a'), (2, 'c')]);
+    }
+
+    #[test]
+    fn test_clrs_merge_sort_sentinel() {
+        let mut values = [0u8; 0];
+        clrs_merge_sort_sentinel(&mut values, 0, 0);
+        assert_eq!(values, []);
+
+        let mut values = [31, 41, 59, 26, 41, 58];
+        let len = values.len();
+        clrs_merge_sort_sentinel(&mut values, 0, len);
+        assert_eq!(values, [26, 31, 41, 41, 58, 59]);
+
+        let mut values = [u8::MAX, 0, u8::MAX, 1];
+        clrs_merge_sort_sentinel(&mut values, 0, 4);
+        assert_eq!(values, [0, 1, u8::MAX, u8::MAX]);
+
+        let mut values = [2, 4, 5, 7, 1, 2, 3, 6];
+        clrs_merge_sentinel(&mut values, 0, 4, 8);
+        assert_eq!(values, [1, 2, 2, 3, 4, 5, 6, 7]);
     }
 
     #[test]
@@ -425,4 +1321,133 @@ mod tests {
         krw_merge_sort(&mut values);
         assert_eq!(values, [1.0, 2.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0]);
     }
+
+    #[test]
+    fn test_krw_merge_sort_by() {
+        let mut values = [5, 2, 4, 6, 1, 3];
+        krw_merge_sort_by(&mut values, |a, b| b.cmp(a));
+        assert_eq!(values, [6, 5, 4, 3, 2, 1]);
+
+        let mut values = [(2, 'a'), (1, 'b'), (2, 'c'), (1, 'd'), (0, 'e')];
+        krw_merge_sort_by_key(&mut values, |&(k, _)| k);
+        assert_eq!(values, [(0, 'e'), (1, 'b'), (1, 'd'), (2, 'a'), (2, 'c')]);
+    }
+
+    #[test]
+    fn test_par_merge_sort() {
+        let mut values = [0u8; 0];
+        par_merge_sort(&mut values, 1);
+        assert_eq!(values, []);
+
+        let mut values = [31.0, 41.0, 59.0, 26.0, 41.0, 58.0];
+        par_merge_sort(&mut values, DEFAULT_GRAIN);
+        assert_eq!(values, [26.0, 31.0, 41.0, 41.0, 58.0, 59.0]);
+
+        // Small grains force the parallel sort and merge all the way down, and
+        // the thread counts don't depend on the machine running the test
+        let input: Vec<u32> = (0..2000).map(|i| (i * 7919) % 1009).collect();
+        let mut expected = input.clone();
+        krw_merge_sort(&mut expected);
+
+        for &grain in &[1, 2, 7, 64, 5000] {
+            for &threads in &[1, 2, 3, 8] {
+                let mut values = input.clone();
+                let mut buffer = values.clone();
+                par_sort_by(&mut values, &mut buffer, grain, threads, &u32::cmp);
+                assert_eq!(values, expected, "grain {}, threads {}", grain, threads);
+            }
+        }
+
+        let mut values = input.clone();
+        par_merge_sort(&mut values, 0);
+        assert_eq!(values, expected);
+    }
+
+    #[test]
+    fn test_par_merge_sort_is_stable() {
+        let input: Vec<(u32, usize)> = (0..2000).map(|i| ((i as u32 * 7919) % 13, i)).collect();
+        let mut expected = input.clone();
+        krw_merge_sort_by_key(&mut expected, |&(k, _)| k);
+
+        for &grain in &[1, 3, 100] {
+            let mut values = input.clone();
+            let mut buffer = values.clone();
+            par_sort_by(&mut values, &mut buffer, grain, 4, &|a: &(u32, usize), b: &(u32, usize)| a.0.cmp(&b.0));
+            assert_eq!(values, expected, "grain {}", grain);
+        }
+
+        let mut values = input.clone();
+        par_merge_sort_by_key(&mut values, 1, |&(k, _)| k);
+        assert_eq!(values, expected);
+
+        let mut values = [5, 2, 4, 6, 1, 3];
+        par_merge_sort_by(&mut values, 1, |a, b| b.cmp(a));
+        assert_eq!(values, [6, 5, 4, 3, 2, 1]);
+    }
+
+    #[test]
+    fn test_kmerge() {
+        let merged: Vec<u32> = kmerge(vec![vec![1, 4, 7], vec![], vec![2, 5, 8], vec![3, 6, 9, 10]]).collect();
+        assert_eq!(merged, [1, 2, 3, 4, 5, 6, 7, 8, 9, 10]);
+
+        let none: Vec<Vec<u32>> = Vec::new();
+        assert_eq!(kmerge(none).next(), None);
+
+        // Equal elements come out in the order of their iterators
+        let merged: Vec<(u32, char)> = kmerge_by(
+            vec![vec![(1, 'a'), (2, 'a')], vec![(1, 'b'), (2, 'b')], vec![(1, 'c')]],
+            |a, b| a.0.cmp(&b.0),
+        ).collect();
+        assert_eq!(merged, [(1, 'a'), (1, 'b'), (1, 'c'), (2, 'a'), (2, 'b')]);
+
+        let merged = kmerge(vec![0..3, 1..2]);
+        assert_eq!(merged.size_hint(), (4, Some(4)));
+        assert_eq!(merged.collect::<Vec<_>>(), [0, 1, 1, 2]);
+
+        let merged: Vec<u32> = kmerge_by(vec![vec![9, 5, 1], vec![8, 4]], |a, b| b.cmp(a)).collect();
+        assert_eq!(merged, [9, 8, 5, 4, 1]);
+    }
+
+    #[test]
+    fn test_merge_iters() {
+        let merged: Vec<u32> = merge_iters(vec![1, 3, 5], vec![2, 3, 4, 6]).collect();
+        assert_eq!(merged, [1, 2, 3, 3, 4, 5, 6]);
+
+        let merged: Vec<(u32, char)> = merge_iters_by(vec![(1, 'a')], vec![(0, 'b'), (1, 'b')], |a, b| a.0.cmp(&b.0)).collect();
+        assert_eq!(merged, [(0, 'b'), (1, 'a'), (1, 'b')]);
+        assert_eq!(merge_iters(0..2, 5..9).size_hint(), (6, Some(6)));
+    }
+
+    #[test]
+    fn test_dedup() {
+        let deduped: Vec<u32> = dedup(vec![1, 1, 2, 3, 3, 3, 4]).collect();
+        assert_eq!(deduped, [1, 2, 3, 4]);
+
+        let deduped: Vec<(u32, char)> = dedup_by(vec![(1, 'a'), (1, 'b'), (2, 'c')], |a, b| a.0.cmp(&b.0)).collect();
+        assert_eq!(deduped, [(1, 'a'), (2, 'c')]);
+    }
+
+    #[test]
+    fn test_set_operations() {
+        let (a, b) = (vec![1, 2, 2, 4, 6], vec![2, 3, 4, 5]);
+        assert_eq!(union(a.clone(), b.clone()).collect::<Vec<_>>(), [1, 2, 2, 3, 4, 5, 6]);
+        assert_eq!(intersection(a.clone(), b.clone()).collect::<Vec<_>>(), [2, 4]);
+        assert_eq!(difference(a.clone(), b.clone()).collect::<Vec<_>>(), [1, 2, 6]);
+        assert_eq!(difference(b.clone(), a.clone()).collect::<Vec<_>>(), [3, 5]);
+
+        assert_eq!(union(Vec::new(), b.clone()).collect::<Vec<_>>(), b);
+        assert_eq!(intersection(a.clone(), Vec::new()).count(), 0);
+        assert_eq!(difference(a.clone(), Vec::new()).collect::<Vec<_>>(), a);
+
+        // Intersection and difference stop without reading the rest of an
+        // endless iterator
+        assert_eq!(intersection(vec![1, 3], 0..).collect::<Vec<_>>(), [1, 3]);
+        assert_eq!(difference(0..4, 2..).collect::<Vec<_>>(), [0, 1]);
+
+        let by_key = |a: &(u32, char), b: &(u32, char)| a.0.cmp(&b.0);
+        let (a, b) = (vec![(1, 'a'), (2, 'a')], vec![(2, 'b'), (3, 'b')]);
+        assert_eq!(union_by(a.clone(), b.clone(), by_key).collect::<Vec<_>>(), [(1, 'a'), (2, 'a'), (3, 'b')]);
+        assert_eq!(intersection_by(a.clone(), b.clone(), by_key).collect::<Vec<_>>(), [(2, 'a')]);
+        assert_eq!(difference_by(a, b, by_key).collect::<Vec<_>>(), [(1, 'a')]);
+    }
 }
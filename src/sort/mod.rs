@@ -1,11 +1,309 @@
 //! Sorting functions
 //!
-//! | Method | Time Complexity | Space Complexity |
-//! | --- | --- | --- |
-//! | Insertion Sort | _O(n²)_ | _O(1)_ |
-//! | Merge Sort | _O(n log(n))_ | _O(n)_ |
-//! | Selection Sort | _O(n²)_ | _O(1)_ |
+//! | Method | Worst Case | Average Case | Space | Stable | In Place |
+//! | --- | --- | --- | --- | --- | --- |
+//! | Heapsort | _O(n log(n))_ | _O(n log(n))_ | _O(1)_ | No | Yes |
+//! | Timsort | _O(n log(n))_ | _O(n log(n))_ | _O(n)_ | Yes | No |
+//! | Insertion Sort | _O(n²)_ | _O(n²)_ | _O(1)_ | Yes | Yes |
+//! | Insertion Sort (binary search) | _O(n²)_ | _O(n²)_ | _O(1)_ | Yes | Yes |
+//! | Insertion Sort (swapping) | _O(n²)_ | _O(n²)_ | _O(1)_ | No | Yes |
+//! | Merge Sort | _O(n log(n))_ | _O(n log(n))_ | _O(n)_ | Yes | No |
+//! | Merge Sort (sentinel) | _O(n log(n))_ | _O(n log(n))_ | _O(n)_ | Yes | No |
+//! | Merge Sort (iterators) | _O(n log(n))_ | _O(n log(n))_ | _O(n)_ | Yes | No |
+//! | Quicksort (Lomuto) | _O(n²)_ | _O(n log(n))_ | _O(lg(n))_ | No | Yes |
+//! | Quicksort (Hoare) | _O(n²)_ | _O(n log(n))_ | _O(lg(n))_ | No | Yes |
+//! | Quicksort (randomized) | _O(n²)_ | _O(n log(n))_ | _O(lg(n))_ | No | Yes |
+//! | Quicksort (median of three) | _O(n²)_ | _O(n log(n))_ | _O(lg(n))_ | No | Yes |
+//! | Quicksort (three-way) | _O(n²)_ | _O(n log(n))_ | _O(lg(n))_ | No | Yes |
+//! | Selection Sort | _O(n²)_ | _O(n²)_ | _O(1)_ | No | Yes |
+//! | Counting Sort | _O(n + k)_ | _O(n)_ | _O(n + k)_ | Yes | No |
+//! | Radix Sort (LSD) | _O(n)_ | _O(n)_ | _O(n)_ | Yes | No |
+//! | Radix Sort (LSD, byte strings) | _O(n)_ | _O(n)_ | _O(n)_ | Yes | No |
+//! | Radix Sort (MSD, strings) | _O(n)_ | _O(n)_ | _O(n)_ | Yes | No |
+//! | Bucket Sort | _O(n²)_ | _O(n)_ | _O(n)_ | Yes | No |
+//!
+//! The table is rendered from the `Sorter` implementations, and a test checks
+//! that it is kept up to date. Another counts the operations of each sort on
+//! uniform input of increasing size, and checks that `complexity::fit` agrees
+//! with the average case.
+//!
+//! Counting, radix and bucket sort don't compare elements with each other, so
+//! they aren't bound by the _Ω(n lg(n))_ lower bound on comparison sorts. In
+//! exchange, they only work for particular types of keys: small unsigned
+//! integers, strings of digits, or values spread uniformly over a range. Their
+//! linear running times assume the number of possible digits and the length
+//! of each key are constant.
+//!
+//! Each `Sorter` can also report the comparisons, swaps, writes and
+//! allocations it makes to an `instrument::Instrument`, such as
+//! `instrument::Metrics`, or `trace::Trace`, which records them in order and
+//! draws them as diagrams.
 
+pub mod bucket;
+pub mod counting;
+pub mod external;
+pub mod heap;
+pub mod hybrid;
 pub mod insertion;
+pub mod instrument;
 pub mod merge;
+pub mod network;
+pub mod partial;
+pub mod quick;
+pub mod radix;
 pub mod selection;
+pub mod trace;
+
+use std::cmp::Ordering;
+
+use num::{PrimInt, Unsigned};
+
+pub use complexity::Complexity;
+
+use self::instrument::Instrument;
+
+/// Describes the behaviour of a sorting algorithm
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Properties {
+    pub name: &'static str,
+    /// Whether equal elements keep their original relative order
+    pub stable: bool,
+    /// Whether elements are rearranged within the slice, rather than copied
+    /// out to auxiliary storage and back
+    pub in_place: bool,
+    pub worst_case: Complexity,
+    pub average_case: Complexity,
+    /// Auxiliary space used during the sort
+    pub space: Complexity,
+}
+
+/// A sorting algorithm with a uniform interface
+///
+/// Every algorithm in this module implements `Sorter`, so they can be used
+/// interchangeably regardless of the signatures of their free functions.
+pub trait Sorter<T> {
+    /// Describes the algorithm
+    fn properties(&self) -> Properties;
+
+    /// Sorts a slice in nondecreasing order
+    fn sort(&self, values: &mut [T]);
+
+    /// Sorts a slice in nondecreasing order, reporting each operation to an
+    /// `Instrument`
+    fn sort_instrumented(&self, values: &mut [T], instrument: &mut dyn Instrument);
+
+    fn name(&self) -> &'static str {
+        self.properties().name
+    }
+
+    fn is_stable(&self) -> bool {
+        self.properties().stable
+    }
+
+    fn is_in_place(&self) -> bool {
+        self.properties().in_place
+    }
+
+    fn worst_case(&self) -> Complexity {
+        self.properties().worst_case
+    }
+
+    fn average_case(&self) -> Complexity {
+        self.properties().average_case
+    }
+}
+
+/// Lists every sorting algorithm in this module
+pub fn all_sorters<T>() -> Vec<Box<dyn Sorter<T>>>
+where
+    T: Copy + Ord
+{
+    vec![
+        Box::new(heap::Heapsort),
+        Box::new(hybrid::Timsort),
+        Box::new(insertion::Clrs),
+        Box::new(insertion::ClrsBsearch),
+        Box::new(insertion::Shepmaster),
+        Box::new(merge::ClrsMergeSort),
+        Box::new(merge::ClrsMergeSortSentinel),
+        Box::new(merge::KrwMergeSort),
+        Box::new(quick::Lomuto),
+        Box::new(quick::Hoare),
+        Box::new(quick::Randomized),
+        Box::new(quick::MedianOfThree),
+        Box::new(quick::ThreeWay),
+        Box::new(selection::Selection),
+    ]
+}
+
+/// Lists every sorting algorithm in this module that sorts unsigned integers
+/// without comparing them
+pub fn unsigned_sorters<T>() -> Vec<Box<dyn Sorter<T>>>
+where
+    T: PrimInt + Unsigned
+{
+    vec![
+        Box::new(counting::CountingSort),
+        Box::new(radix::Lsd),
+    ]
+}
+
+/// Renders the properties of every algorithm as a Markdown table
+pub fn complexity_table() -> String {
+    let mut table = String::from("| Method | Worst Case | Average Case | Space | Stable | In Place |\n");
+    table.push_str("| --- | --- | --- | --- | --- | --- |\n");
+
+    let mut all_properties: Vec<Properties> = all_sorters::<u8>()
+        .iter()
+        .chain(&unsigned_sorters::<u8>())
+        .map(|sorter| sorter.properties())
+        .collect();
+    all_properties.push(Sorter::<&[u8]>::properties(&radix::LsdBytes));
+    all_properties.push(Sorter::<&str>::properties(&radix::Msd));
+    all_properties.push(Sorter::<f64>::properties(&bucket::BucketSort));
+
+    let yes_no = |flag| if flag { "Yes" } else { "No" };
+    for properties in all_properties {
+        table.push_str(&format!(
+            "| {} | _{}_ | _{}_ | _{}_ | {} | {} |\n",
+            properties.name,
+            properties.worst_case,
+            properties.average_case,
+            properties.space,
+            yes_no(properties.stable),
+            yes_no(properties.in_place),
+        ));
+    }
+
+    table
+}
+
+/// Compares two partially ordered values, treating incomparable values as equal
+///
+/// Used by the `PartialOrd` sorts to delegate to their `_by` forms.
+fn partial_order<T>(a: &T, b: &T) -> Ordering
+where
+    T: PartialOrd
+{
+    a.partial_cmp(b).unwrap_or(Ordering::Equal)
+}
+
+/// Returns the module docs at the top of a source file, without the comment
+/// markers
+#[cfg(test)]
+fn docs(source: &str) -> String {
+    source
+        .lines()
+        .take_while(|line| line.starts_with("//!"))
+        .map(|line| {
+            let line = line.trim_start_matches("//!");
+            format!("{}\n", line.strip_prefix(' ').unwrap_or(line))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_all_sorters() {
+        for sorter in all_sorters() {
+            let mut values = [31, 41, 59, 26, 41, 58];
+            sorter.sort(&mut values);
+            assert_eq!(values, [26, 31, 41, 41, 58, 59], "{}", sorter.name());
+
+            let mut empty = [0; 0];
+            sorter.sort(&mut empty);
+            assert_eq!(empty, [], "{}", sorter.name());
+        }
+    }
+
+    #[test]
+    fn test_all_sorters_instrumented() {
+        use self::instrument::Slot;
+
+        /// Checks that every reported index is within the slice
+        struct Bounds(usize);
+
+        impl Instrument for Bounds {
+            fn compare(&mut self, a: Slot, b: Slot) {
+                self.check(a);
+                self.check(b);
+            }
+
+            fn swap(&mut self, i: usize, j: usize) {
+                self.check(Slot::Values(i));
+                self.check(Slot::Values(j));
+            }
+
+            fn write(&mut self, to: Slot, from: Slot) {
+                self.check(to);
+                self.check(from);
+            }
+        }
+
+        impl Bounds {
+            fn check(&self, slot: Slot) {
+                if let Slot::Values(i) = slot {
+                    assert!(i < self.0, "{} is out of bounds", i);
+                }
+            }
+        }
+
+        let input: Vec<u16> = (0..200u32).map(|i| ((i * 7919) % 211) as u16).collect();
+        let mut expected = input.clone();
+        expected.sort();
+
+        for sorter in all_sorters().into_iter().chain(unsigned_sorters()) {
+            let mut values = input.clone();
+            sorter.sort_instrumented(&mut values, &mut Bounds(input.len()));
+            assert_eq!(values, expected, "{}", sorter.name());
+        }
+    }
+
+    #[test]
+    fn test_unsigned_sorters() {
+        for sorter in unsigned_sorters() {
+            let mut values = [31u16, 41, 59, 26, 41, 58, 1000];
+            sorter.sort(&mut values);
+            assert_eq!(values, [26, 31, 41, 41, 58, 59, 1000], "{}", sorter.name());
+        }
+    }
+
+    #[test]
+    fn test_average_case_complexity() {
+        use complexity;
+        use gen;
+        use self::instrument::Metrics;
+
+        let fit = |sorter: &dyn Sorter<u32>, key_range: u32| {
+            let sizes = complexity::geometric(64, 2, 6);
+            complexity::fit(&complexity::measure(&sizes, |n| {
+                let mut values: Vec<u32> = gen::uniform(n, n as u64);
+                for value in &mut values { *value %= key_range; }
+
+                let mut metrics = Metrics::default();
+                sorter.sort_instrumented(&mut values, &mut metrics);
+                (metrics.comparisons + metrics.swaps + metrics.writes) as f64
+            }))
+        };
+
+        for sorter in all_sorters() {
+            let fit = fit(&*sorter, u32::MAX);
+            assert_eq!(fit.complexity, sorter.average_case(), "{}: {:?}", sorter.name(), fit);
+        }
+
+        // Linear time for counting and radix sort assumes the range of keys
+        // doesn't grow with n
+        for sorter in unsigned_sorters() {
+            let fit = fit(&*sorter, 256);
+            assert_eq!(fit.complexity, sorter.average_case(), "{}: {:?}", sorter.name(), fit);
+        }
+    }
+
+    #[test]
+    fn test_complexity_table_matches_docs() {
+        assert!(docs(include_str!("mod.rs")).contains(&complexity_table()), "\n{}", complexity_table());
+    }
+}
@@ -0,0 +1,323 @@
+//! Sorting networks
+//!
+//! A sorting network is a fixed sequence of *comparators* for inputs of a
+//! fixed length. Each comparator `(i, j)`, with `i < j`, compares the elements
+//! at those positions and swaps them if they are out of order. The sequence
+//! doesn't depend on the input, so a network can run without any branches,
+//! which suits small arrays sorted in hot loops.
+//!
+//! Comparators that touch different positions are independent, so they are
+//! grouped into *layers* that could run in parallel. The number of layers is
+//! the *depth* of the network.
+//!
+//! Both of the networks here are due to Batcher, and have
+//! _O(n lg²(n))_ comparators and a depth of _O(lg²(n))_. They are built for
+//! the next power of two, then any comparator that touches a position past
+//! the end is dropped. That is the same as padding the input with elements
+//! larger than any other, which never move.
+//!
+//! *Odd-even merge sort*: sorts each half, then merges them by recursively
+//! merging the elements at even positions and those at odd positions, and
+//! finishing with one layer of comparators between neighbours.
+//!
+//! *Bitonic sort*: sorts each half, then merges them with *half-cleaners*. The
+//! first layer compares each element of a block with its mirror image in the
+//! other half, and each later layer compares elements half as far apart.
+//!
+//! # The 0-1 principle
+//!
+//! If a network sorts every input made of zeros and ones, it sorts every
+//! input. So a network on _n_ elements can be verified by checking all _2ⁿ_
+//! binary inputs, rather than all _n!_ permutations.
+//!
+//! ```
+//! use kb::sort::network::Network;
+//!
+//! let network = Network::odd_even_merge(4);
+//! assert_eq!(network.comparators(), [(0, 1), (2, 3), (0, 2), (1, 3), (1, 2)]);
+//! assert_eq!(network.depth(), 3);
+//! assert!(network.verify().is_ok());
+//!
+//! let mut values = [41, 26, 59, 31];
+//! network.sort_array(&mut values);
+//! assert_eq!(values, [26, 31, 41, 59]);
+//! ```
+
+use std::cmp::{self, Ordering};
+
+/// A sequence of comparators for inputs of a fixed length
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Network {
+    len: usize,
+    comparators: Vec<(usize, usize)>,
+}
+
+impl Network {
+    /// Creates a network from a list of comparators
+    ///
+    /// # Panics
+    ///
+    /// Panics if a comparator `(i, j)` doesn't have `i < j < len`.
+    pub fn new(len: usize, comparators: Vec<(usize, usize)>) -> Self {
+        for &(i, j) in &comparators {
+            assert!(i < j && j < len, "({}, {}) is not a comparator for length {}", i, j, len);
+        }
+
+        Network { len, comparators }
+    }
+
+    /// Generates Batcher's odd-even merge sort for `len` elements
+    pub fn odd_even_merge(len: usize) -> Self {
+        let n = len.next_power_of_two();
+        let mut comparators = Vec::new();
+
+        let mut p = 1;
+        while p < n {
+            let mut k = p;
+            while k >= 1 {
+                let mut j = k % p;
+                while j + k < n {
+                    for i in 0..cmp::min(k, n - j - k) {
+                        // Only compare elements within the same pair of merged blocks
+                        if (i + j) / (2 * p) == (i + j + k) / (2 * p) {
+                            comparators.push((i + j, i + j + k));
+                        }
+                    }
+                    j += 2 * k;
+                }
+                k /= 2;
+            }
+            p *= 2;
+        }
+
+        Network::pruned(len, comparators)
+    }
+
+    /// Generates Batcher's bitonic sort for `len` elements
+    ///
+    /// Every comparator puts the smaller element first. The usual form of the
+    /// network sorts alternate blocks in descending order, so that each pair
+    /// forms a bitonic sequence. Instead, each merge starts by comparing
+    /// mirror images, which has the same effect.
+    pub fn bitonic(len: usize) -> Self {
+        let n = len.next_power_of_two();
+        let mut comparators = Vec::new();
+
+        let mut k = 2;
+        while k <= n {
+            let mut distance = k - 1;
+            while distance > 0 {
+                for i in 0..n {
+                    let j = i ^ distance;
+                    if j > i { comparators.push((i, j)); }
+                }
+                distance = if distance == k - 1 { k / 4 } else { distance / 2 };
+            }
+            k *= 2;
+        }
+
+        Network::pruned(len, comparators)
+    }
+
+    /// Drops the comparators of a power of two network that touch positions
+    /// past `len`
+    fn pruned(len: usize, mut comparators: Vec<(usize, usize)>) -> Self {
+        comparators.retain(|&(_, j)| j < len);
+        Network { len, comparators }
+    }
+
+    /// The length of input the network sorts
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The comparators, in the order they are applied
+    pub fn comparators(&self) -> &[(usize, usize)] {
+        &self.comparators
+    }
+
+    /// Groups the comparators into layers, each of which touches every
+    /// position at most once
+    ///
+    /// Each comparator goes in the layer after the last one to touch either of
+    /// its positions.
+    pub fn layers(&self) -> Vec<Vec<(usize, usize)>> {
+        let mut next = vec![0; self.len];
+        let mut layers: Vec<Vec<(usize, usize)>> = Vec::new();
+        for &(i, j) in &self.comparators {
+            let layer = cmp::max(next[i], next[j]);
+            if layer == layers.len() { layers.push(Vec::new()); }
+
+            layers[layer].push((i, j));
+            next[i] = layer + 1;
+            next[j] = layer + 1;
+        }
+
+        layers
+    }
+
+    /// The number of layers
+    pub fn depth(&self) -> usize {
+        self.layers().len()
+    }
+
+    /// Sorts a slice by applying each comparator
+    ///
+    /// # Panics
+    ///
+    /// Panics if the slice isn't the length of the network.
+    pub fn apply<T>(&self, values: &mut [T])
+    where
+        T: Ord
+    {
+        self.apply_by(values, T::cmp);
+    }
+
+    /// Sorts a slice with respect to a comparator function
+    pub fn apply_by<T, F>(&self, values: &mut [T], mut compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering
+    {
+        assert_eq!(values.len(), self.len, "network sorts {} elements", self.len);
+
+        for &(i, j) in &self.comparators {
+            if compare(&values[j], &values[i]) == Ordering::Less {
+                values.swap(i, j);
+            }
+        }
+    }
+
+    /// Sorts an array without branching on the elements
+    ///
+    /// Each comparator writes the minimum and maximum of its pair back, rather
+    /// than swapping them if they are out of order, which the compiler can
+    /// turn into conditional moves.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the array isn't the length of the network.
+    pub fn sort_array<T, const N: usize>(&self, values: &mut [T; N])
+    where
+        T: Copy + Ord
+    {
+        assert_eq!(N, self.len, "network sorts {} elements", self.len);
+
+        for &(i, j) in &self.comparators {
+            let (a, b) = (values[i], values[j]);
+            values[i] = cmp::min(a, b);
+            values[j] = cmp::max(a, b);
+        }
+    }
+
+    /// Checks that the network sorts every input, by the 0-1 principle
+    ///
+    /// Each binary input is a bitmask, with bit `i` holding position `i`. On
+    /// failure, returns the first binary input that isn't sorted.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the network is longer than 32 elements, as checking every
+    /// input would take too long.
+    pub fn verify(&self) -> Result<(), Vec<u8>> {
+        assert!(self.len <= 32, "can't verify a network of {} elements", self.len);
+
+        let all = (1u64 << self.len) - 1;
+        for input in 0..=all {
+            let mut bits = input;
+            for &(i, j) in &self.comparators {
+                // A one before a zero is the only pair out of order
+                if bits >> i & 1 == 1 && bits >> j & 1 == 0 {
+                    bits ^= 1 << i | 1 << j;
+                }
+            }
+
+            let zeros = self.len as u32 - input.count_ones();
+            if bits != all >> zeros << zeros {
+                return Err((0..self.len).map(|i| (input >> i & 1) as u8).collect());
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_odd_even_merge() {
+        // Sizes and depths of the power of two networks, from Knuth
+        let network = Network::odd_even_merge(8);
+        assert_eq!(network.comparators().len(), 19);
+        assert_eq!(network.depth(), 6);
+        assert_eq!(Network::odd_even_merge(16).comparators().len(), 63);
+
+        for len in 0..=16 {
+            assert_eq!(Network::odd_even_merge(len).verify(), Ok(()), "len {}", len);
+        }
+    }
+
+    #[test]
+    fn test_bitonic() {
+        let network = Network::bitonic(8);
+        assert_eq!(network.comparators().len(), 24);
+        assert_eq!(network.depth(), 6);
+        assert_eq!(Network::bitonic(16).comparators().len(), 80);
+
+        for len in 0..=16 {
+            assert_eq!(Network::bitonic(len).verify(), Ok(()), "len {}", len);
+        }
+    }
+
+    #[test]
+    fn test_verify() {
+        // The last comparator of a four element network is needed
+        let network = Network::new(4, vec![(0, 1), (2, 3), (0, 2), (1, 3)]);
+        assert_eq!(network.verify(), Err(vec![1, 0, 1, 0]));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_new_invalid() {
+        Network::new(4, vec![(2, 1)]);
+    }
+
+    #[test]
+    fn test_layers() {
+        let network = Network::bitonic(4);
+        assert_eq!(network.layers(), [vec![(0, 1), (2, 3)], vec![(0, 3), (1, 2)], vec![(0, 1), (2, 3)]]);
+        assert_eq!(Network::odd_even_merge(1).depth(), 0);
+    }
+
+    #[test]
+    fn test_apply() {
+        let input = [31, 41, 59, 26, 41, 58, 97, 93, 23, 84];
+        let mut expected = input;
+        expected.sort();
+
+        for network in &[Network::odd_even_merge(10), Network::bitonic(10)] {
+            let mut values = input;
+            network.apply(&mut values);
+            assert_eq!(values, expected);
+
+            let mut values = input;
+            network.sort_array(&mut values);
+            assert_eq!(values, expected);
+
+            let mut values = input;
+            network.apply_by(&mut values, |a, b| b.cmp(a));
+            assert_eq!(values.iter().rev().collect::<Vec<_>>(), expected.iter().collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_apply_wrong_length() {
+        Network::bitonic(4).apply(&mut [1, 2, 3]);
+    }
+}
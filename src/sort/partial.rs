@@ -0,0 +1,259 @@
+//! Partial and incremental sorting
+//!
+//! Often only the first few elements are needed in order, or a collection has
+//! to stay sorted as elements arrive. Sorting everything each time does more
+//! work than necessary.
+//!
+//! `partial_sort` puts the smallest _k_ elements in order at the front of a
+//! slice. It selects the _k_-th element with quickselect, which leaves the
+//! smallest _k_ before it, then sorts just those with heapsort, taking
+//! _O(n + k lg(k))_ time.
+//!
+//! `sorted_iter` yields elements in order without knowing _k_ up front, using
+//! *incremental quicksort*. A stack holds the positions of pivots that have
+//! been partitioned into place. To produce the next element, the slice after
+//! the top pivot is partitioned repeatedly, pushing each new pivot, until the
+//! next element is a pivot itself. Each partition only touches elements that
+//! haven't been yielded yet, so taking the first _k_ elements takes
+//! _O(n + k lg(k))_ expected time, and all of them takes _O(n lg(n))_.
+//!
+//! `insert_sorted` finds where an element belongs with a binary search and
+//! shifts the rest along to make room, as the binary search insertion sort
+//! does for each element it inserts.
+//!
+//! ```
+//! use kb::sort::partial;
+//!
+//! let mut values = [31, 41, 59, 26, 41, 58];
+//! partial::partial_sort(&mut values, 3);
+//! assert_eq!(values[..3], [26, 31, 41]);
+//!
+//! let first: Vec<u32> = partial::sorted_iter(vec![31, 41, 59, 26, 41, 58]).take(2).collect();
+//! assert_eq!(first, [26, 31]);
+//!
+//! let mut sorted = vec![26, 41, 58];
+//! assert_eq!(partial::insert_sorted(&mut sorted, 31), 1);
+//! assert_eq!(sorted, [26, 31, 41, 58]);
+//! ```
+
+use std::cmp::Ordering;
+
+use rand::{self, Rng, ThreadRng};
+
+use search::binary;
+use super::heap::heapsort_by;
+use super::merge::Natural;
+use super::selection;
+
+/// Sorts the smallest `k` elements into `values[..k]`, leaving the rest in an
+/// unspecified order
+///
+/// If `k` is at least the length of the slice, the whole slice is sorted.
+pub fn partial_sort<T>(values: &mut [T], k: usize)
+where
+    T: Ord
+{
+    partial_sort_by(values, k, T::cmp);
+}
+
+/// Sorts the smallest `k` elements with respect to a comparator function
+pub fn partial_sort_by<T, F>(values: &mut [T], k: usize, mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering
+{
+    let k = k.min(values.len());
+    if k == 0 { return }
+
+    if k < values.len() {
+        selection::select_nth_by(values, k - 1, &mut compare);
+    }
+
+    heapsort_by(&mut values[..k], compare);
+}
+
+/// Sorts the smallest `k` elements by a key extraction function
+pub fn partial_sort_by_key<T, K, F>(values: &mut [T], k: usize, mut key: F)
+where
+    K: Ord,
+    F: FnMut(&T) -> K
+{
+    partial_sort_by(values, k, |a, b| key(a).cmp(&key(b)));
+}
+
+/// Lazily yields elements in sorted order with incremental quicksort
+///
+/// The elements are kept in reverse, so the next one is always at the end of
+/// the vector and can be popped off. Pivots are chosen at random, and equal
+/// elements may be yielded in any order.
+pub struct SortedIter<T, F> {
+    values: Vec<T>,
+    /// Positions of the pivots in their final places, increasing from the
+    /// bottom of the stack
+    pivots: Vec<usize>,
+    compare: F,
+    rng: ThreadRng,
+}
+
+/// Yields elements in sorted order, sorting only as much as is consumed
+pub fn sorted_iter<I>(iter: I) -> SortedIter<I::Item, Natural<I::Item>>
+where
+    I: IntoIterator,
+    I::Item: Ord
+{
+    sorted_iter_by(iter, Ord::cmp)
+}
+
+/// Yields elements in sorted order with respect to a comparator function
+pub fn sorted_iter_by<I, F>(iter: I, compare: F) -> SortedIter<I::Item, F>
+where
+    I: IntoIterator,
+    F: FnMut(&I::Item, &I::Item) -> Ordering
+{
+    SortedIter {
+        values: iter.into_iter().collect(),
+        pivots: Vec::new(),
+        compare,
+        rng: rand::thread_rng(),
+    }
+}
+
+impl<T, F> Iterator for SortedIter<T, F>
+where
+    F: FnMut(&T, &T) -> Ordering
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        let len = self.values.len();
+        loop {
+            let start = self.pivots.last().map_or(0, |&pivot| pivot + 1);
+            if start == len {
+                self.pivots.pop();
+                break;
+            }
+
+            // A single element after the last pivot is the smallest
+            if start + 1 >= len { break }
+
+            let compare = &mut self.compare;
+            let pivot = self.rng.gen_range(0, len - start);
+            let (lt, gt) = selection::partition(&mut self.values[start..], pivot, &mut |a, b| compare(b, a));
+
+            // Every element equal to the pivot is in its final place
+            self.pivots.extend(start + lt..start + gt);
+        }
+
+        self.values.pop()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.values.len(), Some(self.values.len()))
+    }
+}
+
+impl<T, F> ExactSizeIterator for SortedIter<T, F>
+where
+    F: FnMut(&T, &T) -> Ordering
+{
+}
+
+/// Inserts an element into a sorted vector, after any elements equal to it,
+/// and returns its index
+pub fn insert_sorted<T>(values: &mut Vec<T>, value: T) -> usize
+where
+    T: Ord
+{
+    insert_sorted_by(values, value, T::cmp)
+}
+
+/// Inserts an element into a vector sorted with respect to a comparator
+/// function
+///
+/// The index is the upper bound of the element, from `binary::upper_bound_by`,
+/// rather than `binary::search_closest`, which may land on any of several
+/// equal elements and so wouldn't keep insertions in the order they were made.
+pub fn insert_sorted_by<T, F>(values: &mut Vec<T>, value: T, mut compare: F) -> usize
+where
+    F: FnMut(&T, &T) -> Ordering
+{
+    let i = binary::upper_bound_by(values, |element| compare(element, &value));
+    values.insert(i, value);
+    i
+}
+
+/// Inserts an element into a vector sorted by a key extraction function
+pub fn insert_sorted_by_key<T, K, F>(values: &mut Vec<T>, value: T, mut key: F) -> usize
+where
+    K: Ord,
+    F: FnMut(&T) -> K
+{
+    insert_sorted_by(values, value, |a, b| key(a).cmp(&key(b)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partial_sort() {
+        let input: Vec<u32> = (0..200u32).map(|i| (i * 7919) % 61).collect();
+        let mut sorted = input.clone();
+        sorted.sort();
+
+        for &k in &[0, 1, 2, 10, 100, 199, 200, 500] {
+            let mut values = input.clone();
+            partial_sort(&mut values, k);
+
+            let k = k.min(values.len());
+            assert_eq!(values[..k], sorted[..k], "k = {}", k);
+            assert!(values[k..].iter().all(|v| k == 0 || *v >= values[k - 1]));
+        }
+
+        let mut values = [(3, 'a'), (1, 'b'), (2, 'c')];
+        partial_sort_by_key(&mut values, 2, |&(k, _)| k);
+        assert_eq!(values[..2], [(1, 'b'), (2, 'c')]);
+
+        partial_sort_by(&mut values, 1, |a, b| b.cmp(a));
+        assert_eq!(values[0], (3, 'a'));
+    }
+
+    #[test]
+    fn test_sorted_iter() {
+        let input: Vec<u32> = (0..500u32).map(|i| (i * 7919) % 97).collect();
+        let mut sorted = input.clone();
+        sorted.sort();
+
+        assert_eq!(sorted_iter(input.clone()).collect::<Vec<_>>(), sorted);
+        assert_eq!(sorted_iter(input.clone()).take(5).collect::<Vec<_>>(), sorted[..5]);
+        assert_eq!(sorted_iter(vec![7; 50]).collect::<Vec<_>>(), [7; 50]);
+        assert_eq!(sorted_iter(Vec::<u32>::new()).next(), None);
+
+        let mut iter = sorted_iter(input.clone());
+        assert_eq!(iter.len(), 500);
+        iter.next();
+        assert_eq!(iter.len(), 499);
+
+        let descending: Vec<u32> = sorted_iter_by(input, |a, b| b.cmp(a)).take(3).collect();
+        assert_eq!(descending, [96, 96, 96]);
+    }
+
+    #[test]
+    fn test_insert_sorted() {
+        let mut values = Vec::new();
+        for value in [31, 41, 59, 26, 41, 58] {
+            insert_sorted(&mut values, value);
+        }
+        assert_eq!(values, [26, 31, 41, 41, 58, 59]);
+        assert_eq!(insert_sorted(&mut values, 100), 6);
+        assert_eq!(insert_sorted(&mut values, 0), 0);
+
+        // Equal elements are inserted after those already there
+        let mut values = vec![(1, 'a'), (2, 'a')];
+        assert_eq!(insert_sorted_by_key(&mut values, (1, 'b'), |&(k, _)| k), 1);
+        assert_eq!(values, [(1, 'a'), (1, 'b'), (2, 'a')]);
+
+        let mut values = vec![3, 2, 1];
+        assert_eq!(insert_sorted_by(&mut values, 2, |a, b| b.cmp(a)), 2);
+        assert_eq!(values, [3, 2, 2, 1]);
+    }
+}
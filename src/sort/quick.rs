@@ -0,0 +1,605 @@
+//! Quicksort
+//!
+//! Sorts elements in _O(n lg(n))_ time on average, using a
+//! *divide-and-conquer* approach, but in _O(n²)_ time in the worst case.
+//!
+//! *Divide*: Partition the slice into two (possibly empty) subslices around a
+//! pivot element, such that every element of the lower subslice is less than
+//! or equal to the pivot, which is in turn less than or equal to every element
+//! of the upper subslice.
+//!
+//! *Conquer*: Sort the two subslices by recursive calls to quicksort.
+//!
+//! *Combine*: Nothing to do. The subslices are sorted in place, so the entire
+//! slice is now sorted.
+//!
+//! Unlike merge sort, all of the work happens in the divide step. Everything
+//! rests on the choice of pivot.
+//!
+//! *Best case*: _O(n lg(n))_ when each partition splits the slice evenly. The
+//! recurrence is the same as merge sort's, _2T(n/2) + O(n)_.
+//!
+//! *Worst case*: _O(n²)_ when each partition produces one subslice of _n - 1_
+//! elements and one of none, giving _T(n - 1) + O(n)_. When the first or last
+//! element is the pivot, this happens for input that is already sorted or
+//! reverse sorted. Choosing the pivot at random, or as the median of three
+//! elements, makes the worst case unlikely on such input.
+//!
+//! Lots of equal elements are a separate problem. The Lomuto partition puts
+//! every element equal to the pivot on the same side, so a slice of identical
+//! elements is also quadratic. The Hoare and three-way partitions don't have
+//! this problem.
+//!
+//! Each sort recurses into the smaller subslice and loops over the larger one,
+//! so the recursion depth is at most _lg(n)_, even in the worst case.
+//!
+//! None of the sorts are stable.
+
+use std::cmp::Ordering;
+
+use rand::{self, Rng};
+
+use super::instrument::{Instrument, Probe, Slot};
+use super::{Complexity, Properties, Sorter};
+
+/// The CLRS solution, using the Lomuto partition
+pub fn lomuto<T>(values: &mut [T])
+where
+    T: Ord
+{
+    lomuto_with(values, &mut Probe::new(&mut ()));
+}
+
+fn lomuto_with<T, I>(values: &mut [T], probe: &mut Probe<I>)
+where
+    T: Ord,
+    I: Instrument + ?Sized
+{
+    quicksort(values, probe, |values, probe| {
+        let q = lomuto_partition_with(values, probe);
+        (q, q + 1)
+    });
+}
+
+/// Quicksort using the Hoare partition
+pub fn hoare<T>(values: &mut [T])
+where
+    T: Ord
+{
+    hoare_with(values, &mut Probe::new(&mut ()));
+}
+
+fn hoare_with<T, I>(values: &mut [T], probe: &mut Probe<I>)
+where
+    T: Ord,
+    I: Instrument + ?Sized
+{
+    quicksort(values, probe, |values, probe| {
+        let q = hoare_partition_with(values, probe);
+        (q, q + 1)
+    });
+}
+
+/// The CLRS solution, choosing the pivot at random before each Lomuto
+/// partition
+///
+/// No particular input can elicit the worst case. The expected running time
+/// is _O(n lg(n))_ for every input of distinct elements.
+pub fn randomized<T>(values: &mut [T])
+where
+    T: Ord
+{
+    randomized_with(values, &mut Probe::new(&mut ()));
+}
+
+fn randomized_with<T, I>(values: &mut [T], probe: &mut Probe<I>)
+where
+    T: Ord,
+    I: Instrument + ?Sized
+{
+    let mut rng = rand::thread_rng();
+    quicksort(values, probe, |values, probe| {
+        let pivot = rng.gen_range(0, values.len());
+        let last = values.len() - 1;
+        values.swap(pivot, last);
+        probe.swap(pivot, last);
+
+        let q = lomuto_partition_with(values, probe);
+        (q, q + 1)
+    });
+}
+
+/// Quicksort choosing the median of the first, middle and last elements as
+/// the pivot before each Lomuto partition
+///
+/// Sorted and reverse sorted input is split evenly, rather than being the
+/// worst case.
+///
+/// This is for CLRS problem 7-5
+pub fn median_of_three<T>(values: &mut [T])
+where
+    T: Ord
+{
+    median_of_three_with(values, &mut Probe::new(&mut ()));
+}
+
+fn median_of_three_with<T, I>(values: &mut [T], probe: &mut Probe<I>)
+where
+    T: Ord,
+    I: Instrument + ?Sized
+{
+    quicksort(values, probe, |values, probe| {
+        let (lo, mid, hi) = (0, values.len() / 2, values.len() - 1);
+        for &(i, j) in &[(lo, mid), (lo, hi), (mid, hi)] {
+            let ordering = values[j].cmp(&values[i]);
+            if probe.compare(Slot::Values(j), Slot::Values(i), ordering) == Ordering::Less {
+                values.swap(i, j);
+                probe.swap(i, j);
+            }
+        }
+
+        values.swap(mid, hi);
+        probe.swap(mid, hi);
+
+        let q = lomuto_partition_with(values, probe);
+        (q, q + 1)
+    });
+}
+
+/// Quicksort using Dijkstra's three-way partition
+///
+/// Elements equal to the pivot are gathered in the middle and never looked at
+/// again, so input with few distinct values is sorted in close to linear
+/// time.
+pub fn three_way<T>(values: &mut [T])
+where
+    T: Ord
+{
+    three_way_with(values, &mut Probe::new(&mut ()));
+}
+
+fn three_way_with<T, I>(values: &mut [T], probe: &mut Probe<I>)
+where
+    T: Ord,
+    I: Instrument + ?Sized
+{
+    quicksort(values, probe, three_way_partition_with);
+}
+
+/// Sorts by repeatedly partitioning, recursing into the smaller side
+///
+/// `partition` rearranges a slice of at least two elements and returns
+/// `(lt, gt)`, such that everything in `values[..lt]` and `values[gt..]` still
+/// needs sorting, and everything between them is in its final position.
+fn quicksort<T, F, I>(values: &mut [T], probe: &mut Probe<I>, mut partition: F)
+where
+    F: FnMut(&mut [T], &mut Probe<I>) -> (usize, usize),
+    I: Instrument + ?Sized
+{
+    sort(values, &mut partition, probe);
+}
+
+fn sort<T, F, I>(mut values: &mut [T], partition: &mut F, probe: &mut Probe<I>)
+where
+    F: FnMut(&mut [T], &mut Probe<I>) -> (usize, usize),
+    I: Instrument + ?Sized
+{
+    let mut offset = 0;
+    while values.len() > 1 {
+        let (lt, gt) = partition(values, &mut probe.at(offset));
+        let (lower, rest) = values.split_at_mut(lt);
+        let upper = &mut rest[gt - lt..];
+
+        if lower.len() < upper.len() {
+            sort(lower, partition, &mut probe.at(offset));
+            values = upper;
+            offset += gt;
+        } else {
+            sort(upper, partition, &mut probe.at(offset + gt));
+            values = lower;
+        }
+    }
+}
+
+/// The CLRS partition, which uses the last element as the pivot
+///
+/// Returns the final index of the pivot, *q*. Every element of `values[..q]`
+/// is less than or equal to the pivot, and every element of `values[q + 1..]`
+/// is greater than it.
+///
+/// This matches the CLRS version with one exception: `i` is the index of the
+/// first element of the upper region, rather than the last element of the
+/// lower region. This prevents `i` from starting at *-1*, which would
+/// overflow a `usize`.
+///
+/// # Loop invariant
+///
+/// At the start of each iteration of the for loop, with *r* being the index of
+/// the pivot, every element of `values[..i]` is less than or equal to the
+/// pivot, every element of `values[i..j]` is greater than the pivot, and
+/// `values[j..r]` is yet to be examined.
+///
+/// *Initialization*: `i` and `j` are both zero, so the first two regions are
+/// empty.
+///
+/// *Maintenance*: If `values[j]` is greater than the pivot, incrementing `j`
+/// extends the upper region. Otherwise, swapping `values[j]` with `values[i]`
+/// moves it to the end of the lower region, and the first element of the upper
+/// region to its end. Incrementing both `i` and `j` reestablishes the
+/// invariant.
+///
+/// *Termination*: `j` is *r*, so every element has been placed in one of the
+/// two regions. Swapping the pivot with `values[i]`, the first element of the
+/// upper region, puts it between them.
+///
+/// # Panics
+///
+/// Panics if `values` is empty.
+pub fn lomuto_partition<T>(values: &mut [T]) -> usize
+where
+    T: Ord
+{
+    lomuto_partition_with(values, &mut Probe::new(&mut ()))
+}
+
+fn lomuto_partition_with<T, I>(values: &mut [T], probe: &mut Probe<I>) -> usize
+where
+    T: Ord,
+    I: Instrument + ?Sized
+{
+    let r = values.len() - 1;
+    let mut i = 0;
+    for j in 0..r {
+        if probe.compare(Slot::Values(j), Slot::Values(r), values[j].cmp(&values[r])) != Ordering::Greater {
+            values.swap(i, j);
+            probe.swap(i, j);
+            i += 1;
+        }
+    }
+
+    values.swap(i, r);
+    probe.swap(i, r);
+    i
+}
+
+/// The Hoare partition, which uses the first element as the pivot
+///
+/// Returns the final index of the pivot, *q*. Every element of `values[..q]`
+/// is less than or equal to the pivot, and every element of `values[q + 1..]`
+/// is greater than or equal to it.
+///
+/// Two indices move towards each other from either end of the slice, skipping
+/// elements that are already on the correct side. When both stop, the
+/// elements they point to are swapped. Both stop on elements equal to the
+/// pivot, which spreads them evenly across the two sides.
+///
+/// The version in CLRS problem 7-1 copies the pivot and lets it be swapped
+/// like any other element, so it doesn't end up in its final position. Here
+/// the pivot stays at the front while the rest is partitioned, then is
+/// swapped into place, which needs neither `Clone` nor an extra comparison
+/// afterwards.
+///
+/// # Loop invariant
+///
+/// At the start of each iteration of the loop, every element of `rest[..i]`
+/// is less than or equal to the pivot and every element of `rest[j..]` is
+/// greater than or equal to it, where `rest` is the slice after the pivot.
+///
+/// *Initialization*: `i` is zero and `j` is the length of `rest`, so both
+/// regions are empty.
+///
+/// *Maintenance*: The inner loops only move past elements that belong to their
+/// region. If the indices haven't met, `rest[i]` is at least the pivot and
+/// `rest[j - 1]` is at most the pivot, so swapping them lets both regions grow
+/// by one.
+///
+/// *Termination*: `i` is at least `j`, so every element is in one of the
+/// regions. `values[i]` is the last element of the lower region, and swapping
+/// it with the pivot puts the pivot between them.
+///
+/// # Panics
+///
+/// Panics if `values` is empty.
+pub fn hoare_partition<T>(values: &mut [T]) -> usize
+where
+    T: Ord
+{
+    hoare_partition_with(values, &mut Probe::new(&mut ()))
+}
+
+fn hoare_partition_with<T, I>(values: &mut [T], probe: &mut Probe<I>) -> usize
+where
+    T: Ord,
+    I: Instrument + ?Sized
+{
+    let mut i = 0;
+    {
+        let (pivot, rest) = values.split_first_mut().unwrap();
+        let mut j = rest.len();
+        loop {
+            while i < j && probe.compare(Slot::Values(i + 1), Slot::Values(0), rest[i].cmp(pivot)) == Ordering::Less {
+                i += 1;
+            }
+            while i < j && probe.compare(Slot::Values(j), Slot::Values(0), rest[j - 1].cmp(pivot)) == Ordering::Greater {
+                j -= 1;
+            }
+            if i >= j { break; }
+
+            j -= 1;
+            rest.swap(i, j);
+            probe.swap(i + 1, j + 1);
+            i += 1;
+        }
+    }
+
+    values.swap(0, i);
+    probe.swap(0, i);
+    i
+}
+
+/// Dijkstra's three-way partition, which uses the first element as the pivot
+///
+/// Returns `(lt, gt)`. Every element of `values[..lt]` is less than the pivot,
+/// every element of `values[lt..gt]` is equal to it, and every element of
+/// `values[gt..]` is greater than it.
+///
+/// This is also known as the Dutch national flag problem.
+///
+/// # Loop invariant
+///
+/// At the start of each iteration of the loop, every element of `values[..lt]`
+/// is less than the pivot, every element of `values[lt..i]` is equal to it,
+/// every element of `values[gt..]` is greater than it, and `values[i..gt]` is
+/// yet to be examined.
+///
+/// *Initialization*: `lt` is zero and `i` is one, so the equal region holds
+/// just the pivot. The other two regions are empty.
+///
+/// *Maintenance*: A smaller `values[i]` is swapped with the first equal
+/// element, growing the lower region and moving the equal region along by
+/// one. A larger `values[i]` is swapped with the last unexamined element,
+/// growing the upper region. An equal `values[i]` is already in place.
+///
+/// *Termination*: `i` is `gt`, so there is nothing left to examine.
+///
+/// # Panics
+///
+/// Panics if `values` is empty.
+pub fn three_way_partition<T>(values: &mut [T]) -> (usize, usize)
+where
+    T: Ord
+{
+    three_way_partition_with(values, &mut Probe::new(&mut ()))
+}
+
+fn three_way_partition_with<T, I>(values: &mut [T], probe: &mut Probe<I>) -> (usize, usize)
+where
+    T: Ord,
+    I: Instrument + ?Sized
+{
+    let (mut lt, mut i, mut gt) = (0, 1, values.len());
+    while i < gt {
+        match probe.compare(Slot::Values(i), Slot::Values(lt), values[i].cmp(&values[lt])) {
+            Ordering::Less => {
+                values.swap(lt, i);
+                probe.swap(lt, i);
+                lt += 1;
+                i += 1;
+            }
+            Ordering::Greater => {
+                gt -= 1;
+                values.swap(i, gt);
+                probe.swap(i, gt);
+            }
+            Ordering::Equal => i += 1,
+        }
+    }
+
+    (lt, gt)
+}
+
+/// The Lomuto solution as a `Sorter`
+pub struct Lomuto;
+
+impl<T: Ord> Sorter<T> for Lomuto {
+    fn properties(&self) -> Properties {
+        Properties {
+            name: "Quicksort (Lomuto)",
+            stable: false,
+            in_place: true,
+            worst_case: Complexity::Quadratic,
+            average_case: Complexity::Linearithmic,
+            space: Complexity::Logarithmic,
+        }
+    }
+
+    fn sort(&self, values: &mut [T]) {
+        lomuto(values);
+    }
+
+    fn sort_instrumented(&self, values: &mut [T], instrument: &mut dyn Instrument) {
+        lomuto_with(values, &mut Probe::new(instrument));
+    }
+}
+
+/// The Hoare solution as a `Sorter`
+pub struct Hoare;
+
+impl<T: Ord> Sorter<T> for Hoare {
+    fn properties(&self) -> Properties {
+        Properties {
+            name: "Quicksort (Hoare)",
+            stable: false,
+            in_place: true,
+            worst_case: Complexity::Quadratic,
+            average_case: Complexity::Linearithmic,
+            space: Complexity::Logarithmic,
+        }
+    }
+
+    fn sort(&self, values: &mut [T]) {
+        hoare(values);
+    }
+
+    fn sort_instrumented(&self, values: &mut [T], instrument: &mut dyn Instrument) {
+        hoare_with(values, &mut Probe::new(instrument));
+    }
+}
+
+/// The randomized solution as a `Sorter`
+pub struct Randomized;
+
+impl<T: Ord> Sorter<T> for Randomized {
+    fn properties(&self) -> Properties {
+        Properties {
+            name: "Quicksort (randomized)",
+            stable: false,
+            in_place: true,
+            worst_case: Complexity::Quadratic,
+            average_case: Complexity::Linearithmic,
+            space: Complexity::Logarithmic,
+        }
+    }
+
+    fn sort(&self, values: &mut [T]) {
+        randomized(values);
+    }
+
+    fn sort_instrumented(&self, values: &mut [T], instrument: &mut dyn Instrument) {
+        randomized_with(values, &mut Probe::new(instrument));
+    }
+}
+
+/// The median-of-three solution as a `Sorter`
+pub struct MedianOfThree;
+
+impl<T: Ord> Sorter<T> for MedianOfThree {
+    fn properties(&self) -> Properties {
+        Properties {
+            name: "Quicksort (median of three)",
+            stable: false,
+            in_place: true,
+            worst_case: Complexity::Quadratic,
+            average_case: Complexity::Linearithmic,
+            space: Complexity::Logarithmic,
+        }
+    }
+
+    fn sort(&self, values: &mut [T]) {
+        median_of_three(values);
+    }
+
+    fn sort_instrumented(&self, values: &mut [T], instrument: &mut dyn Instrument) {
+        median_of_three_with(values, &mut Probe::new(instrument));
+    }
+}
+
+/// The three-way solution as a `Sorter`
+pub struct ThreeWay;
+
+impl<T: Ord> Sorter<T> for ThreeWay {
+    fn properties(&self) -> Properties {
+        Properties {
+            name: "Quicksort (three-way)",
+            stable: false,
+            in_place: true,
+            worst_case: Complexity::Quadratic,
+            average_case: Complexity::Linearithmic,
+            space: Complexity::Logarithmic,
+        }
+    }
+
+    fn sort(&self, values: &mut [T]) {
+        three_way(values);
+    }
+
+    fn sort_instrumented(&self, values: &mut [T], instrument: &mut dyn Instrument) {
+        three_way_with(values, &mut Probe::new(instrument));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(sort: fn(&mut [i32])) {
+        let mut values = [5, 2, 4, 6, 1, 3];
+        sort(&mut values);
+        assert_eq!(values, [1, 2, 3, 4, 5, 6]);
+
+        let mut values = [31, 41, 59, 26, 41, 58];
+        sort(&mut values);
+        assert_eq!(values, [26, 31, 41, 41, 58, 59]);
+
+        let mut values = [13, 19, 9, 5, 12, 8, 7, 4, 21, 2, 6, 11];
+        sort(&mut values);
+        assert_eq!(values, [2, 4, 5, 6, 7, 8, 9, 11, 12, 13, 19, 21]);
+
+        let mut values = [3; 8];
+        sort(&mut values);
+        assert_eq!(values, [3; 8]);
+
+        let mut values: Vec<_> = (0..100).rev().collect();
+        sort(&mut values);
+        assert_eq!(values, (0..100).collect::<Vec<_>>());
+
+        let mut empty = [0; 0];
+        sort(&mut empty);
+        assert_eq!(empty, []);
+
+        let mut single = [1];
+        sort(&mut single);
+        assert_eq!(single, [1]);
+    }
+
+    #[test]
+    fn test_lomuto() {
+        check(lomuto);
+    }
+
+    #[test]
+    fn test_hoare() {
+        check(hoare);
+    }
+
+    #[test]
+    fn test_randomized() {
+        check(randomized);
+    }
+
+    #[test]
+    fn test_median_of_three() {
+        check(median_of_three);
+    }
+
+    #[test]
+    fn test_three_way() {
+        check(three_way);
+    }
+
+    #[test]
+    fn test_lomuto_partition() {
+        let mut values = [2, 8, 7, 1, 3, 5, 6, 4];
+        assert_eq!(lomuto_partition(&mut values), 3);
+        assert_eq!(values, [2, 1, 3, 4, 7, 5, 6, 8]);
+    }
+
+    #[test]
+    fn test_hoare_partition() {
+        let mut values = [13, 19, 9, 5, 12, 8, 7, 4, 11, 2, 6, 21];
+        let q = hoare_partition(&mut values);
+        assert_eq!(values[q], 13);
+        assert!(values[..q].iter().all(|&v| v <= 13));
+        assert!(values[q + 1..].iter().all(|&v| v >= 13));
+    }
+
+    #[test]
+    fn test_three_way_partition() {
+        let mut values = [4, 7, 4, 1, 4, 9, 2, 4];
+        let (lt, gt) = three_way_partition(&mut values);
+        assert_eq!((lt, gt), (2, 6));
+        assert!(values[..lt].iter().all(|&v| v < 4));
+        assert_eq!(values[lt..gt], [4, 4, 4, 4]);
+        assert!(values[gt..].iter().all(|&v| v > 4));
+    }
+}
@@ -0,0 +1,301 @@
+//! Radix sort
+//!
+//! Sorts elements made up of _d_ digits, each taking one of _k_ values, in
+//! _O(d(n + k))_ time, without comparing elements to each other.
+//!
+//! *Least significant digit* (LSD) radix sort sorts by the last digit first,
+//! then by each more significant digit in turn, using a stable sort each time.
+//! Because each pass is stable, elements that are equal in the current digit
+//! stay in the order given by the less significant digits. After the final
+//! pass, the elements are sorted by every digit. This is how card sorting
+//! machines sorted punched cards.
+//!
+//! *Most significant digit* (MSD) radix sort goes the other way, splitting the
+//! elements into groups by their first digit, then sorting each group
+//! recursively by the remaining digits. It only looks at as many digits as
+//! are needed to tell elements apart, which suits strings of varying length.
+//!
+//! Here a digit is a byte, so _k = 256_, and each pass is a counting sort.
+//!
+//! # Example
+//!
+//! ```text
+//! 329      720      720      329
+//! 457      355      329      355
+//! 657      436      436      436
+//! 839  =>  457  =>  839  =>  457
+//! 436      657      355      657
+//! 720      329      457      720
+//! 355      839      657      839
+//!            ^       ^       ^
+//! ```
+
+use std::mem;
+
+use num::{PrimInt, Unsigned};
+
+use super::counting::counting_sort_by_key_with;
+use super::instrument::{Instrument, Probe};
+use super::merge;
+use super::{Complexity, Properties, Sorter};
+
+/// Below this many elements, MSD radix sort switches to merge sort
+const MSD_CUTOFF: usize = 16;
+
+/// LSD radix sort for unsigned integers
+///
+/// Each byte is a digit. Passes stop once every remaining byte of the largest
+/// value is zero, so small values in wide types need fewer passes.
+pub fn lsd<T>(values: &mut [T])
+where
+    T: PrimInt + Unsigned
+{
+    lsd_with(values, &mut Probe::new(&mut ()));
+}
+
+pub(super) fn lsd_with<T, I>(values: &mut [T], probe: &mut Probe<I>)
+where
+    T: PrimInt + Unsigned,
+    I: Instrument + ?Sized
+{
+    let max = match values.iter().max() {
+        Some(&max) => max,
+        None => return,
+    };
+
+    let mask = T::from(0xff).unwrap();
+    let mut shift = 0;
+    while shift < mem::size_of::<T>() * 8 && max >> shift != T::zero() {
+        counting_sort_by_key_with(values, 256, &mut |&value: &T| ((value >> shift) & mask).to_usize().unwrap(), probe);
+        shift += 8;
+    }
+}
+
+/// LSD radix sort for byte strings, ordering them lexicographically
+///
+/// Byte strings of different lengths are treated as if padded at the end with
+/// a digit smaller than any byte, so a prefix sorts before the strings that
+/// extend it. This needs one pass per byte of the longest string.
+pub fn lsd_bytes<T>(values: &mut [T])
+where
+    T: AsRef<[u8]> + Clone
+{
+    lsd_bytes_with(values, &mut Probe::new(&mut ()));
+}
+
+fn lsd_bytes_with<T, I>(values: &mut [T], probe: &mut Probe<I>)
+where
+    T: AsRef<[u8]> + Clone,
+    I: Instrument + ?Sized
+{
+    let width = values.iter().map(|value| value.as_ref().len()).max().unwrap_or(0);
+    for d in (0..width).rev() {
+        counting_sort_by_key_with(values, 257, &mut |value: &T| digit(value.as_ref(), d), probe);
+    }
+}
+
+/// MSD radix sort for strings or byte strings, ordering them lexicographically
+///
+/// UTF-8 preserves the order of code points, so sorting strings by their
+/// bytes gives the same order as comparing them with `Ord`.
+pub fn msd<T>(values: &mut [T])
+where
+    T: AsRef<[u8]> + Clone
+{
+    msd_from(values, 0, &mut Probe::new(&mut ()));
+}
+
+/// Sorts strings that share their first `depth` bytes
+///
+/// The groups still to be sorted are kept on a stack rather than recursed
+/// into. A group of strings with a long common prefix is split once per byte
+/// of the prefix, so recursion would need a stack frame for each byte. Bytes
+/// that every string in a group shares are skipped without a counting sort,
+/// which would only leave the group as it was.
+fn msd_from<T, I>(values: &mut [T], depth: usize, probe: &mut Probe<I>)
+where
+    T: AsRef<[u8]> + Clone,
+    I: Instrument + ?Sized
+{
+    let mut groups = vec![(0, values.len(), depth)];
+    'groups: while let Some((start, end, mut depth)) = groups.pop() {
+        let group = &mut values[start..end];
+        let mut probe = probe.at(start);
+        if group.len() <= MSD_CUTOFF {
+            let len = group.len();
+            let mut compare = |a: &T, b: &T| a.as_ref()[depth..].cmp(&b.as_ref()[depth..]);
+            merge::merge_sort_with(group, 0, len, &mut compare, &mut probe);
+            continue;
+        }
+
+        loop {
+            let first = digit(group[0].as_ref(), depth);
+            if group.iter().any(|value| digit(value.as_ref(), depth) != first) {
+                break;
+            }
+
+            // Every string has ended, so they are all equal
+            if first == 0 {
+                continue 'groups;
+            }
+
+            depth += 1;
+        }
+
+        counting_sort_by_key_with(group, 257, &mut |value: &T| digit(value.as_ref(), depth), &mut probe);
+
+        let mut i = 0;
+        while i < group.len() {
+            let key = digit(group[i].as_ref(), depth);
+            let j = i + group[i..]
+                .iter()
+                .take_while(|value| digit(value.as_ref(), depth) == key)
+                .count();
+
+            if key != 0 {
+                groups.push((start + i, start + j, depth + 1));
+            }
+
+            i = j;
+        }
+    }
+}
+
+/// Returns the byte at `d` offset by one, or zero past the end of `bytes`
+fn digit(bytes: &[u8], d: usize) -> usize {
+    bytes.get(d).map_or(0, |&byte| byte as usize + 1)
+}
+
+/// LSD radix sort for unsigned integers as a `Sorter`
+pub struct Lsd;
+
+impl<T: PrimInt + Unsigned> Sorter<T> for Lsd {
+    fn properties(&self) -> Properties {
+        Properties {
+            name: "Radix Sort (LSD)",
+            stable: true,
+            in_place: false,
+            worst_case: Complexity::Linear,
+            average_case: Complexity::Linear,
+            space: Complexity::Linear,
+        }
+    }
+
+    fn sort(&self, values: &mut [T]) {
+        lsd(values);
+    }
+
+    fn sort_instrumented(&self, values: &mut [T], instrument: &mut dyn Instrument) {
+        lsd_with(values, &mut Probe::new(instrument));
+    }
+}
+
+/// LSD radix sort for byte strings as a `Sorter`
+pub struct LsdBytes;
+
+impl<T: AsRef<[u8]> + Clone> Sorter<T> for LsdBytes {
+    fn properties(&self) -> Properties {
+        Properties {
+            name: "Radix Sort (LSD, byte strings)",
+            stable: true,
+            in_place: false,
+            worst_case: Complexity::Linear,
+            average_case: Complexity::Linear,
+            space: Complexity::Linear,
+        }
+    }
+
+    fn sort(&self, values: &mut [T]) {
+        lsd_bytes(values);
+    }
+
+    fn sort_instrumented(&self, values: &mut [T], instrument: &mut dyn Instrument) {
+        lsd_bytes_with(values, &mut Probe::new(instrument));
+    }
+}
+
+/// MSD radix sort as a `Sorter`
+pub struct Msd;
+
+impl<T: AsRef<[u8]> + Clone> Sorter<T> for Msd {
+    fn properties(&self) -> Properties {
+        Properties {
+            name: "Radix Sort (MSD, strings)",
+            stable: true,
+            in_place: false,
+            worst_case: Complexity::Linear,
+            average_case: Complexity::Linear,
+            space: Complexity::Linear,
+        }
+    }
+
+    fn sort(&self, values: &mut [T]) {
+        msd(values);
+    }
+
+    fn sort_instrumented(&self, values: &mut [T], instrument: &mut dyn Instrument) {
+        msd_from(values, 0, &mut Probe::new(instrument));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lsd() {
+        let mut values = [329u16, 457, 657, 839, 436, 720, 355];
+        lsd(&mut values);
+        assert_eq!(values, [329, 355, 436, 457, 657, 720, 839]);
+
+        let mut values = [u64::MAX, 0, 1 << 40, 255, 256];
+        lsd(&mut values);
+        assert_eq!(values, [0, 255, 256, 1 << 40, u64::MAX]);
+
+        let mut values = [5u8, 2, 4, 6, 1, 3];
+        lsd(&mut values);
+        assert_eq!(values, [1, 2, 3, 4, 5, 6]);
+
+        let mut empty = [0u32; 0];
+        lsd(&mut empty);
+        assert_eq!(empty, []);
+    }
+
+    #[test]
+    fn test_lsd_bytes() {
+        let mut values = [&b"dab"[..], b"cab", b"fad", b"b", b"", b"ba", b"bad"];
+        lsd_bytes(&mut values);
+        assert_eq!(values, [&b""[..], b"b", b"ba", b"bad", b"cab", b"dab", b"fad"]);
+    }
+
+    #[test]
+    fn test_msd() {
+        let mut values = ["she", "sells", "seashells", "by", "the", "sea", "shore", "the",
+                          "shells", "she", "sells", "are", "surely", "seashells", "", "ü"];
+        let mut expected = values;
+        expected.sort();
+        msd(&mut values);
+        assert_eq!(values, expected);
+
+        let mut values = vec![String::from("b"), String::from("a")];
+        msd(&mut values);
+        assert_eq!(values, ["a", "b"]);
+    }
+
+    #[test]
+    fn test_msd_long_prefix() {
+        // Each byte of the shared prefix is another level of groups, which
+        // would overflow the stack if they were recursed into
+        let prefix = vec![b'a'; 1 << 20];
+        let mut values: Vec<Vec<u8>> = (0..2 * MSD_CUTOFF as u8).rev().map(|last| {
+            let mut value = prefix.clone();
+            value.push(last);
+            value
+        }).collect();
+        let mut expected = values.clone();
+        expected.sort();
+
+        msd(&mut values);
+        assert!(values == expected);
+    }
+}
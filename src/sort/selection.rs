@@ -24,6 +24,43 @@
 //! *Word case*: _O(n²)_ because the inner loop has to iterate through
 //! `i + 1..n` values to search for the minimum, regardless of how sorted the
 //! array already is.
+//!
+//! # Order statistics
+//!
+//! The _k_-th smallest element can be found without sorting everything.
+//! `select_nth` uses *quickselect*: partition around a random pivot, as
+//! quicksort does, but only continue into the side that holds index _k_. The
+//! slices shrink geometrically on average, so this takes _O(n)_ expected time.
+//!
+//! Unlucky pivots can still make it quadratic, so after too many partitions
+//! that fail to shrink the slice by a quarter it falls back to choosing pivots
+//! by *median of medians*. The elements are split into groups of five, and the
+//! median of their medians is found recursively. At least _3n/10_ elements
+//! are on each side of that pivot, which gives the recurrence
+//! _T(n) ≤ T(n/5) + T(7n/10) + O(n)_, so the worst case is _O(n)_.
+//!
+//! `top_k` and `bottom_k` instead keep the best _k_ elements seen so far in a
+//! heap, taking _O(n lg(k))_ time and _O(k)_ space, so they work on iterators
+//! of any length.
+//!
+//! ```
+//! use kb::sort::selection;
+//!
+//! let mut values = [31, 41, 59, 26, 41, 58];
+//! assert_eq!(*selection::select_nth(&mut values, 1), 31);
+//! assert_eq!(selection::median(&mut values), Some(&41));
+//! assert_eq!(selection::bottom_k(vec![31, 41, 59, 26, 41, 58], 2), [26, 31]);
+//! ```
+
+use std::cmp::Ordering;
+
+use rand::{self, Rng};
+
+use heap::binary;
+use super::heap::heapsort_by;
+use super::insertion;
+use super::instrument::{Instrument, Probe, Slot};
+use super::{Complexity, Properties, Sorter};
 
 /// My own implementation of CLRS exercise 2.2-2
 ///
@@ -35,13 +72,296 @@ pub fn selection<T>(values: &mut [T])
 where
     T: PartialOrd
 {
-    for i in 0..values.len() - 1 {
+    selection_by(values, super::partial_order);
+}
+
+/// Selection sort, ordering elements with a comparator function
+pub fn selection_by<T, F>(values: &mut [T], mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering
+{
+    selection_with(values, &mut compare, &mut Probe::new(&mut ()));
+}
+
+fn selection_with<T, F, I>(values: &mut [T], compare: &mut F, probe: &mut Probe<I>)
+where
+    F: FnMut(&T, &T) -> Ordering,
+    I: Instrument + ?Sized
+{
+    for i in 0..values.len().saturating_sub(1) {
         let mut min = i;
         for j in i + 1..values.len() {
-            if values[j] < values[min] { min = j; }
+            let ordering = compare(&values[j], &values[min]);
+            if probe.compare(Slot::Values(j), Slot::Values(min), ordering) == Ordering::Less { min = j; }
         }
 
         values.swap(i, min);
+        probe.swap(i, min);
+    }
+}
+
+/// Selection sort, ordering elements by a key extraction function
+pub fn selection_by_key<T, K, F>(values: &mut [T], mut key: F)
+where
+    K: Ord,
+    F: FnMut(&T) -> K
+{
+    selection_by(values, |a, b| key(a).cmp(&key(b)));
+}
+
+/// Reorders a slice so that `values[k]` is the element that would be there if
+/// it were sorted, and returns it
+///
+/// Every element before `k` is less than or equal to it, and every element
+/// after is greater than or equal to it.
+///
+/// # Panics
+///
+/// Panics if `k` is out of bounds.
+pub fn select_nth<T>(values: &mut [T], k: usize) -> &T
+where
+    T: Ord
+{
+    select_nth_by(values, k, T::cmp)
+}
+
+/// Selects the `k`-th element with respect to a comparator function
+pub fn select_nth_by<T, F>(values: &mut [T], k: usize, mut compare: F) -> &T
+where
+    F: FnMut(&T, &T) -> Ordering
+{
+    assert!(k < values.len(), "index {} is out of bounds for length {}", k, values.len());
+
+    // Allows about two bad partitions per halving before giving up on chance
+    let budget = 2 * (usize::BITS - values.len().leading_zeros()) as usize;
+    select(values, k, &mut compare, budget);
+    &values[k]
+}
+
+/// Selects the `k`-th element by a key extraction function
+pub fn select_nth_by_key<T, K, F>(values: &mut [T], k: usize, mut key: F) -> &T
+where
+    K: Ord,
+    F: FnMut(&T) -> K
+{
+    select_nth_by(values, k, |a, b| key(a).cmp(&key(b)))
+}
+
+/// Returns the lower median of a slice, reordering it as `select_nth` does,
+/// or `None` if it is empty
+pub fn median<T>(values: &mut [T]) -> Option<&T>
+where
+    T: Ord
+{
+    median_by(values, T::cmp)
+}
+
+/// Returns the lower median with respect to a comparator function
+pub fn median_by<T, F>(values: &mut [T], compare: F) -> Option<&T>
+where
+    F: FnMut(&T, &T) -> Ordering
+{
+    if values.is_empty() { return None }
+
+    let k = (values.len() - 1) / 2;
+    Some(select_nth_by(values, k, compare))
+}
+
+/// Returns the lower median by a key extraction function
+pub fn median_by_key<T, K, F>(values: &mut [T], mut key: F) -> Option<&T>
+where
+    K: Ord,
+    F: FnMut(&T) -> K
+{
+    median_by(values, |a, b| key(a).cmp(&key(b)))
+}
+
+/// Returns the `k` largest elements, largest first
+pub fn top_k<I>(iter: I, k: usize) -> Vec<I::Item>
+where
+    I: IntoIterator,
+    I::Item: Ord
+{
+    top_k_by(iter, k, Ord::cmp)
+}
+
+/// Returns the `k` largest elements with respect to a comparator function,
+/// largest first
+pub fn top_k_by<I, F>(iter: I, k: usize, mut compare: F) -> Vec<I::Item>
+where
+    I: IntoIterator,
+    F: FnMut(&I::Item, &I::Item) -> Ordering
+{
+    bottom_k_by(iter, k, |a, b| compare(b, a))
+}
+
+/// Returns the `k` largest elements by a key extraction function, largest
+/// first
+pub fn top_k_by_key<I, K, F>(iter: I, k: usize, mut key: F) -> Vec<I::Item>
+where
+    I: IntoIterator,
+    K: Ord,
+    F: FnMut(&I::Item) -> K
+{
+    top_k_by(iter, k, |a, b| key(a).cmp(&key(b)))
+}
+
+/// Returns the `k` smallest elements, smallest first
+pub fn bottom_k<I>(iter: I, k: usize) -> Vec<I::Item>
+where
+    I: IntoIterator,
+    I::Item: Ord
+{
+    bottom_k_by(iter, k, Ord::cmp)
+}
+
+/// Returns the `k` smallest elements with respect to a comparator function,
+/// smallest first
+///
+/// The elements kept so far are in a max-heap, so the largest of them is at
+/// the root. Each new element only needs to be compared with the root, and
+/// replaces it if it is smaller.
+///
+/// The heap is sized by the input where it can be, so a `k` far larger than
+/// the input doesn't allocate room for elements that will never come.
+pub fn bottom_k_by<I, F>(iter: I, k: usize, mut compare: F) -> Vec<I::Item>
+where
+    I: IntoIterator,
+    F: FnMut(&I::Item, &I::Item) -> Ordering
+{
+    if k == 0 { return Vec::new() }
+
+    let iter = iter.into_iter();
+    let mut heap = Vec::with_capacity(k.min(iter.size_hint().0));
+    for value in iter {
+        if heap.len() < k {
+            heap.push(value);
+            let i = heap.len() - 1;
+            binary::sift_up_by(&mut heap, i, &mut compare);
+        } else if compare(&value, &heap[0]) == Ordering::Less {
+            heap[0] = value;
+            binary::sift_down_by(&mut heap, 0, &mut compare);
+        }
+    }
+
+    heapsort_by(&mut heap, compare);
+    heap
+}
+
+/// Returns the `k` smallest elements by a key extraction function, smallest
+/// first
+pub fn bottom_k_by_key<I, K, F>(iter: I, k: usize, mut key: F) -> Vec<I::Item>
+where
+    I: IntoIterator,
+    K: Ord,
+    F: FnMut(&I::Item) -> K
+{
+    bottom_k_by(iter, k, |a, b| key(a).cmp(&key(b)))
+}
+
+/// Quickselect, with random pivots until `budget` partitions have failed to
+/// shrink the slice by a quarter, and median of medians pivots after that
+///
+/// A `budget` of zero always uses the median of medians.
+fn select<T, F>(mut values: &mut [T], mut k: usize, compare: &mut F, mut budget: usize)
+where
+    F: FnMut(&T, &T) -> Ordering
+{
+    let mut rng = rand::thread_rng();
+    while values.len() > 1 {
+        let n = values.len();
+        let pivot = if budget == 0 { median_of_medians(values, compare) } else { rng.gen_range(0, n) };
+        let (lt, gt) = partition(values, pivot, compare);
+
+        let (start, end) = if k < lt {
+            (0, lt)
+        } else if k >= gt {
+            (gt, n)
+        } else {
+            return;
+        };
+
+        if budget > 0 && 4 * (end - start) > 3 * n { budget -= 1; }
+        k -= start;
+        values = &mut values[start..end];
+    }
+}
+
+/// Moves the median of each group of five to the front of the slice, and
+/// returns the index of the median of those medians
+fn median_of_medians<T, F>(values: &mut [T], compare: &mut F) -> usize
+where
+    F: FnMut(&T, &T) -> Ordering
+{
+    let groups = values.len().div_ceil(5);
+    for group in 0..groups {
+        let start = group * 5;
+        let end = (start + 5).min(values.len());
+        insertion::shepmaster_by(&mut values[start..end], &mut *compare);
+        values.swap(group, start + (end - start - 1) / 2);
+    }
+
+    let k = (groups - 1) / 2;
+    select(&mut values[..groups], k, compare, 0);
+    k
+}
+
+/// Partitions a slice around `values[pivot]` into elements less than, equal to
+/// and greater than it, and returns the bounds of the equal elements
+///
+/// The pivot is moved to the front, then the rest is partitioned as in the
+/// three-way quicksort, so runs of equal elements can't make selection
+/// quadratic. Also used by `partial::sorted_iter`.
+pub(super) fn partition<T, F>(values: &mut [T], pivot: usize, compare: &mut F) -> (usize, usize)
+where
+    F: FnMut(&T, &T) -> Ordering
+{
+    values.swap(0, pivot);
+
+    let (mut lt, mut i, mut gt) = (1, 1, values.len());
+    while i < gt {
+        match compare(&values[i], &values[0]) {
+            Ordering::Less => {
+                values.swap(lt, i);
+                lt += 1;
+                i += 1;
+            }
+            Ordering::Greater => {
+                gt -= 1;
+                values.swap(i, gt);
+            }
+            Ordering::Equal => i += 1,
+        }
+    }
+
+    values.swap(0, lt - 1);
+    (lt - 1, gt)
+}
+
+/// Selection sort as a `Sorter`
+///
+/// Swapping the minimum into place can move an element past others equal to
+/// it, so it is not stable.
+pub struct Selection;
+
+impl<T: PartialOrd> Sorter<T> for Selection {
+    fn properties(&self) -> Properties {
+        Properties {
+            name: "Selection Sort",
+            stable: false,
+            in_place: true,
+            worst_case: Complexity::Quadratic,
+            average_case: Complexity::Quadratic,
+            space: Complexity::Constant,
+        }
+    }
+
+    fn sort(&self, values: &mut [T]) {
+        selection(values);
+    }
+
+    fn sort_instrumented(&self, values: &mut [T], instrument: &mut dyn Instrument) {
+        selection_with(values, &mut super::partial_order, &mut Probe::new(instrument));
     }
 }
 
@@ -54,5 +374,95 @@ mod tests {
         let mut values = [5, 2, 4, 6, 1, 3];
         selection(&mut values);
         assert_eq!(values, [1, 2, 3, 4, 5, 6]);
+
+        let mut empty = [0; 0];
+        selection(&mut empty);
+        assert_eq!(empty, []);
+    }
+
+    #[test]
+    fn test_selection_by() {
+        let mut values = [5, 2, 4, 6, 1, 3];
+        selection_by(&mut values, |a, b| b.cmp(a));
+        assert_eq!(values, [6, 5, 4, 3, 2, 1]);
+
+        let mut values = [(3, 'a'), (1, 'b'), (2, 'c')];
+        selection_by_key(&mut values, |&(k, _)| k);
+        assert_eq!(values, [(1, 'b'), (2, 'c'), (3, 'a')]);
+    }
+
+    /// Checks that `values[k]` is in its sorted position, and that it
+    /// partitions the rest
+    fn check_selected(values: &[u32], k: usize, sorted: &[u32]) {
+        assert_eq!(values[k], sorted[k], "k = {}", k);
+        assert!(values[..k].iter().all(|v| *v <= values[k]));
+        assert!(values[k + 1..].iter().all(|v| *v >= values[k]));
+    }
+
+    #[test]
+    fn test_select_nth() {
+        let input: Vec<u32> = (0..300u32).map(|i| (i * 7919) % 101).collect();
+        let mut sorted = input.clone();
+        sorted.sort();
+
+        for k in 0..input.len() {
+            let mut values = input.clone();
+            assert_eq!(*select_nth(&mut values, k), sorted[k]);
+            check_selected(&values, k, &sorted);
+        }
+
+        let mut values = [5];
+        assert_eq!(*select_nth(&mut values, 0), 5);
+
+        let mut values = [(3, 'a'), (1, 'b'), (2, 'c')];
+        assert_eq!(*select_nth_by_key(&mut values, 0, |&(k, _)| k), (1, 'b'));
+        assert_eq!(*select_nth_by(&mut values, 0, |a, b| b.cmp(a)), (3, 'a'));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_select_nth_out_of_bounds() {
+        select_nth(&mut [1, 2, 3], 3);
+    }
+
+    #[test]
+    fn test_median_of_medians() {
+        for &n in &[1, 2, 5, 6, 24, 25, 26, 125, 1000] {
+            let input: Vec<u32> = (0..n as u32).rev().chain((0..n as u32 / 3).map(|_| 7)).collect();
+            let mut sorted = input.clone();
+            sorted.sort();
+
+            for &k in &[0, input.len() / 2, input.len() - 1] {
+                let mut values = input.clone();
+                select(&mut values, k, &mut u32::cmp, 0);
+                check_selected(&values, k, &sorted);
+            }
+        }
+    }
+
+    #[test]
+    fn test_median() {
+        assert_eq!(median(&mut [3, 1, 2]), Some(&2));
+        assert_eq!(median(&mut [4, 3, 1, 2]), Some(&2));
+        assert_eq!(median::<u32>(&mut []), None);
+        assert_eq!(median_by(&mut [4, 3, 1, 2], |a, b| b.cmp(a)), Some(&3));
+        assert_eq!(median_by_key(&mut [(1, 'a'), (3, 'b'), (2, 'c')], |&(k, _)| k), Some(&(2, 'c')));
+    }
+
+    #[test]
+    fn test_top_k() {
+        let values = vec![31, 41, 59, 26, 41, 58];
+        assert_eq!(top_k(values.clone(), 3), [59, 58, 41]);
+        assert_eq!(bottom_k(values.clone(), 3), [26, 31, 41]);
+        assert_eq!(top_k(values.clone(), 10), [59, 58, 41, 41, 31, 26]);
+        assert_eq!(bottom_k(values.clone(), 0), []);
+        assert_eq!(bottom_k(0..1_000_000, 2), [0, 1]);
+        assert_eq!(top_k(vec![3, 1, 2], usize::MAX), [3, 2, 1]);
+        assert_eq!(bottom_k(values.iter().filter(|&&v| v > 40), usize::MAX), [&41, &41, &58, &59]);
+
+        let words = ["pear", "fig", "banana", "kiwis"];
+        assert_eq!(top_k_by_key(words.iter(), 1, |w| w.len()), [&"banana"]);
+        assert_eq!(bottom_k_by_key(words.iter(), 2, |w| w.len()), [&"fig", &"pear"]);
+        assert_eq!(bottom_k_by(values, 2, |a, b| b.cmp(a)), [59, 58]);
     }
 }
@@ -0,0 +1,550 @@
+//! Tracing the steps of a sort
+//!
+//! A `Trace` is an `Instrument` that records every operation a sort reports,
+//! in order, as a stream of `Event`s. Replaying the stream against the input
+//! shows how the elements move, and the renderers draw it in the box diagram
+//! style of the `insertion` and `merge` docs.
+//!
+//! Elements are followed by the index they started at, so nothing needs to be
+//! cloned while replaying.
+//!
+//! The events are grouped into steps. A step ends once every element copied
+//! out of the slice has been written back, such as when insertion sort puts
+//! the key into place or a merge has emptied both halves. A swap, or a write
+//! from one index of the slice to another outside of a step, is a step of its
+//! own.
+//!
+//! # Example
+//!
+//! ```
+//! use kb::sort::Sorter;
+//! use kb::sort::insertion::Clrs;
+//! use kb::sort::trace::Trace;
+//!
+//! let values = [3, 1, 2];
+//! let mut trace = Trace::new();
+//! Clrs.sort_instrumented(&mut values.clone(), &mut trace);
+//!
+//! assert_eq!(trace.events()[0].to_string(), "write key <- values[1]");
+//! println!("{}", trace.render_moves(&values));
+//! ```
+
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::{self, Display};
+use std::mem;
+
+use super::instrument::{Instrument, Slot};
+
+/// An operation performed by a sort
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Event {
+    Compare(Slot, Slot),
+    Swap(usize, usize),
+    /// An element is written to the first slot from the second
+    Write(Slot, Slot),
+    Allocate(usize),
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Event::Compare(a, b) => write!(f, "compare {} {}", a, b),
+            Event::Swap(i, j) => write!(f, "swap {} {}", Slot::Values(i), Slot::Values(j)),
+            Event::Write(to, from) => write!(f, "write {} <- {}", to, from),
+            Event::Allocate(len) => write!(f, "allocate {}", len),
+        }
+    }
+}
+
+/// Records the operations performed by a sort
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Trace {
+    events: Vec<Event>,
+}
+
+impl Instrument for Trace {
+    fn compare(&mut self, a: Slot, b: Slot) {
+        self.events.push(Event::Compare(a, b));
+    }
+
+    fn swap(&mut self, i: usize, j: usize) {
+        self.events.push(Event::Swap(i, j));
+    }
+
+    fn write(&mut self, to: Slot, from: Slot) {
+        self.events.push(Event::Write(to, from));
+    }
+
+    fn allocate(&mut self, len: usize) {
+        self.events.push(Event::Allocate(len));
+    }
+}
+
+impl Trace {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the events in the order they were reported
+    pub fn events(&self) -> &[Event] {
+        &self.events
+    }
+
+    /// Returns the index in the input of each element of the slice once the
+    /// sort is done
+    ///
+    /// Equal elements are left in the order they were in the input by a
+    /// stable sort, even when they can't be told apart.
+    pub fn origins(&self, len: usize) -> Vec<usize> {
+        self.steps(len).pop().map_or_else(|| (0..len).collect(), |step| step.after)
+    }
+
+    /// Draws the slice before each step, with arrows from where each element
+    /// written during the step came from to where it ended up, followed by the
+    /// sorted slice
+    ///
+    /// The walls between the elements are only drawn until the first element
+    /// has moved.
+    ///
+    /// `values` is the input the trace was recorded from.
+    pub fn render_moves<T: Display>(&self, values: &[T]) -> String {
+        if values.is_empty() { return String::new() }
+
+        let labels = labels(values);
+        let width = width(&labels);
+        let steps = self.steps(values.len());
+
+        let mut frames: Vec<String> = steps.iter().map(|step| {
+            let moves: Vec<(usize, usize)> = step.written.iter().map(|&k| (step.position(step.after[k]), k)).collect();
+            moves_frame(&labels, &step.before, width, &moves)
+        }).collect();
+
+        frames.push(moves_frame(&labels, &self.origins(values.len()), width, &[]));
+
+        frames.join("\n")
+    }
+
+    /// Draws the runs combined by each merge as a tree, from the sorted slice
+    /// at the top down to the single elements it was merged from
+    ///
+    /// Each merge is one level above the highest of the runs it combines.
+    /// Steps that don't copy elements out of the slice, such as swaps, are not
+    /// merges and are left out.
+    ///
+    /// `values` is the input the trace was recorded from.
+    pub fn render_merges<T: Display>(&self, values: &[T]) -> String {
+        if values.is_empty() { return String::new() }
+
+        let labels = labels(values);
+        let width = width(&labels);
+
+        let mut runs: Vec<Run> = (0..values.len()).map(|i| Run { start: i, end: i + 1, level: 0, origins: vec![i] }).collect();
+        let mut chains: Vec<Vec<usize>> = (0..values.len()).map(|i| vec![i]).collect();
+        for step in self.steps(values.len()).into_iter().filter(|step| step.buffered) {
+            let start = *step.written.iter().next().unwrap();
+            let end = *step.written.iter().next_back().unwrap() + 1;
+            let level = 1 + chains[start..end].iter().map(|chain| runs[*chain.last().unwrap()].level).max().unwrap();
+
+            for chain in &mut chains[start..end] {
+                chain.push(runs.len());
+            }
+            runs.push(Run { start, end, level, origins: step.after[start..end].to_vec() });
+        }
+
+        let levels = runs.iter().map(|run| run.level).max().unwrap();
+        let rows: Vec<Vec<&Run>> = (0..levels + 1).map(|level| {
+            let mut row: Vec<&Run> = Vec::new();
+            for chain in &chains {
+                let run = chain.iter().rev().map(|&r| &runs[r]).find(|run| run.level <= level).unwrap();
+                if row.last().is_none_or(|last| last.start != run.start || last.end != run.end) {
+                    row.push(run);
+                }
+            }
+
+            row
+        }).collect();
+
+        let cells: Vec<Vec<Vec<(usize, usize)>>> = (0..levels + 1).map(|level| {
+            let below: &[&Run] = if level == 0 { &[] } else { &rows[level - 1] };
+            rows[level].iter().map(|run| cells(run, below, width)).collect()
+        }).collect();
+
+        let mut lines: Vec<Vec<char>> = Vec::new();
+        for level in (0..levels + 1).rev() {
+            let row = merges_row(&labels, &rows[level], &cells[level], width - 2);
+            if level < levels {
+                // Connect each run to the run above it that it was merged into
+                let columns: Vec<usize> = rows[level].iter().zip(&cells[level]).map(|(run, below)| {
+                    let parent = rows[level + 1].iter().position(|parent| parent.start <= run.start && run.end <= parent.end).unwrap();
+                    connection(rows[level + 1][parent], &cells[level + 1][parent], run, below)
+                }).collect();
+                let len = row[0].len();
+                let mut connector = vec![' '; len];
+                for &column in &columns {
+                    let bottom = lines.len() - 1;
+                    lines[bottom][column] = '^';
+                    connector[column] = '|';
+                }
+
+                lines.push(connector.clone());
+                lines.push(connector);
+                lines.extend(row);
+
+                let top = lines.len() - 5;
+                for &column in &columns {
+                    lines[top][column] = '+';
+                }
+            } else {
+                lines.extend(row);
+            }
+        }
+
+        join(lines)
+    }
+
+    /// Replays the events on the indices of a slice of `len` elements, split
+    /// into steps
+    fn steps(&self, len: usize) -> Vec<Step> {
+        let mut origins: Vec<usize> = (0..len).collect();
+        let mut held: HashMap<Slot, usize> = HashMap::new();
+        let mut outstanding = 0usize;
+
+        let mut steps = Vec::new();
+        let mut step = Step::new(&origins);
+        for event in &self.events {
+            match *event {
+                Event::Swap(i, j) => {
+                    origins.swap(i, j);
+                    step.written.insert(i);
+                    step.written.insert(j);
+                }
+                Event::Write(to, from) => {
+                    let origin = match from {
+                        Slot::Values(i) => origins[i],
+                        slot => held[&slot],
+                    };
+
+                    match to {
+                        Slot::Values(k) => {
+                            origins[k] = origin;
+                            step.written.insert(k);
+                            if let Slot::Values(_) = from {} else { outstanding = outstanding.saturating_sub(1) }
+                        }
+                        slot => {
+                            held.insert(slot, origin);
+                            step.buffered = true;
+                            outstanding += 1;
+                        }
+                    }
+                }
+                Event::Compare(..) | Event::Allocate(..) => continue,
+            }
+
+            if outstanding == 0 {
+                step.after = origins.clone();
+                steps.push(mem::replace(&mut step, Step::new(&origins)));
+            }
+        }
+
+        steps
+    }
+}
+
+impl fmt::Display for Slot {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Slot::Values(i) => write!(f, "values[{}]", i),
+            Slot::Left(i) => write!(f, "left[{}]", i),
+            Slot::Right(i) => write!(f, "right[{}]", i),
+            Slot::Buffer(i) => write!(f, "buffer[{}]", i),
+            Slot::Key => write!(f, "key"),
+        }
+    }
+}
+
+/// The indices of the slice before and after a step, by the index each
+/// element started at
+struct Step {
+    before: Vec<usize>,
+    after: Vec<usize>,
+    written: BTreeSet<usize>,
+    /// Whether elements were copied out of the slice
+    buffered: bool,
+}
+
+impl Step {
+    fn new(origins: &[usize]) -> Self {
+        Step { before: origins.to_vec(), after: Vec::new(), written: BTreeSet::new(), buffered: false }
+    }
+
+    /// Returns where the element that started at `origin` was before the step
+    fn position(&self, origin: usize) -> usize {
+        self.before.iter().position(|&o| o == origin).unwrap()
+    }
+}
+
+/// A sorted run of the slice, and the level of the merge that produced it
+struct Run {
+    start: usize,
+    end: usize,
+    level: usize,
+    origins: Vec<usize>,
+}
+
+fn labels<T: Display>(values: &[T]) -> Vec<String> {
+    values.iter().map(ToString::to_string).collect()
+}
+
+/// Returns the width inside each box, leaving a space either side of the
+/// widest label
+fn width(labels: &[String]) -> usize {
+    labels.iter().map(|label| label.chars().count()).max().unwrap_or(0) + 2
+}
+
+/// Draws the slice as adjacent boxes, with the given moves underneath
+///
+/// Each arrow enters the left of the box it goes to, and leaves the right of
+/// the box it comes from, one column further in when it points back to an
+/// earlier box so it doesn't meet the head of an arrow leaving the next box.
+/// Shorter arrows are drawn closer to the boxes, and arrows only share a line
+/// when they don't overlap.
+///
+/// The walls between the elements are only drawn if no element has moved from
+/// where it started.
+fn moves_frame(labels: &[String], origins: &[usize], width: usize, moves: &[(usize, usize)]) -> String {
+    let stride = width + 1;
+    let len = origins.len() * stride + 1;
+    let separated = origins.iter().enumerate().all(|(i, &origin)| i == origin);
+
+    let border: Vec<char> = (0..len).map(|x| if x % stride == 0 { '+' } else { '-' }).collect();
+    let blank: Vec<char> = (0..len).map(|x| if x % stride == 0 { '|' } else { ' ' }).collect();
+    let mut value = String::from("|");
+    for (i, &origin) in origins.iter().enumerate() {
+        let wall = if separated || i + 1 == origins.len() { '|' } else { ' ' };
+        value.push_str(&format!(" {:>2$} {}", labels[origin], wall, width - 2));
+    }
+
+    let mut arrows: Vec<(usize, usize)> = moves.iter().map(|&(from, to)| {
+        let tail = if to < from { from * stride + width - 1 } else { from * stride + width };
+        (tail, to * stride + 1)
+    }).collect();
+    arrows.sort_by_key(|&(from, to)| from.abs_diff(to));
+
+    let mut lanes: Vec<Vec<(usize, usize)>> = Vec::new();
+    let mut placed = Vec::new();
+    for &(from, to) in &arrows {
+        let (lo, hi) = if from < to { (from, to) } else { (to, from) };
+        let lane = lanes.iter().position(|lane| lane.iter().all(|&(l, h)| hi + 1 < l || h + 1 < lo));
+        let lane = lane.unwrap_or_else(|| {
+            lanes.push(Vec::new());
+            lanes.len() - 1
+        });
+
+        lanes[lane].push((lo, hi));
+        placed.push((from, to, lo, hi, lane));
+    }
+
+    let mut lines = vec![border.clone(), blank.clone(), value.chars().collect(), blank, border];
+    if !placed.is_empty() {
+        lines.extend((0..lanes.len() + 1).map(|_| vec![' '; len]));
+    }
+
+    for &(_, _, lo, hi, lane) in &placed {
+        for c in &mut lines[6 + lane][lo..hi + 1] {
+            *c = '-';
+        }
+    }
+
+    for &(from, to, _, _, lane) in &placed {
+        lines[4][from] = '+';
+        lines[4][to] = '+';
+        lines[5][from] = '|';
+        lines[5][to] = '^';
+        for line in &mut lines[6..6 + lane] {
+            line[from] = '|';
+            line[to] = '|';
+        }
+        lines[6 + lane][from] = '+';
+        lines[6 + lane][to] = '+';
+    }
+
+    join(lines)
+}
+
+/// Returns the first and last column inside the box of each element of a run
+///
+/// The box of a run stretches over the boxes of the single elements it was
+/// merged from, which are three columns apart. Its elements share the room
+/// evenly, and any columns left over go to the elements at the ends of the
+/// runs below it, which sit over the gaps between them.
+fn cells(run: &Run, below: &[&Run], width: usize) -> Vec<(usize, usize)> {
+    let stride = width + 5;
+    let (left, right) = (run.start * stride, (run.end - 1) * stride + width + 1);
+    let len = run.end - run.start;
+    let room = right - left - len;
+
+    let mut ranked: Vec<usize> = (run.start..run.end).collect();
+    ranked.sort_by_key(|&i| {
+        let (start, end) = below.iter().find(|child| child.start <= i && i < child.end).map_or((run.start, run.end), |child| (child.start, child.end));
+        ((i - start).min(end - 1 - i), (i - run.start).min(run.end - 1 - i), i)
+    });
+    let wide = &ranked[..room % len];
+
+    let mut x = left + 1;
+    (run.start..run.end).map(|i| {
+        let cell = room / len + if wide.contains(&i) { 1 } else { 0 };
+        x += cell + 1;
+        (x - cell - 1, x - 2)
+    }).collect()
+}
+
+/// Draws each run as a box, with the label of each element right-aligned in
+/// a field of `field` columns in the middle of its cell
+fn merges_row(labels: &[String], runs: &[&Run], cells: &[Vec<(usize, usize)>], field: usize) -> Vec<Vec<char>> {
+    let len = cells.last().map_or(0, |cells| cells[cells.len() - 1].1 + 2);
+    let mut lines = vec![vec![' '; len]; 5];
+
+    for (run, cells) in runs.iter().zip(cells) {
+        let (left, right) = (cells[0].0 - 1, cells[cells.len() - 1].1 + 1);
+        for x in left..right + 1 {
+            let wall = cells.iter().all(|&(first, last)| x < first || last < x);
+            lines[0][x] = if wall { '+' } else { '-' };
+            lines[4][x] = lines[0][x];
+            for line in &mut lines[1..4] {
+                line[x] = if wall { '|' } else { ' ' };
+            }
+        }
+
+        for (&(first, last), &origin) in cells.iter().zip(&run.origins) {
+            let label = &labels[origin];
+            let x = first + (last + 1 - first - field).div_ceil(2) + field - label.chars().count();
+            for (c, ch) in lines[2][x..].iter_mut().zip(label.chars()) {
+                *c = ch;
+            }
+        }
+    }
+
+    lines
+}
+
+/// Returns the column of the arrow from a run up to the run it was merged
+/// into
+///
+/// The arrow leaves from the middle element of the run, leaning towards the
+/// middle of the parent, and enters the cell the element is in above it, as
+/// close to straight up as the cells allow.
+fn connection(parent: &Run, above: &[(usize, usize)], run: &Run, below: &[(usize, usize)]) -> usize {
+    let left = run.start + run.end <= parent.start + parent.end;
+    let len = run.end - run.start;
+    let i = if left { len / 2 } else { (len - 1) / 2 };
+
+    let (first, last) = below[i];
+    let (top_first, top_last) = above[run.start + i - parent.start];
+    let (lo, hi) = (first.max(top_first), last.min(top_last));
+    if lo > hi {
+        top_first + (top_last - top_first) / 2
+    } else if left {
+        lo + (hi - lo).div_ceil(2)
+    } else {
+        lo + (hi - lo) / 2
+    }
+}
+
+fn join(lines: Vec<Vec<char>>) -> String {
+    lines.into_iter().map(|line| {
+        let line: String = line.into_iter().collect();
+        format!("{}\n", line.trim_end())
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sort::{self, insertion, merge, selection, Sorter};
+
+    fn trace<S: Sorter<u32>>(sorter: S, values: &[u32]) -> Trace {
+        let mut trace = Trace::new();
+        sorter.sort_instrumented(&mut values.to_vec(), &mut trace);
+        trace
+    }
+
+    #[test]
+    fn test_events() {
+        let trace = trace(selection::Selection, &[2, 1]);
+        assert_eq!(trace.events(), [
+            Event::Compare(Slot::Values(1), Slot::Values(0)),
+            Event::Swap(0, 1),
+        ]);
+
+        let events: Vec<String> = self::trace(insertion::Clrs, &[2, 1]).events().iter().map(ToString::to_string).collect();
+        assert_eq!(events, [
+            "write key <- values[1]",
+            "compare values[0] key",
+            "write values[1] <- values[0]",
+            "write values[0] <- key",
+        ]);
+    }
+
+    #[test]
+    fn test_replay_all_sorters() {
+        // Replaying the events of every sort should end at the sorted slice
+        let input: Vec<u32> = (0..100).map(|i| (i * 7919) % 101).collect();
+        let mut sorted = input.clone();
+        sorted.sort();
+
+        for sorter in sort::all_sorters().into_iter().chain(sort::unsigned_sorters()) {
+            let mut trace = Trace::new();
+            sorter.sort_instrumented(&mut input.clone(), &mut trace);
+            let rendered = trace.render_moves(&input);
+            let last: Vec<&str> = rendered.lines().rev().nth(2).unwrap().split(['|', ' ']).filter(|label| !label.is_empty()).collect();
+            assert_eq!(last, labels(&sorted), "{}", sorter.name());
+        }
+    }
+
+    #[test]
+    fn test_render_moves() {
+        let rendered = trace(selection::Selection, &[2, 1]).render_moves(&[2, 1]);
+        assert_eq!(rendered, "\
++---+---+
+|   |   |
+| 2 | 1 |
+|   |   |
+++-++++-+
+ ^ | ^|
+ | +-+|
+ +----+
+
++---+---+
+|   |   |
+| 1   2 |
+|   |   |
++---+---+
+");
+    }
+
+    #[test]
+    fn test_render_merges() {
+        let rendered = trace(merge::ClrsMergeSort, &[3, 2, 1]).render_merges(&[3, 2, 1]);
+        assert_eq!(rendered, "\
++------+-----+------+
+|      |     |      |
+|   1  |  2  |   3  |
+|      |     |      |
++-^----+--^--+------+
+  |       |
+  |       |
++-+-+   +-+---+-----+
+|   |   |     |     |
+| 3 |   |  1  |  2  |
+|   |   |     |     |
++-^-+   +-^---+---^-+
+  |       |       |
+  |       |       |
++-+-+   +-+-+   +-+-+
+|   |   |   |   |   |
+| 3 |   | 2 |   | 1 |
+|   |   |   |   |   |
++---+   +---+   +---+
+");
+    }
+}
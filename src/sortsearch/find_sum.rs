@@ -20,7 +20,7 @@ where
 
     for value in set.iter() {
         let complement = *target - *value;
-        if let Some(_) = set.search(&complement) {
+        if set.search(&complement).is_some() {
             return true;
         }
     }
@@ -59,7 +59,7 @@ impl<T> VecSet<T> {
     }
 
     /// Produces an iterator to each value within the set
-    pub fn iter(&self) -> Iter<T> {
+    pub fn iter(&self) -> Iter<'_, T> {
         self.inner.iter()
     }
 
@@ -83,4 +83,16 @@ mod tests {
         assert!(find_sum(set.clone(), &10));
         assert!(!find_sum(set.clone(), &11));
     }
+
+    #[test]
+    fn test_find_sum_generated() {
+        use gen;
+
+        let values: Vec<i32> = gen::uniform(50, 1);
+        let set = VecSet::new(&values);
+        for target in 0..100 {
+            let expected = (0..values.len()).any(|i| (0..values.len()).any(|j| values[i] + values[j] == target));
+            assert_eq!(find_sum(set.clone(), &target), expected, "{}", target);
+        }
+    }
 }
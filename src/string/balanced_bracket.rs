@@ -49,7 +49,7 @@ impl From<Brackets> for String {
 /// ```
 pub fn is_balanced(brackets: Brackets) -> bool {
     let input: String = brackets.into();
-    if input.len() % 2 != 0 {
+    if !input.len().is_multiple_of(2) {
         return false;
     }
 
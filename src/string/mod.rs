@@ -1,3 +1,4 @@
 //! String handling
 
 pub mod balanced_bracket;
+pub mod search;
@@ -0,0 +1,252 @@
+//! Boyer-Moore and Horspool
+//!
+//! Both compare the pattern against the text from its last byte backwards.
+//! A mismatch near the end of the pattern often allows a shift of nearly
+//! the whole pattern, so long patterns skip most of the text without reading
+//! it.
+//!
+//! *Bad character rule*: after a mismatch against text byte _c_, the pattern
+//! can shift until its last _c_ lines up with it, or past it entirely if the
+//! pattern has no _c_.
+//!
+//! *Good suffix rule*: the suffix of the pattern that did match must match
+//! again after the shift, so the pattern can shift to the next place that
+//! suffix occurs in it, or where a prefix of the pattern matches the end of
+//! the suffix.
+//!
+//! Boyer-Moore shifts by whichever rule allows more. Horspool uses only the
+//! bad character rule, always for the text byte under the end of the
+//! pattern. It is simpler, with a tighter inner loop, and just as fast on
+//! typical text, but takes _O(nm)_ time on repetitive input such as a run of
+//! `a`s searched for `baaa`.
+//!
+//! # Complexity
+//!
+//! *Preprocessing*: _O(m + σ)_, where _σ = 256_ is the size of the alphabet.
+//!
+//! *Search*: _O(n/m)_ at best, and _O(nm)_ at worst. With the good suffix
+//! rule, Boyer-Moore is _O(n + m)_ when the pattern doesn't occur, but after
+//! each match it starts comparing from the end of the pattern again, so many
+//! overlapping matches, such as `aaa` in a run of `a`s, still take _O(nm)_.
+//! Galil's rule, which isn't implemented here, remembers what matched to
+//! avoid that.
+
+/// The index of the last occurrence of each byte in the pattern, if any
+fn last_occurrence(pattern: &[u8]) -> [Option<usize>; 256] {
+    let mut last = [None; 256];
+    for (i, &byte) in pattern.iter().enumerate() {
+        last[byte as usize] = Some(i);
+    }
+
+    last
+}
+
+/// Computes the good suffix shifts of a pattern
+///
+/// `shift[j]` is how far to shift after `pattern[j..]` matched and
+/// `pattern[j - 1]` didn't, and `shift[0]` how far to shift after a full
+/// match.
+///
+/// `border[i]` is where the widest border of `pattern[i..]` starts: the
+/// longest proper suffix of it that is also a prefix of it. Where a border
+/// can't be extended to the left, the byte before it in the pattern differs,
+/// so the pattern can shift to line it up instead. Any shift not set that
+/// way lines up the widest border of the whole pattern that fits.
+fn good_suffix(pattern: &[u8]) -> Vec<usize> {
+    let m = pattern.len();
+    let mut shift = vec![0; m + 1];
+    let mut border = vec![0; m + 1];
+
+    let (mut i, mut j) = (m, m + 1);
+    border[i] = j;
+    while i > 0 {
+        while j <= m && pattern[i - 1] != pattern[j - 1] {
+            if shift[j] == 0 { shift[j] = j - i }
+            j = border[j];
+        }
+
+        i -= 1;
+        j -= 1;
+        border[i] = j;
+    }
+
+    let mut j = border[0];
+    for (i, shift) in shift.iter_mut().enumerate() {
+        if *shift == 0 { *shift = j }
+        if i == j { j = border[j] }
+    }
+
+    shift
+}
+
+/// A pattern preprocessed for the Boyer-Moore search
+#[derive(Clone, Debug)]
+pub struct BoyerMoore {
+    pattern: Vec<u8>,
+    last: [Option<usize>; 256],
+    shift: Vec<usize>,
+}
+
+impl BoyerMoore {
+    pub fn new<P: AsRef<[u8]> + ?Sized>(pattern: &P) -> Self {
+        let pattern = pattern.as_ref().to_vec();
+        BoyerMoore { last: last_occurrence(&pattern), shift: good_suffix(&pattern), pattern }
+    }
+
+    /// Returns the offset of the first match in the text
+    pub fn find<T: AsRef<[u8]> + ?Sized>(&self, text: &T) -> Option<usize> {
+        self.find_iter(text).next()
+    }
+
+    /// Returns an iterator over the offset of every match in the text
+    pub fn find_iter<'a, T: AsRef<[u8]> + ?Sized>(&'a self, text: &'a T) -> Matches<'a> {
+        Matches { searcher: Searcher::BoyerMoore(self), text: text.as_ref(), offset: 0 }
+    }
+
+    /// Compares the pattern at an offset, returning how far to shift, and
+    /// whether it matched
+    fn step(&self, window: &[u8]) -> (usize, bool) {
+        let mut j = self.pattern.len();
+        while j > 0 && self.pattern[j - 1] == window[j - 1] {
+            j -= 1;
+        }
+
+        if j == 0 { return (self.shift[0], true) }
+
+        // The last occurrence of the mismatched byte, if it is before j - 1
+        let bad = match self.last[window[j - 1] as usize] {
+            Some(last) if last < j - 1 => j - 1 - last,
+            Some(_) => 1,
+            None => j,
+        };
+
+        (bad.max(self.shift[j]), false)
+    }
+}
+
+/// A pattern preprocessed for the Horspool search
+#[derive(Clone, Debug)]
+pub struct Horspool {
+    pattern: Vec<u8>,
+    /// How far to shift when each byte is under the end of the pattern
+    shift: [usize; 256],
+}
+
+impl Horspool {
+    pub fn new<P: AsRef<[u8]> + ?Sized>(pattern: &P) -> Self {
+        let pattern = pattern.as_ref().to_vec();
+        let m = pattern.len();
+
+        // The last byte is left out, or it would always shift by zero
+        let mut shift = [m.max(1); 256];
+        let last = last_occurrence(&pattern[..m.saturating_sub(1)]);
+        for (byte, last) in last.iter().enumerate() {
+            if let Some(last) = *last { shift[byte] = m - 1 - last }
+        }
+
+        Horspool { pattern, shift }
+    }
+
+    /// Returns the offset of the first match in the text
+    pub fn find<T: AsRef<[u8]> + ?Sized>(&self, text: &T) -> Option<usize> {
+        self.find_iter(text).next()
+    }
+
+    /// Returns an iterator over the offset of every match in the text
+    pub fn find_iter<'a, T: AsRef<[u8]> + ?Sized>(&'a self, text: &'a T) -> Matches<'a> {
+        Matches { searcher: Searcher::Horspool(self), text: text.as_ref(), offset: 0 }
+    }
+
+    fn step(&self, window: &[u8]) -> (usize, bool) {
+        let m = self.pattern.len();
+        (self.shift[window[m - 1] as usize], window == &self.pattern[..])
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Searcher<'a> {
+    BoyerMoore(&'a BoyerMoore),
+    Horspool(&'a Horspool),
+}
+
+/// An iterator over the matches of a `BoyerMoore` or `Horspool` pattern
+#[derive(Clone, Debug)]
+pub struct Matches<'a> {
+    searcher: Searcher<'a>,
+    text: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Iterator for Matches<'a> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        let pattern = match self.searcher {
+            Searcher::BoyerMoore(bm) => &bm.pattern,
+            Searcher::Horspool(horspool) => &horspool.pattern,
+        };
+
+        let m = pattern.len();
+        if m == 0 { return super::next_offset(&mut self.offset, self.text.len()) }
+
+        while self.offset + m <= self.text.len() {
+            let offset = self.offset;
+            let window = &self.text[offset..offset + m];
+            let (shift, matched) = match self.searcher {
+                Searcher::BoyerMoore(bm) => bm.step(window),
+                Searcher::Horspool(horspool) => horspool.step(window),
+            };
+
+            self.offset += shift;
+            if matched { return Some(offset) }
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_good_suffix() {
+        // After a full match of abab, ab lines up with its start
+        assert_eq!(good_suffix(b"abab")[0], 2);
+        assert_eq!(good_suffix(b"abcd"), [4, 4, 4, 4, 1]);
+        // A mismatch against a run means the text byte is not in it at all
+        assert_eq!(good_suffix(b"aaaa"), [1, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_horspool_shifts() {
+        let horspool = Horspool::new("abcab");
+        assert_eq!(horspool.shift[b'a' as usize], 1);
+        assert_eq!(horspool.shift[b'b' as usize], 3);
+        assert_eq!(horspool.shift[b'c' as usize], 2);
+        assert_eq!(horspool.shift[b'z' as usize], 5);
+    }
+
+    #[test]
+    fn test_skips_text() {
+        // A byte that isn't in the pattern lets it shift past entirely, so
+        // only every mth byte is read
+        let text = vec![b'x'; 1000];
+        let pattern = vec![b'y'; 10];
+        let bm = BoyerMoore::new(&pattern);
+        let (mut offset, mut steps) = (0, 0);
+        while offset + 10 <= text.len() {
+            offset += bm.step(&text[offset..offset + 10]).0;
+            steps += 1;
+        }
+
+        assert_eq!(steps, 100);
+    }
+
+    #[test]
+    fn test_find() {
+        assert_eq!(BoyerMoore::new("needle").find("haystack with a needle in it"), Some(16));
+        assert_eq!(Horspool::new("needle").find("haystack with a needle in it"), Some(16));
+        assert_eq!(BoyerMoore::new("baaa").find(&vec![b'a'; 50]), None);
+    }
+}
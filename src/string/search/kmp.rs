@@ -0,0 +1,141 @@
+//! Knuth-Morris-Pratt
+//!
+//! When a naive search fails partway through a match, it moves on by one
+//! offset and starts comparing from the beginning of the pattern again,
+//! re-reading text it has already seen. KMP never moves backwards in the
+//! text. The part of the pattern that matched is known, so the *prefix
+//! function* says how much of it still matches at the next offset that
+//! could work, and comparison carries on from there.
+//!
+//! # Complexity
+//!
+//! *Preprocessing*: _O(m)_ for the prefix function.
+//!
+//! *Search*: _O(n)_. Every comparison either advances through the text, or
+//! shortens the current partial match, which can only be as long as the
+//! distance already advanced.
+
+/// Computes the prefix function of a string
+///
+/// `prefix[i]` is the length of the longest proper prefix of `s[..=i]` that
+/// is also a suffix of it. For `abab`, that is `[0, 0, 1, 2]`.
+///
+/// # Loop invariant
+///
+/// At the start of each iteration, `k` is `prefix[i - 1]`, so `s[..k]` is the
+/// longest proper prefix that is also a suffix of `s[..i]`.
+///
+/// *Initialization*: To begin with, *i = 1*, and the only proper prefix of
+/// `s[..1]` is empty, so *k = 0*.
+///
+/// *Maintenance*: A border of `s[..=i]` is a border of `s[..i]` extended by
+/// `s[i]`. The borders of `s[..i]`, from longest to shortest, are `k`,
+/// `prefix[k - 1]`, and so on, so the loop tries each in turn until one can
+/// be extended, or none are left.
+///
+/// *Termination*: Every entry is computed. `k` increases by at most one per
+/// iteration and never goes below zero, so the inner loop runs at most _m_
+/// times in all.
+pub fn prefix_function(s: &[u8]) -> Vec<usize> {
+    let mut prefix = vec![0; s.len()];
+    let mut k = 0;
+    for i in 1..s.len() {
+        while k > 0 && s[i] != s[k] {
+            k = prefix[k - 1];
+        }
+
+        if s[i] == s[k] { k += 1 }
+        prefix[i] = k;
+    }
+
+    prefix
+}
+
+/// A pattern preprocessed for the Knuth-Morris-Pratt search
+#[derive(Clone, Debug)]
+pub struct Kmp {
+    pattern: Vec<u8>,
+    prefix: Vec<usize>,
+}
+
+impl Kmp {
+    pub fn new<P: AsRef<[u8]> + ?Sized>(pattern: &P) -> Self {
+        let pattern = pattern.as_ref().to_vec();
+        let prefix = prefix_function(&pattern);
+        Kmp { pattern, prefix }
+    }
+
+    /// The prefix function of the pattern
+    pub fn prefix(&self) -> &[usize] {
+        &self.prefix
+    }
+
+    /// Returns the offset of the first match in the text
+    pub fn find<T: AsRef<[u8]> + ?Sized>(&self, text: &T) -> Option<usize> {
+        self.find_iter(text).next()
+    }
+
+    /// Returns an iterator over the offset of every match in the text
+    pub fn find_iter<'a, T: AsRef<[u8]> + ?Sized>(&'a self, text: &'a T) -> Matches<'a> {
+        Matches { kmp: self, text: text.as_ref(), i: 0, matched: 0 }
+    }
+}
+
+/// An iterator over the matches of a `Kmp` pattern
+#[derive(Clone, Debug)]
+pub struct Matches<'a> {
+    kmp: &'a Kmp,
+    text: &'a [u8],
+    /// The next byte of the text to read
+    i: usize,
+    /// How much of the pattern matches the text just before `i`
+    matched: usize,
+}
+
+impl<'a> Iterator for Matches<'a> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        let (pattern, text) = (&self.kmp.pattern, self.text);
+        if pattern.is_empty() { return super::next_offset(&mut self.i, text.len()) }
+
+        while self.i < text.len() {
+            while self.matched > 0 && text[self.i] != pattern[self.matched] {
+                self.matched = self.kmp.prefix[self.matched - 1];
+            }
+
+            if text[self.i] == pattern[self.matched] { self.matched += 1 }
+            self.i += 1;
+
+            if self.matched == pattern.len() {
+                // Carry on from the longest border, so overlapping matches are found
+                self.matched = self.kmp.prefix[self.matched - 1];
+                return Some(self.i - pattern.len());
+            }
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prefix_function() {
+        assert_eq!(prefix_function(b""), []);
+        assert_eq!(prefix_function(b"abab"), [0, 0, 1, 2]);
+        assert_eq!(prefix_function(b"aabaaab"), [0, 1, 0, 1, 2, 2, 3]);
+        assert_eq!(prefix_function(b"abcd"), [0, 0, 0, 0]);
+        assert_eq!(Kmp::new("aaaa").prefix(), [0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_find() {
+        let kmp = Kmp::new("needle");
+        assert_eq!(kmp.find("haystack with a needle in it"), Some(16));
+        assert_eq!(kmp.find("haystack"), None);
+        assert_eq!(Kmp::new(&[1u8, 2][..]).find(&vec![0u8, 1, 2]), Some(1));
+    }
+}
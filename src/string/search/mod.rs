@@ -0,0 +1,120 @@
+//! Substring search
+//!
+//! Finding a pattern of length _m_ in a text of length _n_ by checking every
+//! offset takes _O(nm)_ time. Each of these algorithms preprocesses the
+//! pattern so the text can be scanned much faster.
+//!
+//! | Method | Preprocessing | Search | Notes |
+//! | --- | --- | --- | --- |
+//! | Knuth-Morris-Pratt | _O(m)_ | _O(n)_ | Never looks back in the text |
+//! | Boyer-Moore | _O(m + σ)_ | _O(nm)_, _O(n/m)_ at best | Compares from the end of the pattern |
+//! | Horspool | _O(m + σ)_ | _O(nm)_, _O(n/m)_ at best | Boyer-Moore without the good suffix rule |
+//! | Rabin-Karp | _O(m)_ | _O(n)_ expected | Rolling hashes, also for many patterns |
+//! | Z-algorithm | _O(m)_ | _O(n)_ | Longest prefix of the pattern at each offset |
+//!
+//! Here _σ_ is the size of the alphabet, which is 256 for bytes.
+//!
+//! Every searcher works on bytes, and takes anything that is `AsRef<[u8]>`,
+//! including `&str`. A match of a valid UTF-8 pattern always starts and ends
+//! on character boundaries, so offsets into a `&str` can be used to slice it.
+//!
+//! Searches return an iterator of the offsets where the pattern starts, in
+//! order. Unlike `str::match_indices`, matches may overlap. An empty pattern
+//! matches at every offset, including the end of the text.
+//!
+//! ```
+//! use kb::string::search::{BoyerMoore, Horspool, Kmp, RabinKarp, Z};
+//!
+//! let (text, pattern) = ("abracadabra", "abra");
+//! assert_eq!(Kmp::new(pattern).find_iter(text).collect::<Vec<_>>(), [0, 7]);
+//! assert_eq!(BoyerMoore::new(pattern).find_iter(text).collect::<Vec<_>>(), [0, 7]);
+//! assert_eq!(Horspool::new(pattern).find_iter(text).collect::<Vec<_>>(), [0, 7]);
+//! assert_eq!(RabinKarp::new(pattern).find_iter(text).collect::<Vec<_>>(), [0, 7]);
+//! assert_eq!(Z::new(pattern).find_iter(text).collect::<Vec<_>>(), [0, 7]);
+//!
+//! // Overlapping matches are all found
+//! assert_eq!(Kmp::new("aa").find_iter("aaaa").count(), 3);
+//! ```
+
+pub mod boyer_moore;
+pub mod kmp;
+pub mod rabin_karp;
+pub mod z;
+
+pub use self::boyer_moore::{BoyerMoore, Horspool};
+pub use self::kmp::Kmp;
+pub use self::rabin_karp::{RabinKarp, RabinKarpSet};
+pub use self::z::Z;
+
+/// Returns the next offset for an empty pattern, which matches everywhere
+fn next_offset(offset: &mut usize, len: usize) -> Option<usize> {
+    if *offset > len { return None }
+
+    *offset += 1;
+    Some(*offset - 1)
+}
+
+/// Finds every match by checking every offset, to test the others against
+#[cfg(test)]
+fn naive(text: &[u8], pattern: &[u8]) -> Vec<usize> {
+    if pattern.len() > text.len() { return Vec::new() }
+    (0..=text.len() - pattern.len()).filter(|&i| text[i..].starts_with(pattern)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gen;
+
+    /// Runs every searcher on a text and pattern, checking them against the
+    /// naive search
+    fn check(text: &[u8], pattern: &[u8]) {
+        let expected = naive(text, pattern);
+        let found: Vec<(&str, Vec<usize>)> = vec![
+            ("KMP", Kmp::new(pattern).find_iter(text).collect()),
+            ("Boyer-Moore", BoyerMoore::new(pattern).find_iter(text).collect()),
+            ("Horspool", Horspool::new(pattern).find_iter(text).collect()),
+            ("Rabin-Karp", RabinKarp::new(pattern).find_iter(text).collect()),
+            ("Z", Z::new(pattern).find_iter(text).collect()),
+        ];
+
+        for (name, found) in found {
+            assert_eq!(found, expected, "{} for {:?} in {:?}", name, pattern, text);
+        }
+    }
+
+    #[test]
+    fn test_all_searchers() {
+        check(b"", b"");
+        check(b"abc", b"");
+        check(b"", b"a");
+        check(b"ab", b"abc");
+        check(b"aaaaa", b"aa");
+        check(b"abababab", b"abab");
+        check(b"here is a simple example", b"example");
+        check(b"GCATCGCAGAGAGTATACAGTACG", b"GCAGAGAG");
+        check(&[0, 255, 0, 255, 0], &[255, 0]);
+    }
+
+    #[test]
+    fn test_generated() {
+        // Small alphabets make many partial matches
+        for seed in 0..40 {
+            let text: Vec<u8> = gen::uniform::<u8>(200, seed).iter().map(|&value| value % 3).collect();
+            for len in 1..8 {
+                let start = (seed as usize * 7) % (200 - len);
+                check(&text, &text[start..start + len]);
+                check(&text, &vec![1; len]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_str() {
+        let text = "naïve café, naïve cafés";
+        let offsets: Vec<usize> = Kmp::new("café").find_iter(text).collect();
+        assert_eq!(offsets, [7, 21]);
+        assert!(offsets.iter().all(|&i| text[i..].starts_with("café")));
+        assert_eq!(text.find("café"), Some(offsets[0]));
+    }
+}
@@ -0,0 +1,237 @@
+//! Rabin-Karp
+//!
+//! Compares a hash of the pattern with a hash of each window of the text. The
+//! hash is a polynomial in the bytes, modulo a prime, so moving the window
+//! along by one byte takes out the first byte and adds the next in constant
+//! time. That is a *rolling hash*. Only windows whose hash matches are
+//! compared byte by byte, so a false match costs time but is never reported.
+//!
+//! Hashes make it easy to look for many patterns at once: `RabinKarpSet`
+//! hashes each window once per distinct pattern length, and looks the hash
+//! up in a table of patterns.
+//!
+//! # Complexity
+//!
+//! *Preprocessing*: _O(m)_, or the total length of the patterns.
+//!
+//! *Search*: _O(n)_ expected, for each distinct pattern length. A hash
+//! matches by chance with probability about _1/p_, so verification is rare
+//! unless there are many true matches. _O(nm)_ at worst.
+
+use std::collections::HashMap;
+
+/// The base of the polynomial, one more than the largest byte
+const BASE: u64 = 256;
+
+/// The modulus, a prime small enough that `BASE * MODULUS` fits in a `u64`
+const MODULUS: u64 = 1_000_000_007;
+
+/// The hash of a string, _Σ s[i]·BASE^(m - 1 - i)_ modulo `MODULUS`
+fn hash(s: &[u8]) -> u64 {
+    s.iter().fold(0, |hash, &byte| (hash * BASE + u64::from(byte)) % MODULUS)
+}
+
+/// A rolling hash over windows of a fixed length
+#[derive(Clone, Debug)]
+struct Rolling {
+    len: usize,
+    /// _BASE^(len - 1)_, the weight of the byte leaving the window
+    high: u64,
+    hash: u64,
+}
+
+impl Rolling {
+    /// Starts with the hash of the first window of the text
+    fn new(text: &[u8], len: usize) -> Self {
+        let high = (1..len).fold(1, |high, _| high * BASE % MODULUS);
+        Rolling { len, high, hash: hash(&text[..len.min(text.len())]) }
+    }
+
+    /// Moves the window from `text[i..i + len]` to `text[i + 1..i + len + 1]`
+    fn roll(&mut self, text: &[u8], i: usize) {
+        if self.len == 0 || i + self.len >= text.len() { return }
+
+        let out = u64::from(text[i]) * self.high % MODULUS;
+        self.hash = ((self.hash + MODULUS - out) * BASE + u64::from(text[i + self.len])) % MODULUS;
+    }
+}
+
+/// A pattern preprocessed for the Rabin-Karp search
+#[derive(Clone, Debug)]
+pub struct RabinKarp {
+    pattern: Vec<u8>,
+    hash: u64,
+}
+
+impl RabinKarp {
+    pub fn new<P: AsRef<[u8]> + ?Sized>(pattern: &P) -> Self {
+        let pattern = pattern.as_ref().to_vec();
+        RabinKarp { hash: hash(&pattern), pattern }
+    }
+
+    /// Returns the offset of the first match in the text
+    pub fn find<T: AsRef<[u8]> + ?Sized>(&self, text: &T) -> Option<usize> {
+        self.find_iter(text).next()
+    }
+
+    /// Returns an iterator over the offset of every match in the text
+    pub fn find_iter<'a, T: AsRef<[u8]> + ?Sized>(&'a self, text: &'a T) -> Matches<'a> {
+        let text = text.as_ref();
+        Matches { rk: self, text, rolling: Rolling::new(text, self.pattern.len()), offset: 0 }
+    }
+}
+
+/// An iterator over the matches of a `RabinKarp` pattern
+#[derive(Clone, Debug)]
+pub struct Matches<'a> {
+    rk: &'a RabinKarp,
+    text: &'a [u8],
+    rolling: Rolling,
+    offset: usize,
+}
+
+impl<'a> Iterator for Matches<'a> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        let m = self.rk.pattern.len();
+        while self.offset + m <= self.text.len() {
+            let i = self.offset;
+            let found = self.rolling.hash == self.rk.hash && self.text[i..i + m] == self.rk.pattern[..];
+            self.rolling.roll(self.text, i);
+            self.offset += 1;
+
+            if found { return Some(i) }
+        }
+
+        None
+    }
+}
+
+/// Many patterns preprocessed for a Rabin-Karp search for all of them at once
+#[derive(Clone, Debug)]
+pub struct RabinKarpSet {
+    patterns: Vec<Vec<u8>>,
+    /// For each distinct length, the patterns of that length by hash
+    lengths: Vec<(usize, HashMap<u64, Vec<usize>>)>,
+}
+
+impl RabinKarpSet {
+    pub fn new<P: AsRef<[u8]>>(patterns: &[P]) -> Self {
+        let patterns: Vec<Vec<u8>> = patterns.iter().map(|pattern| pattern.as_ref().to_vec()).collect();
+
+        let mut lengths: Vec<(usize, HashMap<u64, Vec<usize>>)> = Vec::new();
+        for (index, pattern) in patterns.iter().enumerate() {
+            let len = pattern.len();
+            let group = match lengths.iter().position(|&(l, _)| l == len) {
+                Some(group) => group,
+                None => {
+                    lengths.push((len, HashMap::new()));
+                    lengths.len() - 1
+                }
+            };
+
+            lengths[group].1.entry(hash(pattern)).or_default().push(index);
+        }
+
+        RabinKarpSet { patterns, lengths }
+    }
+
+    /// Returns an iterator over every match in the text, as the offset and
+    /// the index of the pattern, ordered by offset then index
+    pub fn find_iter<'a, T: AsRef<[u8]> + ?Sized>(&'a self, text: &'a T) -> SetMatches<'a> {
+        let text = text.as_ref();
+        let rolling = self.lengths.iter().map(|&(len, _)| Rolling::new(text, len)).collect();
+        SetMatches { set: self, text, rolling, offset: 0, pending: Vec::new() }
+    }
+}
+
+/// An iterator over the matches of a `RabinKarpSet`
+#[derive(Clone, Debug)]
+pub struct SetMatches<'a> {
+    set: &'a RabinKarpSet,
+    text: &'a [u8],
+    /// A rolling hash for each distinct length
+    rolling: Vec<Rolling>,
+    offset: usize,
+    /// Matches found at the last offset but not yet returned, last first
+    pending: Vec<(usize, usize)>,
+}
+
+impl<'a> Iterator for SetMatches<'a> {
+    type Item = (usize, usize);
+
+    fn next(&mut self) -> Option<(usize, usize)> {
+        while self.pending.is_empty() && self.offset <= self.text.len() {
+            let i = self.offset;
+            for (rolling, (len, table)) in self.rolling.iter_mut().zip(&self.set.lengths) {
+                if i + len > self.text.len() { continue }
+
+                if let Some(indices) = table.get(&rolling.hash) {
+                    let window = &self.text[i..i + len];
+                    for &index in indices {
+                        if self.set.patterns[index][..] == *window { self.pending.push((i, index)) }
+                    }
+                }
+
+                rolling.roll(self.text, i);
+            }
+
+            self.pending.sort_by(|a, b| b.cmp(a));
+            self.offset += 1;
+        }
+
+        self.pending.pop()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rolling_hash() {
+        let text = b"the quick brown fox";
+        let mut rolling = Rolling::new(text, 5);
+        for i in 0..=text.len() - 5 {
+            assert_eq!(rolling.hash, hash(&text[i..i + 5]), "{}", i);
+            rolling.roll(text, i);
+        }
+    }
+
+    #[test]
+    fn test_find() {
+        assert_eq!(RabinKarp::new("needle").find("haystack with a needle in it"), Some(16));
+        assert_eq!(RabinKarp::new("needle").find("haystack"), None);
+    }
+
+    #[test]
+    fn test_set() {
+        let set = RabinKarpSet::new(&["he", "she", "his", "hers"]);
+        let matches: Vec<(usize, usize)> = set.find_iter("ushers").collect();
+        assert_eq!(matches, [(1, 1), (2, 0), (2, 3)]);
+
+        let set = RabinKarpSet::new(&["", "a", "a"]);
+        let matches: Vec<(usize, usize)> = set.find_iter("aa").collect();
+        assert_eq!(matches, [(0, 0), (0, 1), (0, 2), (1, 0), (1, 1), (1, 2), (2, 0)]);
+
+        assert_eq!(RabinKarpSet::new::<&str>(&[]).find_iter("abc").count(), 0);
+    }
+
+    #[test]
+    fn test_set_generated() {
+        use gen;
+
+        let text: Vec<u8> = gen::uniform::<u8>(500, 3).iter().map(|&value| b'a' + value % 4).collect();
+        let patterns: Vec<&[u8]> = vec![&text[10..13], &text[50..55], &text[7..9], b"abcd", &text[100..103]];
+
+        let mut expected = Vec::new();
+        for i in 0..=text.len() {
+            for (index, pattern) in patterns.iter().enumerate() {
+                if text[i..].starts_with(pattern) { expected.push((i, index)) }
+            }
+        }
+
+        assert_eq!(RabinKarpSet::new(&patterns).find_iter(&text).collect::<Vec<_>>(), expected);
+    }
+}
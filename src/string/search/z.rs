@@ -0,0 +1,166 @@
+//! The Z-algorithm
+//!
+//! The *Z-function* of a string gives, for each offset, the length of the
+//! longest common prefix of the string and the suffix starting there. The
+//! algorithm keeps the *Z-box*, the matching window that reaches furthest
+//! right. Inside the box, the string repeats its own prefix, so an offset's
+//! value can start from the one already computed for the same position in
+//! the prefix, and only the part past the box needs comparing.
+//!
+//! Searching computes the same values for each offset of the text against
+//! the pattern, and reports the offsets whose value is the whole pattern.
+//!
+//! # Complexity
+//!
+//! *Preprocessing*: _O(m)_ for the Z-function of the pattern.
+//!
+//! *Search*: _O(n)_. Each comparison that succeeds moves the end of the box
+//! right, and each offset has at most one that fails.
+
+/// Computes the Z-function of a string
+///
+/// `z[i]` is the length of the longest common prefix of `s` and `s[i..]`, and
+/// `z[0]` is the length of `s`. For `aabxaab`, that is `[7, 1, 0, 0, 3, 1, 0]`.
+///
+/// # Loop invariant
+///
+/// At the start of each iteration, `s[l..r]` is the Z-box: of the windows
+/// `s[j..j + z[j]]` for _0 < j < i_, the one that ends furthest right, which
+/// equals `s[..r - l]`.
+///
+/// *Initialization*: To begin with, no windows have been computed, and the
+/// box is empty.
+///
+/// *Maintenance*: If `i` is inside the box, `s[i..r]` equals
+/// `s[i - l..r - l]`, so `z[i]` is at least `z[i - l]`, as far as the box
+/// goes. Comparison carries on from there, and if the window reaches past
+/// `r`, it becomes the new box.
+///
+/// *Termination*: Every entry is computed. `r` never moves left, so
+/// comparisons that succeed take _O(m)_ in all.
+pub fn z_function(s: &[u8]) -> Vec<usize> {
+    let n = s.len();
+    let mut z = vec![0; n];
+    if n == 0 { return z }
+
+    z[0] = n;
+    let (mut l, mut r) = (0, 0);
+    for i in 1..n {
+        let mut k = if i < r { z[i - l].min(r - i) } else { 0 };
+        while i + k < n && s[k] == s[i + k] {
+            k += 1;
+        }
+
+        z[i] = k;
+        if i + k > r {
+            l = i;
+            r = i + k;
+        }
+    }
+
+    z
+}
+
+/// A pattern preprocessed for the Z-algorithm search
+#[derive(Clone, Debug)]
+pub struct Z {
+    pattern: Vec<u8>,
+    z: Vec<usize>,
+}
+
+impl Z {
+    pub fn new<P: AsRef<[u8]> + ?Sized>(pattern: &P) -> Self {
+        let pattern = pattern.as_ref().to_vec();
+        let z = z_function(&pattern);
+        Z { pattern, z }
+    }
+
+    /// The Z-function of the pattern
+    pub fn z(&self) -> &[usize] {
+        &self.z
+    }
+
+    /// Returns the offset of the first match in the text
+    pub fn find<T: AsRef<[u8]> + ?Sized>(&self, text: &T) -> Option<usize> {
+        self.find_iter(text).next()
+    }
+
+    /// Returns an iterator over the offset of every match in the text
+    pub fn find_iter<'a, T: AsRef<[u8]> + ?Sized>(&'a self, text: &'a T) -> Matches<'a> {
+        Matches { z: self, text: text.as_ref(), i: 0, l: 0, r: 0 }
+    }
+}
+
+/// An iterator over the matches of a `Z` pattern
+///
+/// The box is `text[l..r]`, which equals `pattern[..r - l]`. It is never
+/// longer than the pattern, so for an offset `i` inside it, `i - l` is an
+/// offset into the pattern. The pattern's own Z-function stops at its end,
+/// but can only reach it if it reaches the end of the box too, in which case
+/// comparison carries on past the box anyway.
+#[derive(Clone, Debug)]
+pub struct Matches<'a> {
+    z: &'a Z,
+    text: &'a [u8],
+    i: usize,
+    l: usize,
+    r: usize,
+}
+
+impl<'a> Iterator for Matches<'a> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        let (pattern, text) = (&self.z.pattern, self.text);
+        let m = pattern.len();
+        if m == 0 { return super::next_offset(&mut self.i, text.len()) }
+
+        while self.i + m <= text.len() {
+            let i = self.i;
+            let mut k = if i < self.r { self.z.z[i - self.l].min(self.r - i) } else { 0 };
+            while k < m && pattern[k] == text[i + k] {
+                k += 1;
+            }
+
+            if i + k > self.r {
+                self.l = i;
+                self.r = i + k;
+            }
+
+            self.i += 1;
+            if k == m { return Some(i) }
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_z_function() {
+        assert_eq!(z_function(b""), []);
+        assert_eq!(z_function(b"aabxaab"), [7, 1, 0, 0, 3, 1, 0]);
+        assert_eq!(z_function(b"aaaaa"), [5, 4, 3, 2, 1]);
+        assert_eq!(Z::new("abacaba").z(), [7, 0, 1, 0, 3, 0, 1]);
+    }
+
+    #[test]
+    fn test_z_function_naive() {
+        use gen;
+
+        for seed in 0..20 {
+            let s: Vec<u8> = gen::uniform::<u8>(60, seed).iter().map(|&value| value % 2).collect();
+            let naive: Vec<usize> = (0..s.len()).map(|i| s[i..].iter().zip(&s).take_while(|(a, b)| a == b).count()).collect();
+            assert_eq!(z_function(&s), naive);
+        }
+    }
+
+    #[test]
+    fn test_find() {
+        assert_eq!(Z::new("needle").find("haystack with a needle in it"), Some(16));
+        assert_eq!(Z::new("needle").find("haystack"), None);
+    }
+}
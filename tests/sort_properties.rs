@@ -0,0 +1,440 @@
+//! Property tests for every sort
+//!
+//! Each sort is run on inputs from several distributions and of many lengths,
+//! and its output is checked to be:
+//!
+//! - sorted,
+//! - a permutation of its input,
+//! - in agreement with `slice::sort`, exactly so for stable sorts, which
+//!   must also keep equal elements in their original order,
+//! - unchanged by sorting it again.
+//!
+//! Sorts that work through a trait bound other than `Ord`, such as radix sort
+//! on strings or bucket sort on floats, are run on keys mapped into their
+//! domain in a way that preserves order, and mapped back afterwards.
+//!
+//! When a property fails, the input is shrunk to a minimal counterexample by
+//! removing elements and making keys smaller for as long as it still fails.
+//! The inputs come from `kb::gen` with a fixed seed, so any failure is
+//! reproducible.
+
+extern crate kb;
+
+use std::cmp::Ordering;
+use std::fmt::Debug;
+use std::io::{self, BufRead, Write};
+
+use kb::gen;
+use kb::sort::bucket::BucketSort;
+use kb::sort::external::{Codec, ExternalSort};
+use kb::sort::network::Network;
+use kb::sort::trace::Trace;
+use kb::sort::{self, merge, partial, radix, Sorter};
+
+const SEED: u64 = 0x6b62_5eed;
+
+/// An element that is ordered by its key alone, and remembers its position in
+/// the input so stability can be checked
+#[derive(Clone, Copy, Debug)]
+struct Keyed {
+    key: u8,
+    index: usize,
+}
+
+impl PartialEq for Keyed {
+    fn eq(&self, other: &Keyed) -> bool {
+        self.key == other.key
+    }
+}
+
+impl Eq for Keyed {}
+
+impl PartialOrd for Keyed {
+    fn partial_cmp(&self, other: &Keyed) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Keyed {
+    fn cmp(&self, other: &Keyed) -> Ordering {
+        self.key.cmp(&other.key)
+    }
+}
+
+/// An element a sort can be checked on
+trait Record: Clone + Debug + Ord {
+    fn new(key: u8, index: usize) -> Self;
+
+    /// Everything about the element, including what `Ord` ignores
+    fn identity(&self) -> (u8, usize);
+}
+
+impl Record for u8 {
+    fn new(key: u8, _: usize) -> Self {
+        key
+    }
+
+    fn identity(&self) -> (u8, usize) {
+        (*self, 0)
+    }
+}
+
+impl Record for Keyed {
+    fn new(key: u8, index: usize) -> Self {
+        Keyed { key, index }
+    }
+
+    fn identity(&self) -> (u8, usize) {
+        (self.key, self.index)
+    }
+}
+
+/// A key mapped to a string, which remembers the position of its record in
+/// the input
+///
+/// The radix sorts only look at the bytes of the string, so the position is
+/// carried along without affecting the order.
+#[derive(Clone, Debug)]
+struct Tagged {
+    string: String,
+    index: usize,
+}
+
+impl AsRef<[u8]> for Tagged {
+    fn as_ref(&self) -> &[u8] {
+        self.string.as_bytes()
+    }
+}
+
+/// Keyed records for the external sort, as the key followed by the position
+/// in eight little-endian bytes
+struct KeyedCodec;
+
+impl Codec for KeyedCodec {
+    type Record = Keyed;
+
+    fn encode<W: Write>(&self, record: &Keyed, writer: &mut W) -> io::Result<()> {
+        writer.write_all(&[record.key])?;
+        writer.write_all(&(record.index as u64).to_le_bytes())
+    }
+
+    fn decode<R: BufRead>(&self, reader: &mut R) -> io::Result<Option<Keyed>> {
+        if reader.fill_buf()?.is_empty() { return Ok(None) }
+
+        let mut bytes = [0; 9];
+        reader.read_exact(&mut bytes)?;
+        let mut index = [0; 8];
+        index.copy_from_slice(&bytes[1..]);
+        Ok(Some(Keyed { key: bytes[0], index: u64::from_le_bytes(index) as usize }))
+    }
+}
+
+/// Sorts a slice in place
+type SortFn<T> = Box<dyn Fn(&mut [T])>;
+
+/// A sort under test
+struct Subject<T> {
+    name: String,
+    stable: bool,
+    /// The longest input the sort accepts
+    max_len: usize,
+    sort: SortFn<T>,
+}
+
+impl<T> Subject<T> {
+    fn new<F>(name: &str, stable: bool, sort: F) -> Self
+    where
+        F: Fn(&mut [T]) + 'static
+    {
+        Subject { name: name.to_string(), stable, max_len: usize::MAX, sort: Box::new(sort) }
+    }
+
+    fn max_len(mut self, max_len: usize) -> Self {
+        self.max_len = max_len;
+        self
+    }
+}
+
+/// Builds the records for a list of keys
+fn records<T: Record>(keys: &[u8]) -> Vec<T> {
+    keys.iter().enumerate().map(|(i, &key)| T::new(key, i)).collect()
+}
+
+fn identities<T: Record>(values: &[T]) -> Vec<(u8, usize)> {
+    values.iter().map(Record::identity).collect()
+}
+
+/// Checks every property of a sort on one input
+fn check<T: Record>(subject: &Subject<T>, keys: &[u8]) -> Result<(), String> {
+    let input: Vec<T> = records(keys);
+    let mut output = input.clone();
+    (subject.sort)(&mut output);
+
+    if let Some(i) = (1..output.len()).find(|&i| output[i - 1] > output[i]) {
+        return Err(format!("not sorted at {}: {:?}", i, output));
+    }
+
+    let (mut before, mut after) = (identities(&input), identities(&output));
+    before.sort();
+    after.sort();
+    if before != after {
+        return Err(format!("not a permutation of the input: {:?}", output));
+    }
+
+    let mut expected = input.clone();
+    expected.sort();
+    if subject.stable {
+        if let Some(i) = (1..output.len()).find(|&i| output[i - 1] == output[i] && output[i - 1].identity() > output[i].identity()) {
+            return Err(format!("not stable at {}: {:?}", i, output));
+        }
+
+        if identities(&output) != identities(&expected) {
+            return Err(format!("disagrees with slice::sort: {:?}", output));
+        }
+    } else if output != expected {
+        return Err(format!("disagrees with slice::sort: {:?}", output));
+    }
+
+    let mut again = output.clone();
+    (subject.sort)(&mut again);
+    let unchanged = if subject.stable { identities(&again) == identities(&output) } else { again == output };
+    if !unchanged {
+        return Err(format!("changed by sorting again: {:?}", again));
+    }
+
+    Ok(())
+}
+
+/// Shrinks a failing input to one where removing any element, or making any
+/// key smaller, makes it pass
+fn shrink<F>(mut keys: Vec<u8>, fails: F) -> Vec<u8>
+where
+    F: Fn(&[u8]) -> bool
+{
+    loop {
+        let mut progress = false;
+
+        // Remove chunks, starting with halves and finishing with single elements
+        let mut chunk = keys.len() / 2;
+        while chunk > 0 {
+            let mut start = 0;
+            while start + chunk <= keys.len() {
+                let mut candidate = keys.clone();
+                candidate.drain(start..start + chunk);
+                if fails(&candidate) {
+                    keys = candidate;
+                    progress = true;
+                } else {
+                    start += chunk;
+                }
+            }
+            chunk /= 2;
+        }
+
+        for i in 0..keys.len() {
+            for smaller in [0, keys[i] / 2, keys[i].saturating_sub(1)] {
+                if smaller >= keys[i] { continue }
+
+                let mut candidate = keys.clone();
+                candidate[i] = smaller;
+                if fails(&candidate) {
+                    keys = candidate;
+                    progress = true;
+                    break;
+                }
+            }
+        }
+
+        if !progress { return keys }
+    }
+}
+
+/// Generates inputs of a given length
+fn distributions(len: usize, seed: u64) -> Vec<(&'static str, Vec<u8>)> {
+    vec![
+        ("uniform", gen::uniform(len, seed)),
+        ("sorted", gen::sorted(len)),
+        ("reversed", gen::reversed(len)),
+        ("nearly sorted", gen::nearly_sorted(len, 2, seed)),
+        // A few unique keys make plenty of duplicates
+        ("few unique", gen::few_unique(len, 4, seed)),
+        ("all equal", gen::few_unique(len, 1, seed)),
+        ("sawtooth", gen::sawtooth(len, 5)),
+        ("organ pipe", gen::organ_pipe(len)),
+        ("zipf", gen::zipf(len, 1.0, seed)),
+        ("median of three killer", gen::median_of_three_killer(len)),
+    ]
+}
+
+/// Runs a sort on every distribution at many lengths, panicking with a
+/// minimal counterexample if any property fails
+fn run<T: Record>(subject: &Subject<T>) {
+    let lens = (0..=20).chain(vec![31, 64, 100, 257]).filter(|&len| len <= subject.max_len);
+
+    for len in lens {
+        for (distribution, keys) in distributions(len, SEED + len as u64) {
+            if let Err(err) = check(subject, &keys) {
+                let minimal = shrink(keys.clone(), |keys| check(subject, keys).is_err());
+                panic!(
+                    "{} failed on {} input of length {}: {}\nminimal counterexample: {:?}\n{}",
+                    subject.name,
+                    distribution,
+                    len,
+                    err,
+                    minimal,
+                    check(subject, &minimal).unwrap_err(),
+                );
+            }
+        }
+    }
+}
+
+/// Maps a key to a string, so that the order of strings is the order of keys
+///
+/// Each 26 is an extra `z`, and the remainder is the final letter. Lengths
+/// vary, and some strings are prefixes of others.
+fn to_string(key: u8) -> String {
+    let mut string = "z".repeat(key as usize / 26);
+    string.push((b'a' + key % 26) as char);
+    string
+}
+
+fn from_string(string: &str) -> u8 {
+    let last = string.as_bytes()[string.len() - 1];
+    ((string.len() - 1) * 26) as u8 + (last - b'a')
+}
+
+#[test]
+fn test_all_sorters() {
+    for sorter in sort::all_sorters::<Keyed>() {
+        let stable = sorter.is_stable();
+        run(&Subject::new(sorter.name(), stable, move |values| sorter.sort(values)));
+    }
+}
+
+#[test]
+fn test_unsigned_sorters() {
+    // These sorts only take integers, so the keys are sorted on their own and
+    // a trace of the sort shows which record each of them came from
+    for sorter in sort::unsigned_sorters::<u8>() {
+        let stable = sorter.is_stable();
+        run(&Subject::new(sorter.name(), stable, move |values: &mut [Keyed]| {
+            let mut keys: Vec<u8> = values.iter().map(|value| value.key).collect();
+            let mut trace = Trace::new();
+            sorter.sort_instrumented(&mut keys, &mut trace);
+
+            let sorted: Vec<Keyed> = trace.origins(values.len()).into_iter().map(|i| values[i]).collect();
+            values.copy_from_slice(&sorted);
+        }));
+    }
+}
+
+#[test]
+fn test_string_sorters() {
+    let sorters: Vec<Box<dyn Sorter<Tagged>>> = vec![Box::new(radix::LsdBytes), Box::new(radix::Msd)];
+
+    for sorter in sorters {
+        let stable = sorter.is_stable();
+        run(&Subject::new(sorter.name(), stable, move |values: &mut [Keyed]| {
+            let mut strings: Vec<Tagged> = values
+                .iter()
+                .map(|value| Tagged { string: to_string(value.key), index: value.index })
+                .collect();
+            sorter.sort(&mut strings);
+            for (value, tagged) in values.iter_mut().zip(&strings) {
+                *value = Keyed { key: from_string(&tagged.string), index: tagged.index };
+            }
+        }));
+    }
+}
+
+#[test]
+fn test_bucket_sort() {
+    // Floats can't carry a position, so a trace of the sort shows which record
+    // each of them came from
+    run(&Subject::new("Bucket Sort", BucketSort.is_stable(), |values: &mut [Keyed]| {
+        let mut floats: Vec<f64> = values.iter().map(|value| f64::from(value.key) / 256.0).collect();
+        let mut trace = Trace::new();
+        BucketSort.sort_instrumented(&mut floats, &mut trace);
+
+        let sorted: Vec<Keyed> = trace.origins(values.len()).into_iter().map(|i| values[i]).collect();
+        for ((value, record), float) in values.iter_mut().zip(sorted).zip(&floats) {
+            assert_eq!(record.key, (float * 256.0) as u8, "the trace disagrees with the sort");
+            *value = record;
+        }
+    }));
+}
+
+#[test]
+fn test_external_sort() {
+    // The sort is documented as stable
+    run(&Subject::new("External Sort", true, |values: &mut [Keyed]| {
+        let mut input = Vec::new();
+        for value in values.iter() {
+            KeyedCodec.encode(value, &mut input).unwrap();
+        }
+
+        let mut output = Vec::new();
+        ExternalSort::new(KeyedCodec, 7).fan_in(3).sort(&input[..], &mut output).unwrap();
+
+        let mut reader = &output[..];
+        for value in values.iter_mut() {
+            *value = KeyedCodec.decode(&mut reader).unwrap().unwrap();
+        }
+    }).max_len(100));
+}
+
+#[test]
+fn test_parallel_merge_sort() {
+    run(&Subject::new("Merge Sort (parallel)", true, |values: &mut [Keyed]| merge::par_merge_sort(values, 4)));
+}
+
+#[test]
+fn test_partial_sorts() {
+    run(&Subject::new("Partial Sort", false, |values: &mut [Keyed]| {
+        let len = values.len();
+        partial::partial_sort(values, len);
+    }));
+
+    run(&Subject::new("Sorted Iterator", false, |values: &mut [Keyed]| {
+        let sorted: Vec<Keyed> = partial::sorted_iter(values.to_vec()).collect();
+        values.copy_from_slice(&sorted);
+    }));
+}
+
+#[test]
+fn test_networks() {
+    run(&Subject::new("Odd-even Merge Network", false, |values: &mut [Keyed]| {
+        Network::odd_even_merge(values.len()).apply(values);
+    }).max_len(16));
+
+    run(&Subject::new("Bitonic Network", false, |values: &mut [Keyed]| {
+        Network::bitonic(values.len()).apply(values);
+    }).max_len(16));
+}
+
+#[test]
+fn test_shrink() {
+    // Swaps the last two elements whenever there are at least four, and any
+    // key is at least 10
+    let broken = Subject::new("Broken", true, |values: &mut [Keyed]| {
+        values.sort();
+        if values.len() >= 4 && values.iter().any(|value| value.key >= 10) {
+            let len = values.len();
+            values.swap(len - 2, len - 1);
+        }
+    });
+
+    let minimal = shrink(vec![200, 3, 17, 5, 80, 12, 9], |keys| check(&broken, keys).is_err());
+    assert_eq!(minimal, [0, 0, 10, 0]);
+}
+
+#[test]
+fn test_string_keys() {
+    for key in 0..=255 {
+        assert_eq!(from_string(&to_string(key)), key);
+        if key > 0 {
+            assert!(to_string(key - 1) < to_string(key), "{}", key);
+        }
+    }
+}